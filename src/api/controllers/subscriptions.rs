@@ -1,4 +1,6 @@
 use crate::api::controllers::macros::*;
+#[cfg(feature = "formats")]
+use crate::api::types::formats::{ClientTypeHealth, SubscriptionConfig, SubscriptionHealthError, SubscriptionHealthReport};
 use crate::api::types::subscriptions::*;
 use uuid::Uuid;
 
@@ -20,20 +22,87 @@ impl SubscriptionsController {
         let response = api_request_common!(self, get, url.clone(), None::<()>)?;
         self.handle_text_response(response, url).await
     }
+
+    #[cfg(feature = "formats")]
+    #[doc = "GET /api/sub/{}/{} - SubscriptionsController (parsed into a typed config)"]
+    pub async fn get_config_by_client_type(&self, short_uuid: String, client_type: SubscriptionClientType) -> Result<SubscriptionConfig, crate::ApiError> {
+        let url = format!("{}/api/sub/{}/{}", self.client.base_url(), short_uuid, client_type);
+        let response = api_request_common!(self, get, url.clone(), None::<()>)?;
+        let status_code = response.status().as_u16();
+        let body = self.handle_text_response(response, url.clone()).await?;
+
+        SubscriptionConfig::parse(&client_type, &body).map_err(|e| crate::ApiError {
+            status_code,
+            url,
+            request_body: None,
+            response_body: body,
+            response_headers: std::collections::HashMap::new(),
+            timestamp: None,
+            path: None,
+            message: Some(format!("Failed to deserialize response: {}", e)),
+            error_code: None,
+            error: None,
+        })
+    }
+
+    /// Fetches and parses the subscription for every [`SubscriptionClientType`] and checks that each one contains
+    /// every served host of [`get_raw_by_short_uuid`](Self::get_raw_by_short_uuid), matched by remark.
+    ///
+    /// Only fetching the raw subscription can fail; per-format failures are recorded in the report.
+    #[cfg(feature = "formats")]
+    pub async fn check_health(&self, short_uuid: String) -> Result<SubscriptionHealthReport, crate::ApiError> {
+        let raw = self.get_raw_by_short_uuid(short_uuid.clone(), None).await?.response;
+        let expected_hosts: Vec<String> =
+            raw.raw_hosts.iter().filter(|host| host.is_served()).map(|host| crate::tools::substitute_placeholders(host.remark.as_deref().unwrap_or_default(), &raw)).collect();
+
+        let mut client_types = Vec::new();
+        for client_type in SubscriptionClientType::ALL {
+            let mut health = ClientTypeHealth {
+                client_type: client_type.clone(),
+                error: None,
+                proxies: Vec::new(),
+                missing_hosts: Vec::new(),
+            };
+            match self.get_by_client_type(short_uuid.clone(), client_type.clone()).await {
+                Err(e) => health.error = Some(SubscriptionHealthError::Request(e.to_string())),
+                Ok(body) => match SubscriptionConfig::parse(&client_type, &body) {
+                    Err(e) => health.error = Some(SubscriptionHealthError::Parse(e.to_string())),
+                    Ok(config) => {
+                        health.proxies = config.proxy_names().into_iter().map(String::from).collect();
+                        health.missing_hosts = expected_hosts.iter().filter(|host| !health.proxies.contains(host)).cloned().collect();
+                    }
+                },
+            }
+            client_types.push(health);
+        }
+
+        Ok(SubscriptionHealthReport {
+            short_uuid,
+            expected_hosts,
+            client_types,
+        })
+    }
 }
 
 impl SubscriptionsController {
     #[doc = "GET /api/sub/outline/{}/{}/{} - SubscriptionsController"]
-    pub async fn get_with_type(&self, short_uuid: String, encoded_tag: String, subscription_type: Option<String>) -> Result<String, crate::ApiError> {
-        let subscription_type = subscription_type.unwrap_or_else(|| "ss".to_string());
+    pub async fn get_with_type(&self, short_uuid: String, encoded_tag: String, subscription_type: OutlineSubscriptionType) -> Result<OutlineKey, crate::ApiError> {
         let url = format!("{}/api/sub/outline/{}/{}/{}", self.client.base_url(), short_uuid, subscription_type, encoded_tag);
         let response = api_request_common!(self, get, url.clone(), None::<()>)?;
-        self.handle_text_response(response, url).await
+        self.handle_response(response, url).await
+    }
+
+    /// `ssconf://` URL of this panel for `short_uuid` and the unencoded host `tag`, see [`outline_ssconf_url`].
+    pub fn outline_ssconf_url(&self, short_uuid: &str, tag: &str, subscription_type: OutlineSubscriptionType) -> String {
+        outline_ssconf_url(self.client.base_url(), short_uuid, subscription_type, tag)
     }
 
     #[deprecated(note = "Use get_with_type")]
     pub async fn get_subscription_with_type(&self, short_uuid: String, encoded_tag: String, subscription_type: Option<String>) -> Result<String, crate::ApiError> {
-        self.get_with_type(short_uuid, encoded_tag, subscription_type).await
+        let subscription_type = subscription_type.unwrap_or_else(|| OutlineSubscriptionType::default().to_string());
+        let url = format!("{}/api/sub/outline/{}/{}/{}", self.client.base_url(), short_uuid, subscription_type, encoded_tag);
+        let response = api_request_common!(self, get, url.clone(), None::<()>)?;
+        self.handle_text_response(response, url).await
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::types::CountryCode;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -86,7 +87,7 @@ pub struct BillingHistorySummary {
 pub struct BillingNodeSummary {
     pub node_uuid: String,
     pub name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -209,7 +210,7 @@ pub struct BillingNodeProviderInfo {
 pub struct BillingNodeInfo {
     pub uuid: Uuid,
     pub name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -217,7 +218,7 @@ pub struct BillingNodeInfo {
 pub struct AvailableBillingNodeDto {
     pub uuid: Uuid,
     pub name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -1,3 +1,4 @@
+use crate::types::{CountryCode, XrayConfig};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -40,6 +41,8 @@ pub struct GetConfigProfileByUuidResponseDto {
     pub response: ConfigProfile,
 }
 
+/// The computed config is built from the stored one and stays raw like every [`ConfigProfile`] response; use
+/// [`ConfigProfile::xray_config`] for the typed model.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GetComputedConfigProfileByUuidResponseDto {
     pub response: ConfigProfile,
@@ -59,7 +62,7 @@ pub struct DeleteConfigProfileResponseData {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CreateConfigProfileRequestDto {
     pub name: String,
-    pub config: serde_json::Value,
+    pub config: XrayConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -70,7 +73,7 @@ pub struct CreateConfigProfileResponseDto {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UpdateConfigProfileRequestDto {
     pub uuid: Uuid,
-    pub config: serde_json::Value,
+    pub config: XrayConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -83,6 +86,8 @@ pub struct UpdateConfigProfileResponseDto {
 pub struct ConfigProfile {
     pub uuid: Uuid,
     pub name: String,
+    /// Raw config, so that responses parse whatever the panel stores, including configs the typed model rejects. Only
+    /// request DTOs take [`XrayConfig`]; see [`ConfigProfile::xray_config`].
     pub config: serde_json::Value,
     pub inbounds: Vec<Inbound>,
     pub nodes: Vec<Node>,
@@ -90,6 +95,13 @@ pub struct ConfigProfile {
     pub updated_at: String,
 }
 
+impl ConfigProfile {
+    /// Parses `config` into the typed model.
+    pub fn xray_config(&self) -> Result<XrayConfig, serde_json::Error> {
+        serde_json::from_value(self.config.clone())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Inbound {
@@ -109,5 +121,5 @@ pub struct Inbound {
 pub struct Node {
     pub uuid: Uuid,
     pub name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
 }
@@ -0,0 +1,52 @@
+use crate::api::types::subscriptions::SubscriptionClientType;
+use serde::Serialize;
+
+/// Result of `SubscriptionsController::check_health`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SubscriptionHealthReport {
+    pub short_uuid: String,
+    /// Remarks of the enabled, non-hidden hosts every format is expected to contain.
+    pub expected_hosts: Vec<String>,
+    pub client_types: Vec<ClientTypeHealth>,
+}
+
+impl SubscriptionHealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.client_types.iter().all(ClientTypeHealth::is_healthy)
+    }
+
+    pub fn unhealthy(&self) -> impl Iterator<Item = &ClientTypeHealth> {
+        self.client_types.iter().filter(|c| !c.is_healthy())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ClientTypeHealth {
+    pub client_type: SubscriptionClientType,
+    /// Set if the subscription could not be fetched or parsed; the other fields are empty then.
+    pub error: Option<SubscriptionHealthError>,
+    /// Proxy names found in the config, see `SubscriptionConfig::proxy_names`.
+    pub proxies: Vec<String>,
+    /// Expected hosts whose remark is not among `proxies`.
+    pub missing_hosts: Vec<String>,
+}
+
+impl ClientTypeHealth {
+    /// Fetched and parsed fine but contains no proxies.
+    pub fn is_empty(&self) -> bool {
+        self.error.is_none() && self.proxies.is_empty()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none() && !self.proxies.is_empty() && self.missing_hosts.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum SubscriptionHealthError {
+    /// The request failed or the panel answered with an error status.
+    Request(String),
+    /// The body is not a valid config for the client type.
+    Parse(String),
+}
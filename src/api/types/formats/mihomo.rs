@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{fmt, str::FromStr};
+
+/// Mihomo / Clash / Stash YAML config. Keys without a typed field are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct MihomoConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proxies: Vec<MihomoProxy>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proxy_groups: Vec<MihomoProxyGroup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<MihomoRule>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct MihomoProxy {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub proxy_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct MihomoProxyGroup {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub group_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proxies: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A single entry of the `rules` list, e.g. `DOMAIN-SUFFIX,google.com,PROXY,no-resolve`.
+///
+/// Logical rules (`AND`, `OR`, `NOT`, `SUB-RULE`) keep their parenthesised payload verbatim.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct MihomoRule {
+    pub rule_type: String,
+    pub payload: Option<String>,
+    pub target: String,
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MihomoRuleParseError(pub String);
+
+impl fmt::Display for MihomoRuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid mihomo rule: {}", self.0)
+    }
+}
+
+impl std::error::Error for MihomoRuleParseError {}
+
+impl MihomoRule {
+    pub fn new(rule_type: impl Into<String>, payload: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            rule_type: rule_type.into(),
+            payload: Some(payload.into()),
+            target: target.into(),
+            options: Vec::new(),
+        }
+    }
+
+    /// `MATCH,<target>` - the final catch-all rule.
+    pub fn match_all(target: impl Into<String>) -> Self {
+        Self {
+            rule_type: "MATCH".to_string(),
+            payload: None,
+            target: target.into(),
+            options: Vec::new(),
+        }
+    }
+
+    pub fn with_option(mut self, option: impl Into<String>) -> Self {
+        self.options.push(option.into());
+        self
+    }
+
+    pub fn is_logical(&self) -> bool {
+        matches!(self.rule_type.as_str(), "AND" | "OR" | "NOT" | "SUB-RULE")
+    }
+}
+
+impl FromStr for MihomoRule {
+    type Err = MihomoRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || MihomoRuleParseError(s.to_string());
+        let (rule_type, rest) = s.split_once(',').ok_or_else(err)?;
+        let rule_type = rule_type.trim().to_string();
+
+        if rule_type == "MATCH" {
+            let mut parts = rest.split(',').map(|p| p.trim().to_string());
+            let target = parts.next().filter(|t| !t.is_empty()).ok_or_else(err)?;
+            return Ok(Self {
+                rule_type,
+                payload: None,
+                target,
+                options: parts.collect(),
+            });
+        }
+
+        let (payload, rest) = if matches!(rule_type.as_str(), "AND" | "OR" | "NOT" | "SUB-RULE") {
+            let rest = rest.trim_start();
+            let mut depth = 0usize;
+            let mut end = None;
+            for (i, c) in rest.char_indices() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth = depth.checked_sub(1).ok_or_else(err)?;
+                        if depth == 0 {
+                            end = Some(i + 1);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let end = end.ok_or_else(err)?;
+            let tail = rest[end..].trim_start().strip_prefix(',').ok_or_else(err)?;
+            (rest[..end].to_string(), tail)
+        } else {
+            rest.split_once(',').map(|(payload, rest)| (payload.trim().to_string(), rest)).ok_or_else(err)?
+        };
+
+        let mut parts = rest.split(',').map(|p| p.trim().to_string());
+        let target = parts.next().filter(|t| !t.is_empty()).ok_or_else(err)?;
+
+        Ok(Self {
+            rule_type,
+            payload: Some(payload),
+            target,
+            options: parts.collect(),
+        })
+    }
+}
+
+impl fmt::Display for MihomoRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rule_type)?;
+        if let Some(payload) = &self.payload {
+            write!(f, ",{payload}")?;
+        }
+        write!(f, ",{}", self.target)?;
+        for option in &self.options {
+            write!(f, ",{option}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for MihomoRule {
+    type Error = MihomoRuleParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<MihomoRule> for String {
+    fn from(rule: MihomoRule) -> Self {
+        rule.to_string()
+    }
+}
+
+impl MihomoConfig {
+    pub fn proxy(&self, name: &str) -> Option<&MihomoProxy> {
+        self.proxies.iter().find(|p| p.name == name)
+    }
+
+    pub fn proxy_group(&self, name: &str) -> Option<&MihomoProxyGroup> {
+        self.proxy_groups.iter().find(|g| g.name == name)
+    }
+
+    pub fn proxy_group_mut(&mut self, name: &str) -> Option<&mut MihomoProxyGroup> {
+        self.proxy_groups.iter_mut().find(|g| g.name == name)
+    }
+
+    /// Renames a proxy or proxy group and rewrites every group member and rule target that points at it.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        let mut found = false;
+        for proxy in self.proxies.iter_mut().filter(|p| p.name == from) {
+            proxy.name = to.to_string();
+            found = true;
+        }
+        for group in self.proxy_groups.iter_mut() {
+            if group.name == from {
+                group.name = to.to_string();
+                found = true;
+            }
+            for member in group.proxies.iter_mut().filter(|m| *m == from) {
+                *member = to.to_string();
+            }
+        }
+        if found {
+            for rule in self.rules.iter_mut().filter(|r| r.target == from) {
+                rule.target = to.to_string();
+            }
+        }
+        found
+    }
+
+    /// Inserts a rule in front of the trailing `MATCH` rule, or at the end if there is none.
+    pub fn insert_rule(&mut self, rule: MihomoRule) {
+        let index = self.rules.iter().position(|r| r.rule_type == "MATCH").unwrap_or(self.rules.len());
+        self.rules.insert(index, rule);
+    }
+
+    pub fn prepend_rule(&mut self, rule: MihomoRule) {
+        self.rules.insert(0, rule);
+    }
+}
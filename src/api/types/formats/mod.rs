@@ -0,0 +1,87 @@
+pub mod health;
+pub mod mihomo;
+pub mod singbox;
+pub mod xray;
+
+pub use health::*;
+pub use mihomo::*;
+pub use singbox::*;
+pub use xray::*;
+
+use crate::api::types::subscriptions::SubscriptionClientType;
+use std::fmt;
+
+/// Typed body of `GET /api/sub/{short}/{clientType}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionConfig {
+    /// `mihomo`, `clash` and `stash` (YAML)
+    Mihomo(MihomoConfig),
+    /// `singbox` and `singbox-legacy` (JSON)
+    SingBox(SingBoxConfig),
+    /// `json` and `v2ray-Json` (JSON array)
+    XrayJson(XrayJsonSubscription),
+}
+
+#[derive(Debug)]
+pub enum SubscriptionFormatError {
+    Yaml(serde_yaml::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for SubscriptionFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml(e) => write!(f, "YAML error: {e}"),
+            Self::Json(e) => write!(f, "JSON error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SubscriptionFormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Yaml(e) => Some(e),
+            Self::Json(e) => Some(e),
+        }
+    }
+}
+
+impl From<serde_yaml::Error> for SubscriptionFormatError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Yaml(e)
+    }
+}
+
+impl From<serde_json::Error> for SubscriptionFormatError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl SubscriptionConfig {
+    pub fn parse(client_type: &SubscriptionClientType, body: &str) -> Result<Self, SubscriptionFormatError> {
+        Ok(match client_type {
+            SubscriptionClientType::Mihomo | SubscriptionClientType::Clash | SubscriptionClientType::Stash => Self::Mihomo(serde_yaml::from_str(body)?),
+            SubscriptionClientType::SingBox | SubscriptionClientType::SingBoxLegacy => Self::SingBox(serde_json::from_str(body)?),
+            SubscriptionClientType::Json | SubscriptionClientType::V2RayJson => Self::XrayJson(serde_json::from_str(body)?),
+        })
+    }
+
+    /// Serializes the config back into the text format it was parsed from.
+    pub fn to_text(&self) -> Result<String, SubscriptionFormatError> {
+        Ok(match self {
+            Self::Mihomo(config) => serde_yaml::to_string(config)?,
+            Self::SingBox(config) => serde_json::to_string_pretty(config)?,
+            Self::XrayJson(configs) => serde_json::to_string_pretty(configs)?,
+        })
+    }
+
+    /// Names of the proxy entries: Mihomo `proxies`, sing-box outbounds with a `server`, or the `remarks` of each Xray config.
+    pub fn proxy_names(&self) -> Vec<&str> {
+        match self {
+            Self::Mihomo(config) => config.proxies.iter().map(|p| p.name.as_str()).collect(),
+            Self::SingBox(config) => config.outbounds.iter().filter(|o| o.server.is_some()).map(|o| o.tag.as_str()).collect(),
+            Self::XrayJson(configs) => configs.iter().filter_map(|c| c.remarks.as_deref()).collect(),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// sing-box JSON config. Keys without a typed field (`log`, `dns`, `inbounds`, `experimental`, ...) are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SingBoxConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outbounds: Vec<SingBoxOutbound>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<SingBoxRoute>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SingBoxOutbound {
+    #[serde(rename = "type")]
+    pub outbound_type: String,
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_port: Option<u16>,
+    /// Members of `selector` and `urltest` outbounds.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outbounds: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SingBoxRoute {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<SingBoxRouteRule>,
+    #[serde(rename = "final", default, skip_serializing_if = "Option::is_none")]
+    pub final_outbound: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A route rule. Matchers (`domain_suffix`, `ip_cidr`, `rule_set`, ...) are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SingBoxRouteRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl SingBoxConfig {
+    pub fn outbound(&self, tag: &str) -> Option<&SingBoxOutbound> {
+        self.outbounds.iter().find(|o| o.tag == tag)
+    }
+
+    pub fn outbound_mut(&mut self, tag: &str) -> Option<&mut SingBoxOutbound> {
+        self.outbounds.iter_mut().find(|o| o.tag == tag)
+    }
+
+    /// Renames an outbound and rewrites selector members, route rules and `route.final` that point at it.
+    pub fn rename_outbound(&mut self, from: &str, to: &str) -> bool {
+        let mut found = false;
+        for outbound in self.outbounds.iter_mut() {
+            if outbound.tag == from {
+                outbound.tag = to.to_string();
+                found = true;
+            }
+            for member in outbound.outbounds.iter_mut().filter(|m| *m == from) {
+                *member = to.to_string();
+            }
+        }
+        if let Some(route) = self.route.as_mut().filter(|_| found) {
+            for rule in route.rules.iter_mut().filter(|r| r.outbound.as_deref() == Some(from)) {
+                rule.outbound = Some(to.to_string());
+            }
+            if route.final_outbound.as_deref() == Some(from) {
+                route.final_outbound = Some(to.to_string());
+            }
+        }
+        found
+    }
+
+    pub fn prepend_route_rule(&mut self, rule: SingBoxRouteRule) {
+        self.route.get_or_insert_with(SingBoxRoute::default).rules.insert(0, rule);
+    }
+}
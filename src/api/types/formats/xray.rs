@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The `json` / `v2ray-json` subscription body: one full Xray client config per host.
+pub type XrayJsonSubscription = Vec<XrayJsonConfig>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayJsonConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remarks: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outbounds: Vec<XrayJsonOutbound>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<XrayJsonRouting>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayJsonOutbound {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub protocol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_settings: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayJsonRouting {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<XrayJsonRoutingRule>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A routing rule. Matchers (`domain`, `ip`, `port`, `network`, ...) are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayJsonRoutingRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balancer_tag: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl XrayJsonConfig {
+    pub fn outbound(&self, tag: &str) -> Option<&XrayJsonOutbound> {
+        self.outbounds.iter().find(|o| o.tag.as_deref() == Some(tag))
+    }
+
+    pub fn outbound_mut(&mut self, tag: &str) -> Option<&mut XrayJsonOutbound> {
+        self.outbounds.iter_mut().find(|o| o.tag.as_deref() == Some(tag))
+    }
+
+    pub fn prepend_routing_rule(&mut self, rule: XrayJsonRoutingRule) {
+        self.routing.get_or_insert_with(XrayJsonRouting::default).rules.insert(0, rule);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::api::types::external_squads::ExternalSquadSubscriptionSettings;
+use crate::api::types::subscriptions::SubscriptionSettings;
+use crate::diagnostics::{pointer_token, Diagnostic};
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+const ROUTING_LINK_PREFIX: &str = "happ://routing/";
+
+/// Longest announce text Happ displays.
+pub const HAPP_ANNOUNCE_MAX_LEN: usize = 200;
+
+/// Happ routing profile, the JSON wrapped in a `happ://routing/{add,onadd}/<base64>` deep link.
+///
+/// Happ writes booleans as `"true"` / `"false"` strings; both forms are accepted when decoding.
+/// Keys without a typed field are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct HappRoutingProfile {
+    pub name: String,
+    #[serde(with = "string_bool", default)]
+    pub global_proxy: bool,
+    #[serde(rename = "RemoteDNSType", default, skip_serializing_if = "Option::is_none")]
+    pub remote_dns_type: Option<HappDnsType>,
+    #[serde(rename = "RemoteDNSDomain", default, skip_serializing_if = "Option::is_none")]
+    pub remote_dns_domain: Option<String>,
+    #[serde(rename = "RemoteDNSIP", default, skip_serializing_if = "Option::is_none")]
+    pub remote_dns_ip: Option<String>,
+    #[serde(rename = "DomesticDNSType", default, skip_serializing_if = "Option::is_none")]
+    pub domestic_dns_type: Option<HappDnsType>,
+    #[serde(rename = "DomesticDNSDomain", default, skip_serializing_if = "Option::is_none")]
+    pub domestic_dns_domain: Option<String>,
+    #[serde(rename = "DomesticDNSIP", default, skip_serializing_if = "Option::is_none")]
+    pub domestic_dns_ip: Option<String>,
+    #[serde(rename = "Geoipurl", default, skip_serializing_if = "Option::is_none")]
+    pub geoip_url: Option<String>,
+    #[serde(rename = "Geositeurl", default, skip_serializing_if = "Option::is_none")]
+    pub geosite_url: Option<String>,
+    /// Unix timestamp; Happ re-imports a profile with the same name only if this changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dns_hosts: BTreeMap<String, String>,
+    #[serde(default)]
+    pub direct_sites: Vec<String>,
+    #[serde(default)]
+    pub direct_ip: Vec<String>,
+    #[serde(default)]
+    pub proxy_sites: Vec<String>,
+    #[serde(default)]
+    pub proxy_ip: Vec<String>,
+    #[serde(default)]
+    pub block_sites: Vec<String>,
+    #[serde(default)]
+    pub block_ip: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_strategy: Option<String>,
+    #[serde(rename = "FakeDNS", with = "string_bool", default)]
+    pub fake_dns: bool,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HappDnsType {
+    /// DNS over HTTPS
+    DoH,
+    /// Plain DNS over UDP
+    DoU,
+}
+
+/// Action of a routing deep link.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HappRoutingLinkMode {
+    /// Adds the profile without switching to it.
+    Add,
+    /// Adds the profile and makes it active.
+    #[default]
+    OnAdd,
+}
+
+impl fmt::Display for HappRoutingLinkMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = serde_plain::to_string(self).unwrap();
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug)]
+pub enum HappError {
+    /// Not a `happ://routing/add/...` or `happ://routing/onadd/...` link.
+    InvalidRoutingLink(String),
+    /// The announce is longer than [`HAPP_ANNOUNCE_MAX_LEN`] characters.
+    AnnounceTooLong(usize),
+    Base64(base64::DecodeError),
+    Utf8(std::string::FromUtf8Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for HappError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRoutingLink(link) => write!(f, "invalid Happ routing link: {link}"),
+            Self::AnnounceTooLong(len) => write!(f, "Happ announce is {len} characters long, at most {HAPP_ANNOUNCE_MAX_LEN} are allowed"),
+            Self::Base64(e) => write!(f, "invalid base64: {e}"),
+            Self::Utf8(e) => write!(f, "invalid UTF-8: {e}"),
+            Self::Json(e) => write!(f, "invalid routing profile JSON: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for HappError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidRoutingLink(_) | Self::AnnounceTooLong(_) => None,
+            Self::Base64(e) => Some(e),
+            Self::Utf8(e) => Some(e),
+            Self::Json(e) => Some(e),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for HappError {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Base64(e)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for HappError {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        Self::Utf8(e)
+    }
+}
+
+impl From<serde_json::Error> for HappError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl HappRoutingProfile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Base64 of the compact profile JSON.
+    pub fn encode(&self) -> Result<String, HappError> {
+        Ok(base64::engine::general_purpose::STANDARD.encode(serde_json::to_vec(self)?))
+    }
+
+    /// Decodes the base64 payload of a deep link (standard or URL-safe alphabet, padding optional).
+    pub fn decode(payload: &str) -> Result<Self, HappError> {
+        let payload = payload.trim().trim_end_matches('=');
+        let bytes = base64::engine::general_purpose::STANDARD_NO_PAD.decode(payload).or_else(|_| base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload))?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// `happ://routing/<mode>/<base64>`, the value stored in `happ_routing`.
+    pub fn to_deep_link(&self, mode: HappRoutingLinkMode) -> Result<String, HappError> {
+        Ok(format!("{ROUTING_LINK_PREFIX}{mode}/{}", self.encode()?))
+    }
+
+    pub fn from_deep_link(link: &str) -> Result<Self, HappError> {
+        Self::parse_deep_link(link).map(|(_, profile)| profile)
+    }
+
+    pub fn parse_deep_link(link: &str) -> Result<(HappRoutingLinkMode, Self), HappError> {
+        let invalid = || HappError::InvalidRoutingLink(link.to_string());
+        let (mode, payload) = link.trim().strip_prefix(ROUTING_LINK_PREFIX).and_then(|rest| rest.split_once('/')).ok_or_else(invalid)?;
+        let mode = HappRoutingLinkMode::from_str(mode).map_err(|_| invalid())?;
+        Ok((mode, Self::decode(payload)?))
+    }
+
+    /// Sets `last_updated` to the current time so clients pick up the new version.
+    pub fn touch(&mut self) {
+        self.last_updated = Some(chrono::Utc::now().timestamp().to_string());
+    }
+
+    /// Checks DNS settings, geo file URLs and IP rules. Pointers refer to the profile JSON.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        if self.name.trim().is_empty() {
+            diagnostics.push(Diagnostic::error("empty-name", "/Name", "routing profile name must not be empty"));
+        }
+
+        let dns = [
+            ("Remote", self.remote_dns_type, &self.remote_dns_domain, &self.remote_dns_ip),
+            ("Domestic", self.domestic_dns_type, &self.domestic_dns_domain, &self.domestic_dns_ip),
+        ];
+        for (prefix, dns_type, domain, ip) in dns {
+            if let Some(ip) = ip.as_deref().filter(|ip| !ip.is_empty() && ip.parse::<IpAddr>().is_err()) {
+                diagnostics.push(Diagnostic::error("invalid-dns-ip", format!("/{prefix}DNSIP"), format!("'{ip}' is not an IP address")));
+            }
+            let domain = domain.as_deref().unwrap_or_default();
+            if dns_type == Some(HappDnsType::DoH) && !domain.starts_with("https://") {
+                diagnostics.push(Diagnostic::error("invalid-doh-url", format!("/{prefix}DNSDomain"), "DoH resolvers need an https:// URL"));
+            }
+        }
+
+        for (key, url) in [("Geoipurl", &self.geoip_url), ("Geositeurl", &self.geosite_url)] {
+            if let Some(url) = url.as_deref().filter(|u| !u.is_empty() && !u.starts_with("https://") && !u.starts_with("http://")) {
+                diagnostics.push(Diagnostic::error("invalid-geo-url", format!("/{key}"), format!("'{url}' is not an http(s) URL")));
+            }
+        }
+
+        for (host, ip) in &self.dns_hosts {
+            if ip.parse::<IpAddr>().is_err() {
+                diagnostics.push(Diagnostic::error("invalid-dns-host", format!("/DnsHosts/{}", pointer_token(host)), format!("'{ip}' is not an IP address")));
+            }
+        }
+
+        for (key, entries) in [("DirectIp", &self.direct_ip), ("ProxyIp", &self.proxy_ip), ("BlockIp", &self.block_ip)] {
+            for (index, entry) in entries.iter().enumerate() {
+                if !is_ip_rule(entry) {
+                    diagnostics.push(Diagnostic::error("invalid-ip-rule", format!("/{key}/{index}"), format!("'{entry}' is neither an IP, a CIDR nor a geoip: rule")));
+                }
+            }
+        }
+
+        for (key, entries) in [("DirectSites", &self.direct_sites), ("ProxySites", &self.proxy_sites), ("BlockSites", &self.block_sites)] {
+            for (index, entry) in entries.iter().enumerate() {
+                if entry.trim().is_empty() {
+                    diagnostics.push(Diagnostic::error("empty-site-rule", format!("/{key}/{index}"), "site rule must not be empty"));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+impl FromStr for HappRoutingLinkMode {
+    type Err = HappError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_plain::from_str(s).map_err(|_| HappError::InvalidRoutingLink(s.to_string()))
+    }
+}
+
+impl FromStr for HappRoutingProfile {
+    type Err = HappError;
+
+    /// Accepts a deep link, a bare base64 payload or plain JSON.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with(ROUTING_LINK_PREFIX) {
+            Self::from_deep_link(s)
+        } else if s.starts_with('{') {
+            Ok(serde_json::from_str(s)?)
+        } else {
+            Self::decode(s)
+        }
+    }
+}
+
+fn is_ip_rule(entry: &str) -> bool {
+    if entry.starts_with("geoip:") || entry.starts_with("ext:") {
+        return true;
+    }
+    match entry.split_once('/') {
+        Some((ip, prefix)) => match (ip.parse::<IpAddr>(), prefix.parse::<u8>()) {
+            (Ok(IpAddr::V4(_)), Ok(prefix)) => prefix <= 32,
+            (Ok(IpAddr::V6(_)), Ok(prefix)) => prefix <= 128,
+            _ => false,
+        },
+        None => entry.parse::<IpAddr>().is_ok(),
+    }
+}
+
+/// Value of the `announce` subscription header Happ reads (`base64:<text>`).
+pub fn encode_happ_announce(text: &str) -> Result<String, HappError> {
+    let len = text.chars().count();
+    if len > HAPP_ANNOUNCE_MAX_LEN {
+        return Err(HappError::AnnounceTooLong(len));
+    }
+    Ok(format!("base64:{}", base64::engine::general_purpose::STANDARD.encode(text)))
+}
+
+/// Inverse of [`encode_happ_announce`]; values without the `base64:` prefix are returned as is.
+pub fn decode_happ_announce(value: &str) -> Result<String, HappError> {
+    match value.strip_prefix("base64:") {
+        Some(encoded) => Ok(String::from_utf8(base64::engine::general_purpose::STANDARD.decode(encoded.trim())?)?),
+        None => Ok(value.to_string()),
+    }
+}
+
+impl SubscriptionSettings {
+    /// Parses `happ_routing`; `None` if it is unset or empty.
+    pub fn happ_routing_profile(&self) -> Option<Result<HappRoutingProfile, HappError>> {
+        self.happ_routing.as_deref().filter(|r| !r.trim().is_empty()).map(HappRoutingProfile::from_str)
+    }
+}
+
+impl ExternalSquadSubscriptionSettings {
+    /// Parses `happ_routing`; `None` if it is unset or empty.
+    pub fn happ_routing_profile(&self) -> Option<Result<HappRoutingProfile, HappError>> {
+        self.happ_routing.as_deref().filter(|r| !r.trim().is_empty()).map(HappRoutingProfile::from_str)
+    }
+}
+
+mod string_bool {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(if *value {
+            "true"
+        } else {
+            "false"
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrBool {
+            Bool(bool),
+            String(String),
+        }
+
+        match StringOrBool::deserialize(deserializer)? {
+            StringOrBool::Bool(value) => Ok(value),
+            StringOrBool::String(value) => match value.to_ascii_lowercase().as_str() {
+                "true" | "1" => Ok(true),
+                "false" | "0" | "" => Ok(false),
+                other => Err(serde::de::Error::custom(format!("expected a boolean, got '{other}'"))),
+            },
+        }
+    }
+}
@@ -1,5 +1,7 @@
+use crate::types::{MuxParams, SockoptParams, XHttpExtraParams};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -25,6 +27,17 @@ impl fmt::Display for AlpnType {
     }
 }
 
+impl FromStr for AlpnType {
+    type Err = ParseHostEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_plain::from_str(s).map_err(|_| ParseHostEnumError {
+            type_name: "AlpnType",
+            value: s.to_string(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum FingerprintType {
@@ -46,6 +59,17 @@ impl fmt::Display for FingerprintType {
     }
 }
 
+impl FromStr for FingerprintType {
+    type Err = ParseHostEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_plain::from_str(s).map_err(|_| ParseHostEnumError {
+            type_name: "FingerprintType",
+            value: s.to_string(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum SecurityLayerType {
@@ -62,6 +86,32 @@ impl fmt::Display for SecurityLayerType {
     }
 }
 
+impl FromStr for SecurityLayerType {
+    type Err = ParseHostEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_plain::from_str(s).map_err(|_| ParseHostEnumError {
+            type_name: "SecurityLayerType",
+            value: s.to_string(),
+        })
+    }
+}
+
+/// Returned by the `FromStr` impls of [`AlpnType`], [`FingerprintType`] and [`SecurityLayerType`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHostEnumError {
+    pub type_name: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for ParseHostEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {}: {}", self.type_name, self.value)
+    }
+}
+
+impl std::error::Error for ParseHostEnumError {}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct HostInboundRequest {
@@ -110,8 +160,11 @@ pub struct HostDto {
     pub fingerprint: Option<String>,
     pub is_disabled: bool,
     pub security_layer: SecurityLayerType,
+    /// Raw xHTTP `extra`; see [`HostDto::x_http_extra`].
     pub x_http_extra_params: Option<serde_json::Value>,
+    /// Raw `mux`; see [`HostDto::mux`].
     pub mux_params: Option<serde_json::Value>,
+    /// Raw `sockopt`; see [`HostDto::sockopt`].
     pub sockopt_params: Option<serde_json::Value>,
     pub inbound: HostInboundDto,
     pub server_description: Option<String>,
@@ -131,6 +184,33 @@ pub struct HostDto {
     pub nodes: Vec<Uuid>,
 }
 
+impl HostDto {
+    /// `alpn` as [`AlpnType`]; `None` if unset or not a known value.
+    pub fn alpn_type(&self) -> Option<AlpnType> {
+        self.alpn.as_deref().and_then(|alpn| alpn.parse().ok())
+    }
+
+    /// `fingerprint` as [`FingerprintType`]; `None` if unset or not a known value.
+    pub fn fingerprint_type(&self) -> Option<FingerprintType> {
+        self.fingerprint.as_deref().and_then(|fingerprint| fingerprint.parse().ok())
+    }
+
+    /// `x_http_extra_params` as [`XHttpExtraParams`]; `Ok(None)` if unset.
+    pub fn x_http_extra(&self) -> Result<Option<XHttpExtraParams>, serde_json::Error> {
+        self.x_http_extra_params.as_ref().map(XHttpExtraParams::deserialize).transpose()
+    }
+
+    /// `mux_params` as [`MuxParams`]; `Ok(None)` if unset.
+    pub fn mux(&self) -> Result<Option<MuxParams>, serde_json::Error> {
+        self.mux_params.as_ref().map(MuxParams::deserialize).transpose()
+    }
+
+    /// `sockopt_params` as [`SockoptParams`]; `Ok(None)` if unset.
+    pub fn sockopt(&self) -> Result<Option<SockoptParams>, serde_json::Error> {
+        self.sockopt_params.as_ref().map(SockoptParams::deserialize).transpose()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateHostRequestDto {
@@ -145,9 +225,9 @@ pub struct CreateHostRequestDto {
     pub fingerprint: Option<FingerprintType>,
     pub is_disabled: bool,
     pub security_layer: SecurityLayerType,
-    pub x_http_extra_params: Option<serde_json::Value>,
-    pub mux_params: Option<serde_json::Value>,
-    pub sockopt_params: Option<serde_json::Value>,
+    pub x_http_extra_params: Option<XHttpExtraParams>,
+    pub mux_params: Option<MuxParams>,
+    pub sockopt_params: Option<SockoptParams>,
     pub server_description: Option<String>,
     pub tag: Option<String>,
     #[serde(default)]
@@ -180,9 +260,9 @@ pub struct UpdateHostRequestDto {
     pub fingerprint: Option<FingerprintType>,
     pub is_disabled: Option<bool>,
     pub security_layer: Option<SecurityLayerType>,
-    pub x_http_extra_params: Option<serde_json::Value>,
-    pub mux_params: Option<serde_json::Value>,
-    pub sockopt_params: Option<serde_json::Value>,
+    pub x_http_extra_params: Option<XHttpExtraParams>,
+    pub mux_params: Option<MuxParams>,
+    pub sockopt_params: Option<SockoptParams>,
     pub server_description: Option<String>,
     pub tag: Option<String>,
     pub is_hidden: Option<bool>,
@@ -1,4 +1,4 @@
-use crate::types::InboundDto;
+use crate::types::{CountryCode, InboundDto};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -107,7 +107,7 @@ pub struct InternalSquadAccessibleNodesData {
 pub struct AccessibleNodeDto {
     pub uuid: Uuid,
     pub node_name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
     pub config_profile_uuid: Uuid,
     pub config_profile_name: String,
     pub active_inbounds: Vec<Uuid>,
@@ -2,7 +2,11 @@ pub mod auth;
 pub mod billing;
 pub mod common;
 pub mod config_profiles;
+pub mod country;
 pub mod external_squads;
+#[cfg(feature = "formats")]
+pub mod formats;
+pub mod happ;
 pub mod hosts;
 pub mod hwid;
 pub mod internal_squads;
@@ -16,12 +20,17 @@ pub mod subscriptions;
 pub mod system;
 pub mod tokens;
 pub mod users;
+pub mod xray;
 
 pub use auth::*;
 pub use billing::*;
 pub use common::*;
 pub use config_profiles::*;
+pub use country::*;
 pub use external_squads::*;
+#[cfg(feature = "formats")]
+pub use formats::*;
+pub use happ::*;
 pub use hosts::*;
 pub use hwid::*;
 pub use internal_squads::*;
@@ -35,3 +44,4 @@ pub use subscriptions::*;
 pub use system::*;
 pub use tokens::*;
 pub use users::*;
+pub use xray::*;
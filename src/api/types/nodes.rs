@@ -1,4 +1,4 @@
-use crate::types::InboundDto;
+use crate::types::{CountryCode, InboundDto};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -12,17 +12,13 @@ pub struct CreateNodeRequestDto {
     pub traffic_limit_bytes: usize,
     pub notify_percent: u8,
     pub traffic_reset_day: u8,
-    #[serde(default = "default_country_code")]
-    pub country_code: String,
+    #[serde(default)]
+    pub country_code: CountryCode,
     pub consumption_multiplier: f32,
     pub config_profile: ConfigProfileRequest,
     pub provider_uuid: Option<Option<Uuid>>,
 }
 
-fn default_country_code() -> String {
-    "XX".to_string()
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateNodeRequestDto {
@@ -34,7 +30,7 @@ pub struct UpdateNodeRequestDto {
     pub traffic_limit_bytes: Option<usize>,
     pub notify_percent: Option<u8>,
     pub traffic_reset_day: Option<u8>,
-    pub country_code: Option<String>,
+    pub country_code: Option<CountryCode>,
     pub consumption_multiplier: Option<f32>,
     pub config_profile: Option<ConfigProfileRequest>,
     pub provider_uuid: Option<Option<Uuid>>,
@@ -145,7 +141,7 @@ pub struct NodeDto {
     pub notify_percent: Option<i32>,
     pub users_online: Option<i32>,
     pub view_position: u8,
-    pub country_code: String,
+    pub country_code: CountryCode,
     pub consumption_multiplier: f32,
     pub cpu_count: Option<i32>,
     pub cpu_model: Option<String>,
@@ -218,7 +214,7 @@ pub struct RestartAllNodesData {
 pub struct NodesUsageData {
     pub node_uuid: Uuid,
     pub node_name: String,
-    pub node_country_code: String,
+    pub node_country_code: CountryCode,
     pub total: usize,
     pub total_download: usize,
     pub total_upload: usize,
@@ -243,7 +239,7 @@ pub struct NodeUserUsageData {
 pub struct NodeRealtimeUsage {
     pub node_uuid: Uuid,
     pub node_name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
     pub download_bytes: usize,
     pub upload_bytes: usize,
     pub total_bytes: usize,
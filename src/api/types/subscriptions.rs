@@ -1,5 +1,5 @@
 use crate::api::types::users::{InternalSquad, LastConnectedNode};
-use crate::types::{TrafficLimitStrategy, UserStatus};
+use crate::types::{MuxParams, SockoptParams, TrafficLimitStrategy, UserStatus, XHttpExtraParams};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt};
@@ -19,6 +19,10 @@ pub enum SubscriptionClientType {
     Clash,
 }
 
+impl SubscriptionClientType {
+    pub const ALL: [SubscriptionClientType; 7] = [Self::Stash, Self::SingBox, Self::SingBoxLegacy, Self::Mihomo, Self::Json, Self::V2RayJson, Self::Clash];
+}
+
 impl fmt::Display for SubscriptionClientType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = serde_plain::to_string(self).unwrap();
@@ -44,6 +48,78 @@ impl fmt::Display for SubscriptionTemplateType {
     }
 }
 
+/// `subscription_type` path segment of the Outline endpoint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutlineSubscriptionType {
+    #[default]
+    Ss,
+}
+
+impl fmt::Display for OutlineSubscriptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = serde_plain::to_string(self).unwrap();
+        write!(f, "{s}")
+    }
+}
+
+/// Outline dynamic access key, the JSON served at `/api/sub/outline/{short_uuid}/{type}/{encoded_tag}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OutlineKey {
+    pub server: String,
+    pub server_port: u16,
+    pub password: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+impl OutlineKey {
+    /// Static SIP002 `ss://` access key for the same server; `name` becomes the fragment.
+    pub fn to_ss_url(&self, name: Option<&str>) -> String {
+        use base64::Engine;
+
+        let user_info = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(format!("{}:{}", self.method, self.password));
+        let host = if self.server.contains(':') {
+            format!("[{}]", self.server)
+        } else {
+            self.server.clone()
+        };
+        let mut url = format!("ss://{user_info}@{host}:{}", self.server_port);
+        if let Some(prefix) = &self.prefix {
+            url.push_str(&format!("/?outline=1&prefix={}", percent_encode(prefix)));
+        }
+        if let Some(name) = name {
+            url.push_str(&format!("#{}", percent_encode(name)));
+        }
+        url
+    }
+}
+
+/// Encodes a host tag for the `encoded_tag` segment of the Outline endpoint (URL-safe base64, no padding).
+pub fn encode_outline_tag(tag: &str) -> String {
+    use base64::Engine;
+
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(tag)
+}
+
+/// `ssconf://` URL that makes Outline clients fetch [`OutlineKey`]s for `short_uuid` and `tag` from the panel at `base_url`.
+pub fn outline_ssconf_url(base_url: &str, short_uuid: &str, subscription_type: OutlineSubscriptionType, tag: &str) -> String {
+    let authority = base_url.trim_end_matches('/');
+    let authority = authority.strip_prefix("https://").or_else(|| authority.strip_prefix("http://")).unwrap_or(authority);
+    format!("ssconf://{authority}/api/sub/outline/{short_uuid}/{subscription_type}/{}#{}", encode_outline_tag(tag), percent_encode(tag))
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionUser {
@@ -107,6 +183,29 @@ pub struct RawHost {
     pub db_data: Option<RawHostDbData>,
 }
 
+impl RawHost {
+    /// Whether the host ends up in rendered subscriptions, i.e. it is neither disabled nor hidden.
+    /// Hosts without `db_data` are assumed to be served.
+    pub fn is_served(&self) -> bool {
+        self.db_data.as_ref().is_none_or(|db| !db.is_disabled && !db.is_hidden)
+    }
+
+    /// `x_http_extra_params` as [`XHttpExtraParams`]; `Ok(None)` if unset.
+    pub fn x_http_extra(&self) -> Result<Option<XHttpExtraParams>, serde_json::Error> {
+        self.x_http_extra_params.as_ref().map(|extra| XHttpExtraParams::deserialize(serde_json::Value::Object(extra.clone().into_iter().collect()))).transpose()
+    }
+
+    /// `mux_params` as [`MuxParams`]; `Ok(None)` if unset.
+    pub fn mux(&self) -> Result<Option<MuxParams>, serde_json::Error> {
+        self.mux_params.as_ref().map(MuxParams::deserialize).transpose()
+    }
+
+    /// `sockopt_params` as [`SockoptParams`]; `Ok(None)` if unset.
+    pub fn sockopt(&self) -> Result<Option<SockoptParams>, serde_json::Error> {
+        self.sockopt_params.as_ref().map(SockoptParams::deserialize).transpose()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct AdditionalParams {
@@ -428,6 +527,13 @@ pub enum SubscriptionResponseRuleType {
     SocketDrop,
 }
 
+impl fmt::Display for SubscriptionResponseRuleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = serde_plain::to_string(self).unwrap();
+        write!(f, "{s}")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionResponseRuleModifications {
@@ -1,4 +1,4 @@
-use crate::types::{TrafficLimitStrategy, UserStatus};
+use crate::types::{CountryCode, TrafficLimitStrategy, UserStatus};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -14,7 +14,7 @@ pub struct InternalSquad {
 pub struct LastConnectedNode {
     pub connected_at: DateTime<Utc>,
     pub node_name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -28,7 +28,7 @@ pub struct Happ {
 pub struct ActiveNode {
     pub uuid: Uuid,
     pub node_name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
     pub config_profile_uuid: Uuid,
     pub config_profile_name: String,
     pub active_squads: Vec<ActiveSquad>,
@@ -430,7 +430,7 @@ pub struct UsageData {
     pub user_uuid: Uuid,
     pub node_uuid: Uuid,
     pub node_name: String,
-    pub country_code: String,
+    pub country_code: CountryCode,
     pub total: usize,
     pub date: String,
 }
@@ -0,0 +1,794 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Xray `Int32Range`: a fixed number, a `"from-to"` string or a `{ "from", "to" }` object. Kept in the form it was
+/// read in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum XrayRange {
+    Fixed(i64),
+    Text(String),
+    Bounds {
+        from: i64,
+        to: i64,
+    },
+}
+
+impl XrayRange {
+    /// Lower and upper bound; `None` for strings that are not `"n"` or `"from-to"`.
+    pub fn bounds(&self) -> Option<(i64, i64)> {
+        match self {
+            Self::Fixed(n) => Some((*n, *n)),
+            Self::Bounds {
+                from,
+                to,
+            } => Some((*from, *to)),
+            Self::Text(text) => match text.split_once('-') {
+                Some((from, to)) => Some((from.trim().parse().ok()?, to.trim().parse().ok()?)),
+                None => text.trim().parse().ok().map(|n| (n, n)),
+            },
+        }
+    }
+}
+
+impl From<i64> for XrayRange {
+    fn from(n: i64) -> Self {
+        Self::Fixed(n)
+    }
+}
+
+impl From<(i64, i64)> for XrayRange {
+    fn from((from, to): (i64, i64)) -> Self {
+        Self::Text(format!("{from}-{to}"))
+    }
+}
+
+/// Host `xHttpExtraParams`: the `extra` object of Xray's `xhttpSettings`. Unknown keys are kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XHttpExtraParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<BTreeMap<String, String>>,
+    /// Random padding added to requests and responses, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_padding_bytes: Option<XrayRange>,
+    /// Drop the `Content-Type: application/grpc` header in `stream-up`/`stream-one` mode.
+    #[serde(default, rename = "noGRPCHeader", skip_serializing_if = "Option::is_none")]
+    pub no_grpc_header: Option<bool>,
+    #[serde(default, rename = "noSSEHeader", skip_serializing_if = "Option::is_none")]
+    pub no_sse_header: Option<bool>,
+    /// `packet-up` only: maximum size of one upload POST.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sc_max_each_post_bytes: Option<XrayRange>,
+    /// `packet-up` only: minimum interval between upload POSTs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sc_min_posts_interval_ms: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sc_max_buffered_posts: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sc_stream_up_server_secs: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xmux: Option<XHttpXmux>,
+    /// Separate stream settings for the download direction, e.g. download over a CDN and upload directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_settings: Option<XHttpDownloadSettings>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Connection reuse of xHTTP over HTTP/2 and HTTP/3. `max_concurrency` and `max_connections` are mutually exclusive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XHttpXmux {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c_max_reuse_times: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub h_max_request_times: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub h_max_reusable_secs: Option<XrayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub h_keep_alive_period: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `downloadSettings` of xHTTP extra: a stream settings object with its own address. TLS, Reality and xHTTP
+/// settings stay untyped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XHttpDownloadSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_settings: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reality_settings: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xhttp_settings: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sockopt: Option<SockoptParams>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Host `muxParams`: the outbound `mux` object.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MuxParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Maximum TCP sub-connections per mux connection, 1 to 1024; `-1` disables mux for TCP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<i16>,
+    /// Sub-connections for UDP (XUDP), 1 to 1024; `-1` disables mux for UDP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xudp_concurrency: Option<i16>,
+    /// `reject`, `allow` or `skip` for UDP/443 (QUIC) traffic.
+    #[serde(default, rename = "xudpProxyUDP443", skip_serializing_if = "Option::is_none")]
+    pub xudp_proxy_udp443: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `tcpFastOpen`: on/off or the server's pending connection queue length.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum TcpFastOpen {
+    Enabled(bool),
+    QueueLength(i32),
+}
+
+/// Host `sockoptParams`: the stream settings `sockopt` object.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SockoptParams {
+    /// `SO_MARK` for outgoing connections (Linux).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mark: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_fast_open: Option<TcpFastOpen>,
+    /// `redirect`, `tproxy` or `off`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tproxy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_strategy: Option<String>,
+    /// Tag of the outbound to dial through, for proxy chains.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialer_proxy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_proxy_protocol: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_keep_alive_interval: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_keep_alive_idle: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_user_timeout: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_max_seg: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_window_clamp: Option<i32>,
+    /// Congestion control algorithm, e.g. `bbr`.
+    #[serde(default, rename = "tcpcongestion", skip_serializing_if = "Option::is_none")]
+    pub tcp_congestion: Option<String>,
+    /// Network interface to bind to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    #[serde(default, rename = "v6only", skip_serializing_if = "Option::is_none")]
+    pub v6_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_mptcp: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub happy_eyeballs: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A port or port list: `443`, `"1000-2000"`, `"53,443"` or `"env:PORT"`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum XrayPort {
+    Number(u16),
+    Text(String),
+}
+
+/// Reality `target`/`dest`: a local port or an `address:port`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum XrayDestination {
+    Port(u16),
+    Address(String),
+}
+
+/// Xray server config as stored in a config profile. Sections and fields the model does not know are kept in the
+/// `extra` maps and serialized back unchanged; empty arrays of typed fields other than `clients` are omitted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<XrayLog>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<XrayApi>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns: Option<XrayDns>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing: Option<XrayRouting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<XrayPolicy>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inbounds: Vec<XrayInbound>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outbounds: Vec<XrayOutbound>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<XrayStats>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl XrayConfig {
+    pub fn inbound(&self, tag: &str) -> Option<&XrayInbound> {
+        self.inbounds.iter().find(|i| i.tag.as_deref() == Some(tag))
+    }
+
+    pub fn inbound_mut(&mut self, tag: &str) -> Option<&mut XrayInbound> {
+        self.inbounds.iter_mut().find(|i| i.tag.as_deref() == Some(tag))
+    }
+
+    pub fn outbound(&self, tag: &str) -> Option<&XrayOutbound> {
+        self.outbounds.iter().find(|o| o.tag.as_deref() == Some(tag))
+    }
+
+    pub fn outbound_mut(&mut self, tag: &str) -> Option<&mut XrayOutbound> {
+        self.outbounds.iter_mut().find(|o| o.tag.as_deref() == Some(tag))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayLog {
+    /// `debug`, `info`, `warning`, `error` or `none`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loglevel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_log: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask_address: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayApi {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayDns {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hosts: Option<BTreeMap<String, Value>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<XrayDnsServer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    /// `UseIP`, `UseIPv4` or `UseIPv6`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_cache: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_fallback: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A DNS server: a plain address such as `"1.1.1.1"` or `"https://dns.google/dns-query"`, or a server object.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum XrayDnsServer {
+    Address(String),
+    Server(XrayDnsServerObject),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayDnsServerObject {
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<String>,
+    #[serde(default, rename = "expectIPs", skip_serializing_if = "Vec::is_empty")]
+    pub expect_ips: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip_fallback: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_strategy: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayRouting {
+    /// `AsIs`, `IPIfNonMatch` or `IPOnDemand`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<XrayRoutingRule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub balancers: Vec<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A routing rule; all set matchers must match. The legacy `"type": "field"` is kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayRoutingRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domain: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ip: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<XrayPort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_port: Option<XrayPort>,
+    /// `tcp`, `udp` or `tcp,udp`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub user: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inbound_tag: Vec<String>,
+    /// Sniffed protocols: `http`, `tls`, `quic` or `bittorrent`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protocol: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attrs: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balancer_tag: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayPolicy {
+    /// Policies per user level, keyed by the level as a string.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub levels: BTreeMap<String, XrayLevelPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<XraySystemPolicy>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Timeouts are in seconds, `buffer_size` in KiB.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayLevelPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handshake: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conn_idle: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uplink_only: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downlink_only: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_user_uplink: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_user_downlink: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_user_online: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_size: Option<i32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XraySystemPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_inbound_uplink: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_inbound_downlink: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_outbound_uplink: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_outbound_downlink: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `stats` has no options; its presence enables traffic statistics.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct XrayStats {
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayInbound {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<XrayPort>,
+    /// `protocol` and `settings`.
+    #[serde(flatten)]
+    pub protocol: XrayInboundProtocol,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_settings: Option<XrayStreamSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sniffing: Option<XraySniffing>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl XrayInbound {
+    /// `streamSettings.network`; Xray defaults to `raw` (TCP) if unset.
+    pub fn network(&self) -> Option<&str> {
+        self.stream_settings.as_ref().and_then(|s| s.network.as_deref())
+    }
+
+    /// `streamSettings.security`; `none` if unset.
+    pub fn security(&self) -> Option<&str> {
+        self.stream_settings.as_ref().and_then(|s| s.security.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayOutbound {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_through: Option<String>,
+    /// `protocol` and `settings`.
+    #[serde(flatten)]
+    pub protocol: XrayOutboundProtocol,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_settings: Option<XrayStreamSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_settings: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mux: Option<MuxParams>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `protocol` plus `settings`, the shape shared by inbounds and outbounds.
+#[derive(Serialize, Deserialize)]
+struct ProtocolSettings {
+    protocol: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    settings: Option<Value>,
+}
+
+/// Enum over the typed `settings` of known protocols with an `Other` fallback. `None` settings are omitted, as in
+/// `{ "protocol": "blackhole" }`.
+macro_rules! protocol_settings {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident($settings:ty) = $protocol:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum $name {
+            $($variant(Option<$settings>),)*
+            Other { protocol: String, settings: Option<Value> },
+        }
+
+        impl $name {
+            pub fn protocol(&self) -> &str {
+                match self {
+                    $(Self::$variant(_) => $protocol,)*
+                    Self::Other { protocol, .. } => protocol,
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let settings = match self {
+                    $(Self::$variant(settings) => settings.as_ref().map(serde_json::to_value).transpose().map_err(serde::ser::Error::custom)?,)*
+                    Self::Other { settings, .. } => settings.clone(),
+                };
+                ProtocolSettings { protocol: self.protocol().to_owned(), settings }.serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let ProtocolSettings { protocol, settings } = ProtocolSettings::deserialize(deserializer)?;
+                Ok(match protocol.as_str() {
+                    $($protocol => Self::$variant(settings.map(serde_json::from_value).transpose().map_err(serde::de::Error::custom)?),)*
+                    _ => Self::Other { protocol, settings },
+                })
+            }
+        }
+    };
+}
+
+protocol_settings! {
+    /// Inbound `protocol` and `settings`.
+    XrayInboundProtocol {
+        Vless(VlessInboundSettings) = "vless",
+        Vmess(VmessInboundSettings) = "vmess",
+        Trojan(TrojanInboundSettings) = "trojan",
+        Shadowsocks(ShadowsocksInboundSettings) = "shadowsocks",
+    }
+}
+
+protocol_settings! {
+    /// Outbound `protocol` and `settings`.
+    XrayOutboundProtocol {
+        Freedom(FreedomSettings) = "freedom",
+        Blackhole(BlackholeSettings) = "blackhole",
+    }
+}
+
+/// An inbound user. Remnawave fills `clients` from its users, so profiles usually leave it empty.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayClient {
+    /// VLESS/VMess UUID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Trojan/Shadowsocks password.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// VLESS flow, e.g. `xtls-rprx-vision`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VlessInboundSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clients: Option<Vec<XrayClient>>,
+    /// `none`, or the VLESS Encryption server key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decryption: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VmessInboundSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clients: Option<Vec<XrayClient>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TrojanInboundSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clients: Option<Vec<XrayClient>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowsocksInboundSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clients: Option<Vec<XrayClient>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FreedomSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain_strategy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BlackholeSettings {
+    /// `{ "type": "none" | "http" }`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `streamSettings`. Transports other than xHTTP (`rawSettings`, `wsSettings`, `grpcSettings`, ...) stay in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayStreamSettings {
+    /// `raw`, `xhttp`, `ws`, `grpc`, `httpupgrade`, `kcp`, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// `none`, `tls` or `reality`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_settings: Option<XrayTlsSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reality_settings: Option<XrayRealitySettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xhttp_settings: Option<XHttpSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sockopt: Option<SockoptParams>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayTlsSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alpn: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub certificates: Vec<XrayCertificate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reject_unknown_sni: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_insecure: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A certificate given by file paths or inline PEM lines.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayCertificate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub certificate: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub key: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocsp_stapling: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Server-side Reality settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XrayRealitySettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show: Option<bool>,
+    /// Where non-Reality traffic is forwarded; older configs call it `dest`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<XrayDestination>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dest: Option<XrayDestination>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xver: Option<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub server_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub short_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_client_ver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_client_ver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_time_diff: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl XrayRealitySettings {
+    /// `target`, or `dest` for older configs.
+    pub fn destination(&self) -> Option<&XrayDestination> {
+        self.target.as_ref().or(self.dest.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XHttpSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// `auto`, `packet-up`, `stream-up` or `stream-one`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Xray's own `extra` key.
+    #[serde(default, rename = "extra", skip_serializing_if = "Option::is_none")]
+    pub extra_params: Option<XHttpExtraParams>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XraySniffing {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// `http`, `tls`, `quic` and/or `fakedns`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dest_override: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains_excluded: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
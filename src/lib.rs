@@ -1,4 +1,6 @@
 pub mod api;
+pub mod diagnostics;
+pub mod tools;
 pub use api::*;
 
 #[derive(Debug)]
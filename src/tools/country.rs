@@ -0,0 +1,68 @@
+use crate::api::types::country::CountryCode;
+use crate::api::types::nodes::{NodeDto, NodesUsageData};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Groups `items` by country, countries in code order and items in input order.
+pub fn group_by_country<T>(items: &[T], country: impl Fn(&T) -> CountryCode) -> BTreeMap<CountryCode, Vec<&T>> {
+    let mut groups: BTreeMap<CountryCode, Vec<&T>> = BTreeMap::new();
+    for item in items {
+        groups.entry(country(item)).or_default().push(item);
+    }
+    groups
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeCountrySummary {
+    pub country: CountryCode,
+    pub nodes: usize,
+    /// Connected and not disabled.
+    pub online: usize,
+    pub users_online: u64,
+    pub traffic_used_bytes: u64,
+}
+
+/// One summary per country of `nodes`, in code order.
+pub fn summarize_nodes_by_country(nodes: &[NodeDto]) -> Vec<NodeCountrySummary> {
+    group_by_country(nodes, |node| node.country_code)
+        .into_iter()
+        .map(|(country, nodes)| NodeCountrySummary {
+            country,
+            nodes: nodes.len(),
+            online: nodes.iter().filter(|node| node.is_connected && !node.is_disabled).count(),
+            users_online: nodes.iter().filter_map(|node| node.users_online).map(|users| users.max(0) as u64).sum(),
+            traffic_used_bytes: nodes.iter().filter_map(|node| node.traffic_used_bytes).map(|bytes| bytes as u64).sum(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CountryUsage {
+    pub country: CountryCode,
+    /// Distinct nodes with usage in the country.
+    pub nodes: usize,
+    pub total: u64,
+    pub download: u64,
+    pub upload: u64,
+}
+
+/// Sums `NodesUsageController::get_usage_by_range` rows per country, highest total first.
+pub fn summarize_usage_by_country(usage: &[NodesUsageData]) -> Vec<CountryUsage> {
+    let mut summaries: Vec<CountryUsage> = group_by_country(usage, |row| row.node_country_code)
+        .into_iter()
+        .map(|(country, rows)| {
+            let mut nodes: Vec<uuid::Uuid> = rows.iter().map(|row| row.node_uuid).collect();
+            nodes.sort_unstable();
+            nodes.dedup();
+            CountryUsage {
+                country,
+                nodes: nodes.len(),
+                total: rows.iter().map(|row| row.total as u64).sum(),
+                download: rows.iter().map(|row| row.total_download as u64).sum(),
+                upload: rows.iter().map(|row| row.total_upload as u64).sum(),
+            }
+        })
+        .collect();
+    summaries.sort_by(|a, b| b.total.cmp(&a.total).then(a.country.cmp(&b.country)));
+    summaries
+}
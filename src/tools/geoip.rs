@@ -0,0 +1,279 @@
+use crate::api::types::subscription_request_history::{SubscriptionRequestHistoryData, SubscriptionRequestRecord};
+use crate::api::types::users::{UserSubscriptionRequestHistoryData, UserSubscriptionRequestRecord};
+use maxminddb::{MaxMindDBError, Reader};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum GeoIpError {
+    /// The `.mmdb` file could not be read or is not a valid MaxMind DB.
+    Database(MaxMindDBError),
+    InvalidIp(String),
+}
+
+impl fmt::Display for GeoIpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(e) => write!(f, "GeoIP database error: {e}"),
+            Self::InvalidIp(ip) => write!(f, "Invalid IP address: {ip}"),
+        }
+    }
+}
+
+impl std::error::Error for GeoIpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Database(e) => Some(e),
+            Self::InvalidIp(_) => None,
+        }
+    }
+}
+
+impl From<MaxMindDBError> for GeoIpError {
+    fn from(e: MaxMindDBError) -> Self {
+        Self::Database(e)
+    }
+}
+
+/// Location and network of an IP address. Fields are `None` if the database does not carry them.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct GeoIpInfo {
+    /// ISO 3166-1 alpha-2 code.
+    pub country_code: Option<String>,
+    /// English country name.
+    pub country: Option<String>,
+    /// English city name.
+    pub city: Option<String>,
+    pub asn: Option<u32>,
+    pub as_organization: Option<String>,
+}
+
+impl GeoIpInfo {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    fn merge(&mut self, other: GeoIpInfo) {
+        self.country_code = self.country_code.take().or(other.country_code);
+        self.country = self.country.take().or(other.country);
+        self.city = self.city.take().or(other.city);
+        self.asn = self.asn.or(other.asn);
+        self.as_organization = self.as_organization.take().or(other.as_organization);
+    }
+}
+
+/// The subset of the GeoIP2/GeoLite2 City, Country and ASN schemas (also used by DB-IP) that is read.
+#[derive(Deserialize)]
+struct MmdbRecord {
+    country: Option<MmdbPlace>,
+    registered_country: Option<MmdbPlace>,
+    city: Option<MmdbPlace>,
+    autonomous_system_number: Option<u32>,
+    autonomous_system_organization: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MmdbPlace {
+    iso_code: Option<String>,
+    names: Option<BTreeMap<String, String>>,
+}
+
+impl MmdbPlace {
+    fn english_name(&self) -> Option<String> {
+        self.names.as_ref().and_then(|names| names.get("en").cloned())
+    }
+}
+
+impl From<MmdbRecord> for GeoIpInfo {
+    fn from(record: MmdbRecord) -> Self {
+        let country = record.country.or(record.registered_country);
+        Self {
+            country_code: country.as_ref().and_then(|c| c.iso_code.clone()),
+            country: country.as_ref().and_then(MmdbPlace::english_name),
+            city: record.city.as_ref().and_then(MmdbPlace::english_name),
+            asn: record.autonomous_system_number,
+            as_organization: record.autonomous_system_organization,
+        }
+    }
+}
+
+/// Offline GeoIP lookups from local MaxMind or DB-IP `.mmdb` files; no network requests are made.
+///
+/// City/country and ASN data usually ship as separate files, so several databases can be combined.
+/// For each field the first database that has it wins.
+pub struct GeoIp {
+    readers: Vec<Reader<Vec<u8>>>,
+}
+
+impl fmt::Debug for GeoIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.readers.iter().map(|r| &r.metadata.database_type)).finish()
+    }
+}
+
+impl GeoIp {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GeoIpError> {
+        Ok(Self {
+            readers: vec![Reader::open_readfile(path)?],
+        })
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, GeoIpError> {
+        Ok(Self {
+            readers: vec![Reader::from_source(bytes)?],
+        })
+    }
+
+    /// Adds another database, e.g. GeoLite2-ASN next to GeoLite2-City.
+    pub fn with_database(mut self, path: impl AsRef<Path>) -> Result<Self, GeoIpError> {
+        self.readers.push(Reader::open_readfile(path)?);
+        Ok(self)
+    }
+
+    pub fn with_database_bytes(mut self, bytes: Vec<u8>) -> Result<Self, GeoIpError> {
+        self.readers.push(Reader::from_source(bytes)?);
+        Ok(self)
+    }
+
+    /// `None` if no database knows the address.
+    pub fn lookup(&self, ip: IpAddr) -> Result<Option<GeoIpInfo>, GeoIpError> {
+        let mut info = GeoIpInfo::default();
+        // IPv4-only databases would walk their tree with the first bits of an IPv6 address.
+        for reader in self.readers.iter().filter(|r| ip.is_ipv4() || r.metadata.ip_version == 6) {
+            match reader.lookup::<MmdbRecord>(ip) {
+                Ok(record) => info.merge(record.into()),
+                Err(MaxMindDBError::AddressNotFoundError(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok((!info.is_empty()).then_some(info))
+    }
+
+    pub fn lookup_str(&self, ip: &str) -> Result<Option<GeoIpInfo>, GeoIpError> {
+        let ip: IpAddr = ip.trim().parse().map_err(|_| GeoIpError::InvalidIp(ip.to_string()))?;
+        self.lookup(ip)
+    }
+
+    /// Pairs each record with the location of its `request_ip`. Records without an IP, with an unparseable IP
+    /// or with an IP unknown to the databases get `None`.
+    pub fn enrich<'a, R: RequestIp>(&self, records: &'a [R]) -> Vec<GeoIpRecord<'a, R>> {
+        let mut cache: HashMap<&str, Option<GeoIpInfo>> = HashMap::new();
+        records
+            .iter()
+            .map(|record| {
+                let geo = record.request_ip().and_then(|ip| cache.entry(ip).or_insert_with(|| self.lookup_str(ip).ok().flatten()).clone());
+                GeoIpRecord {
+                    record,
+                    geo,
+                }
+            })
+            .collect()
+    }
+
+    /// Request counts by country and by ASN, most requests first.
+    pub fn aggregate<R: RequestIp>(&self, records: &[R]) -> GeoIpAggregates {
+        GeoIpAggregates::from_enriched(&self.enrich(records))
+    }
+}
+
+/// Subscription request history records carrying a request IP.
+pub trait RequestIp {
+    fn request_ip(&self) -> Option<&str>;
+}
+
+impl RequestIp for SubscriptionRequestRecord {
+    fn request_ip(&self) -> Option<&str> {
+        self.request_ip.as_deref()
+    }
+}
+
+impl RequestIp for UserSubscriptionRequestRecord {
+    fn request_ip(&self) -> Option<&str> {
+        self.request_ip.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct GeoIpRecord<'a, R> {
+    pub record: &'a R,
+    pub geo: Option<GeoIpInfo>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct GeoIpAggregates {
+    pub by_country: Vec<CountryCount>,
+    pub by_asn: Vec<AsnCount>,
+    /// Records without a known location.
+    pub unknown: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CountryCount {
+    pub country_code: String,
+    pub country: Option<String>,
+    pub requests: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AsnCount {
+    pub asn: u32,
+    pub as_organization: Option<String>,
+    pub requests: usize,
+}
+
+impl GeoIpAggregates {
+    pub fn from_enriched<R>(records: &[GeoIpRecord<'_, R>]) -> Self {
+        let mut countries: BTreeMap<String, CountryCount> = BTreeMap::new();
+        let mut asns: BTreeMap<u32, AsnCount> = BTreeMap::new();
+        let mut unknown = 0;
+
+        for geo in records.iter().map(|r| r.geo.as_ref()) {
+            let Some(geo) = geo else {
+                unknown += 1;
+                continue;
+            };
+            if let Some(code) = &geo.country_code {
+                let entry = countries.entry(code.clone()).or_insert_with(|| CountryCount {
+                    country_code: code.clone(),
+                    country: geo.country.clone(),
+                    requests: 0,
+                });
+                entry.requests += 1;
+            }
+            if let Some(asn) = geo.asn {
+                let entry = asns.entry(asn).or_insert_with(|| AsnCount {
+                    asn,
+                    as_organization: geo.as_organization.clone(),
+                    requests: 0,
+                });
+                entry.requests += 1;
+            }
+        }
+
+        let mut by_country: Vec<CountryCount> = countries.into_values().collect();
+        by_country.sort_by_key(|c| std::cmp::Reverse(c.requests));
+        let mut by_asn: Vec<AsnCount> = asns.into_values().collect();
+        by_asn.sort_by_key(|a| std::cmp::Reverse(a.requests));
+
+        Self {
+            by_country,
+            by_asn,
+            unknown,
+        }
+    }
+}
+
+impl SubscriptionRequestHistoryData {
+    pub fn geoip_aggregates(&self, geoip: &GeoIp) -> GeoIpAggregates {
+        geoip.aggregate(&self.records)
+    }
+}
+
+impl UserSubscriptionRequestHistoryData {
+    pub fn geoip_aggregates(&self, geoip: &GeoIp) -> GeoIpAggregates {
+        geoip.aggregate(&self.records)
+    }
+}
@@ -0,0 +1,277 @@
+use crate::api::controllers::HostsController;
+use crate::api::types::hosts::{AlpnType, BulkDeleteHostsRequestDto, CreateHostRequestDto, FingerprintType, HostDto, HostInboundRequest, SecurityLayerType};
+use crate::ApiError;
+use std::fmt;
+use uuid::Uuid;
+
+/// Failure of a single host in a [`HostBatch`].
+#[derive(Debug)]
+pub enum HostBatchError {
+    Api(Box<ApiError>),
+    /// The source host has no inbound and the overrides do not set one.
+    MissingInbound(Uuid),
+    /// The source host's xHTTP extra, mux or sockopt params do not fit the typed create request.
+    InvalidParams(Uuid, serde_json::Error),
+}
+
+impl fmt::Display for HostBatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(e) => write!(f, "{e}"),
+            Self::MissingInbound(uuid) => write!(f, "Host {uuid} has no inbound; set one in the overrides"),
+            Self::InvalidParams(uuid, e) => write!(f, "Host {uuid} has invalid params: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for HostBatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api(e) => Some(e.as_ref()),
+            Self::MissingInbound(_) => None,
+            Self::InvalidParams(_, e) => Some(e),
+        }
+    }
+}
+
+impl From<ApiError> for HostBatchError {
+    fn from(e: ApiError) -> Self {
+        Self::Api(Box::new(e))
+    }
+}
+
+/// Fields replaced on a cloned host. Unset fields keep the source host's value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostOverrides {
+    pub inbound: Option<HostInboundRequest>,
+    pub remark: Option<String>,
+    pub address: Option<String>,
+    pub port: Option<u16>,
+    pub path: Option<String>,
+    pub sni: Option<String>,
+    pub host: Option<String>,
+    pub alpn: Option<AlpnType>,
+    pub fingerprint: Option<FingerprintType>,
+    pub security_layer: Option<SecurityLayerType>,
+    pub server_description: Option<String>,
+    pub is_disabled: Option<bool>,
+    pub is_hidden: Option<bool>,
+    pub nodes: Option<Vec<Uuid>>,
+}
+
+impl HostOverrides {
+    pub fn inbound(mut self, inbound: HostInboundRequest) -> Self {
+        self.inbound = Some(inbound);
+        self
+    }
+
+    pub fn remark(mut self, remark: impl Into<String>) -> Self {
+        self.remark = Some(remark.into());
+        self
+    }
+
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn sni(mut self, sni: impl Into<String>) -> Self {
+        self.sni = Some(sni.into());
+        self
+    }
+
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    pub fn alpn(mut self, alpn: AlpnType) -> Self {
+        self.alpn = Some(alpn);
+        self
+    }
+
+    pub fn fingerprint(mut self, fingerprint: FingerprintType) -> Self {
+        self.fingerprint = Some(fingerprint);
+        self
+    }
+
+    pub fn security_layer(mut self, security_layer: SecurityLayerType) -> Self {
+        self.security_layer = Some(security_layer);
+        self
+    }
+
+    pub fn server_description(mut self, description: impl Into<String>) -> Self {
+        self.server_description = Some(description.into());
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.is_disabled = Some(disabled);
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.is_hidden = Some(hidden);
+        self
+    }
+
+    pub fn nodes(mut self, nodes: impl IntoIterator<Item = Uuid>) -> Self {
+        self.nodes = Some(nodes.into_iter().collect());
+        self
+    }
+
+    /// Create request for a copy of `source` with these overrides. `alpn` and `fingerprint` values the SDK does
+    /// not know are dropped; params that do not parse fail with [`HostBatchError::InvalidParams`].
+    pub fn apply(&self, source: &HostDto) -> Result<CreateHostRequestDto, HostBatchError> {
+        let inbound = match (&self.inbound, source.inbound.config_profile_uuid, source.inbound.config_profile_inbound_uuid) {
+            (Some(inbound), _, _) => inbound.clone(),
+            (None, Some(config_profile_uuid), Some(config_profile_inbound_uuid)) => HostInboundRequest {
+                config_profile_uuid,
+                config_profile_inbound_uuid,
+            },
+            _ => return Err(HostBatchError::MissingInbound(source.uuid)),
+        };
+        let invalid = |e| HostBatchError::InvalidParams(source.uuid, e);
+        Ok(CreateHostRequestDto {
+            inbound,
+            remark: self.remark.clone().unwrap_or_else(|| source.remark.clone()),
+            address: self.address.clone().unwrap_or_else(|| source.address.clone()),
+            port: self.port.unwrap_or(source.port),
+            path: self.path.clone().or_else(|| source.path.clone()),
+            sni: self.sni.clone().or_else(|| source.sni.clone()),
+            host: self.host.clone().or_else(|| source.host.clone()),
+            alpn: self.alpn.clone().or_else(|| source.alpn_type()),
+            fingerprint: self.fingerprint.clone().or_else(|| source.fingerprint_type()),
+            is_disabled: self.is_disabled.unwrap_or(source.is_disabled),
+            security_layer: self.security_layer.clone().unwrap_or_else(|| source.security_layer.clone()),
+            x_http_extra_params: source.x_http_extra().map_err(invalid)?,
+            mux_params: source.mux().map_err(invalid)?,
+            sockopt_params: source.sockopt().map_err(invalid)?,
+            server_description: self.server_description.clone().or_else(|| source.server_description.clone()),
+            tag: source.tag.clone(),
+            is_hidden: self.is_hidden.unwrap_or(source.is_hidden),
+            override_sni_from_address: source.override_sni_from_address,
+            allow_insecure: source.allow_insecure,
+            vless_route_id: source.vless_route_id,
+            shuffle_host: source.shuffle_host,
+            mihomo_x25519: source.mihomo_x25519,
+            nodes: self.nodes.clone().unwrap_or_else(|| source.nodes.clone()),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct HostBatchItem {
+    /// The request that was sent; `None` if it could not be built.
+    pub request: Option<CreateHostRequestDto>,
+    pub result: Result<HostDto, HostBatchError>,
+}
+
+/// Hosts created by [`HostsController::clone_host`] or [`HostsController::fan_out`], one item per requested host in
+/// input order. Every created host carries `group_tag` as its tag.
+#[derive(Debug)]
+pub struct HostBatch {
+    pub group_tag: String,
+    pub items: Vec<HostBatchItem>,
+}
+
+impl HostBatch {
+    pub fn created(&self) -> impl Iterator<Item = &HostDto> {
+        self.items.iter().filter_map(|item| item.result.as_ref().ok())
+    }
+
+    pub fn created_uuids(&self) -> Vec<Uuid> {
+        self.created().map(|host| host.uuid).collect()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &HostBatchError> {
+        self.items.iter().filter_map(|item| item.result.as_ref().err())
+    }
+
+    /// Whether every host was created.
+    pub fn is_complete(&self) -> bool {
+        self.items.iter().all(|item| item.result.is_ok())
+    }
+}
+
+impl HostsController {
+    /// Creates one copy of host `uuid` per entry in `overrides`, tagged with `group_tag`. Only fetching the source
+    /// host fails the whole call; failed creates are reported per item.
+    pub async fn clone_host(&self, uuid: Uuid, overrides: impl IntoIterator<Item = HostOverrides>, group_tag: impl Into<String>) -> Result<HostBatch, ApiError> {
+        let source = self.get(uuid).await?.response;
+        let requests = overrides.into_iter().map(|overrides| overrides.apply(&source)).collect();
+        Ok(self.create_batch(requests, group_tag.into()).await)
+    }
+
+    /// Creates `template` once per inbound, tagged with `group_tag`.
+    pub async fn fan_out(&self, template: &CreateHostRequestDto, inbounds: impl IntoIterator<Item = HostInboundRequest>, group_tag: impl Into<String>) -> HostBatch {
+        let requests = inbounds
+            .into_iter()
+            .map(|inbound| {
+                Ok(CreateHostRequestDto {
+                    inbound,
+                    ..template.clone()
+                })
+            })
+            .collect();
+        self.create_batch(requests, group_tag.into()).await
+    }
+
+    /// Deletes the hosts the batch created. Does nothing if none were.
+    pub async fn rollback(&self, batch: &HostBatch) -> Result<Vec<HostDto>, ApiError> {
+        self.bulk_delete_uuids(batch.created_uuids()).await
+    }
+
+    /// Deletes every host tagged `group_tag`, e.g. to roll back a batch from another process.
+    pub async fn delete_group(&self, group_tag: &str) -> Result<Vec<HostDto>, ApiError> {
+        let uuids = self.get_all().await?.response.into_iter().filter(|host| host.tag.as_deref() == Some(group_tag)).map(|host| host.uuid).collect();
+        self.bulk_delete_uuids(uuids).await
+    }
+
+    async fn create_batch(&self, requests: Vec<Result<CreateHostRequestDto, HostBatchError>>, group_tag: String) -> HostBatch {
+        let mut items = Vec::with_capacity(requests.len());
+        for request in requests {
+            let item = match request {
+                Ok(mut request) => {
+                    request.tag = Some(group_tag.clone());
+                    let result = self.create(request.clone()).await.map(|created| created.response).map_err(HostBatchError::from);
+                    HostBatchItem {
+                        request: Some(request),
+                        result,
+                    }
+                }
+                Err(e) => HostBatchItem {
+                    request: None,
+                    result: Err(e),
+                },
+            };
+            items.push(item);
+        }
+        HostBatch {
+            group_tag,
+            items,
+        }
+    }
+
+    async fn bulk_delete_uuids(&self, uuids: Vec<Uuid>) -> Result<Vec<HostDto>, ApiError> {
+        if uuids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .bulk_delete(BulkDeleteHostsRequestDto {
+                uuids,
+            })
+            .await?
+            .response)
+    }
+}
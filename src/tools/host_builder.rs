@@ -0,0 +1,302 @@
+use crate::api::types::config_profiles::Inbound;
+use crate::api::types::hosts::{AlpnType, CreateHostRequestDto, CreateHostResponseDto, FingerprintType, HostInboundRequest, SecurityLayerType};
+use crate::api::types::xray::{MuxParams, SockoptParams, XHttpExtraParams};
+use crate::diagnostics::Diagnostic;
+use crate::{ApiError, RemnawaveApiClient};
+use std::fmt;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum HostBuildError {
+    Api(Box<ApiError>),
+    /// Diagnostics of the failed validation; contains at least one error.
+    Invalid(Vec<Diagnostic>),
+}
+
+impl fmt::Display for HostBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(e) => write!(f, "{e}"),
+            Self::Invalid(diagnostics) => {
+                write!(f, "Invalid host")?;
+                for diagnostic in diagnostics.iter().filter(|d| d.is_error()) {
+                    write!(f, "; {diagnostic}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for HostBuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api(e) => Some(e.as_ref()),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<ApiError> for HostBuildError {
+    fn from(e: ApiError) -> Self {
+        Self::Api(Box::new(e))
+    }
+}
+
+/// Fluent builder for [`CreateHostRequestDto`]. Hosts are enabled, visible and inherit the inbound's security
+/// layer by default.
+#[derive(Debug, Clone)]
+pub struct HostBuilder {
+    host: CreateHostRequestDto,
+}
+
+impl CreateHostRequestDto {
+    pub fn builder(inbound: HostInboundRequest, remark: impl Into<String>, address: impl Into<String>, port: u16) -> HostBuilder {
+        HostBuilder {
+            host: CreateHostRequestDto {
+                inbound,
+                remark: remark.into(),
+                address: address.into(),
+                port,
+                path: None,
+                sni: None,
+                host: None,
+                alpn: None,
+                fingerprint: None,
+                is_disabled: false,
+                security_layer: SecurityLayerType::DEFAULT,
+                x_http_extra_params: None,
+                mux_params: None,
+                sockopt_params: None,
+                server_description: None,
+                tag: None,
+                is_hidden: false,
+                override_sni_from_address: false,
+                allow_insecure: false,
+                vless_route_id: None,
+                shuffle_host: false,
+                mihomo_x25519: false,
+                nodes: Vec::new(),
+            },
+        }
+    }
+}
+
+impl HostBuilder {
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.host.path = Some(path.into());
+        self
+    }
+
+    pub fn sni(mut self, sni: impl Into<String>) -> Self {
+        self.host.sni = Some(sni.into());
+        self
+    }
+
+    /// HTTP `Host` header.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host.host = Some(host.into());
+        self
+    }
+
+    pub fn alpn(mut self, alpn: AlpnType) -> Self {
+        self.host.alpn = Some(alpn);
+        self
+    }
+
+    pub fn fingerprint(mut self, fingerprint: FingerprintType) -> Self {
+        self.host.fingerprint = Some(fingerprint);
+        self
+    }
+
+    pub fn security_layer(mut self, security_layer: SecurityLayerType) -> Self {
+        self.host.security_layer = security_layer;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.host.is_disabled = disabled;
+        self
+    }
+
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.host.is_hidden = hidden;
+        self
+    }
+
+    pub fn x_http_extra_params(mut self, params: XHttpExtraParams) -> Self {
+        self.host.x_http_extra_params = Some(params);
+        self
+    }
+
+    pub fn mux_params(mut self, params: MuxParams) -> Self {
+        self.host.mux_params = Some(params);
+        self
+    }
+
+    pub fn sockopt_params(mut self, params: SockoptParams) -> Self {
+        self.host.sockopt_params = Some(params);
+        self
+    }
+
+    pub fn server_description(mut self, description: impl Into<String>) -> Self {
+        self.host.server_description = Some(description.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.host.tag = Some(tag.into());
+        self
+    }
+
+    /// Sends `address` as SNI instead of [`sni`](Self::sni).
+    pub fn override_sni_from_address(mut self, enabled: bool) -> Self {
+        self.host.override_sni_from_address = enabled;
+        self
+    }
+
+    /// Lets clients skip certificate verification.
+    pub fn allow_insecure(mut self, enabled: bool) -> Self {
+        self.host.allow_insecure = enabled;
+        self
+    }
+
+    pub fn vless_route_id(mut self, route_id: i32) -> Self {
+        self.host.vless_route_id = Some(route_id);
+        self
+    }
+
+    pub fn shuffle_host(mut self, enabled: bool) -> Self {
+        self.host.shuffle_host = enabled;
+        self
+    }
+
+    pub fn mihomo_x25519(mut self, enabled: bool) -> Self {
+        self.host.mihomo_x25519 = enabled;
+        self
+    }
+
+    /// Restricts the host to these nodes.
+    pub fn nodes(mut self, nodes: impl IntoIterator<Item = Uuid>) -> Self {
+        self.host.nodes.extend(nodes);
+        self
+    }
+
+    /// Checks the host on its own and, if given, against the config profile inbound it refers to. Pointers are
+    /// into the serialized [`CreateHostRequestDto`].
+    pub fn validate(&self, inbound: Option<&Inbound>) -> Vec<Diagnostic> {
+        let host = &self.host;
+        let mut diagnostics = Vec::new();
+
+        if host.remark.trim().is_empty() {
+            diagnostics.push(Diagnostic::error("empty-remark", "/remark", "remark must not be empty"));
+        }
+        if host.address.trim().is_empty() {
+            diagnostics.push(Diagnostic::error("empty-address", "/address", "address must not be empty"));
+        }
+        if host.port == 0 {
+            diagnostics.push(Diagnostic::error("invalid-port", "/port", "port must be between 1 and 65535"));
+        }
+
+        if let Some(inbound) = inbound {
+            if inbound.uuid != host.inbound.config_profile_inbound_uuid || inbound.profile_uuid != host.inbound.config_profile_uuid {
+                diagnostics.push(Diagnostic::error("inbound-mismatch", "/inbound", format!("inbound '{}' does not match the host's inbound reference", inbound.tag)));
+            }
+        }
+
+        let inbound_security = inbound.and_then(|inbound| inbound.security.as_deref());
+        let is_reality = inbound_security == Some("reality");
+        // `None` if the security depends on an inbound that was not given.
+        let uses_tls = match host.security_layer {
+            SecurityLayerType::TLS => Some(true),
+            SecurityLayerType::NONE => Some(false),
+            SecurityLayerType::DEFAULT => inbound.map(|_| matches!(inbound_security, Some("tls" | "reality"))),
+        };
+
+        if is_reality && host.security_layer == SecurityLayerType::TLS {
+            diagnostics.push(Diagnostic::error("reality-with-tls", "/securityLayer", "Reality inbounds cannot be served with the TLS security layer; use DEFAULT"));
+        }
+        if uses_tls == Some(false) {
+            for (set, pointer, name) in [
+                (host.sni.is_some(), "/sni", "sni"),
+                (host.alpn.is_some(), "/alpn", "alpn"),
+                (host.fingerprint.is_some(), "/fingerprint", "fingerprint"),
+                (host.allow_insecure, "/allowInsecure", "allowInsecure"),
+                (host.override_sni_from_address, "/overrideSniFromAddress", "overrideSniFromAddress"),
+            ] {
+                if set {
+                    diagnostics.push(Diagnostic::warning("tls-setting-without-tls", pointer, format!("{name} has no effect without TLS")));
+                }
+            }
+        }
+        if is_reality {
+            if host.alpn.is_some() {
+                diagnostics.push(Diagnostic::warning("alpn-with-reality", "/alpn", "alpn is ignored by Reality"));
+            }
+            if host.allow_insecure {
+                diagnostics.push(Diagnostic::warning("insecure-with-reality", "/allowInsecure", "allowInsecure has no effect with Reality"));
+            }
+        } else if host.allow_insecure && uses_tls != Some(false) {
+            diagnostics.push(Diagnostic::warning("allow-insecure", "/allowInsecure", "clients will not verify the server certificate"));
+        }
+
+        if host.override_sni_from_address {
+            if host.sni.is_some() {
+                diagnostics.push(Diagnostic::warning("sni-overridden", "/sni", "sni is replaced by the address because overrideSniFromAddress is set"));
+            }
+            if host.address.parse::<IpAddr>().is_ok() {
+                diagnostics.push(Diagnostic::error("ip-sni", "/overrideSniFromAddress", "the address is an IP literal and cannot be sent as SNI"));
+            }
+        }
+        if host.sni.as_deref().is_some_and(|sni| sni.parse::<IpAddr>().is_ok()) {
+            diagnostics.push(Diagnostic::warning("ip-sni", "/sni", "IP addresses are not sent as SNI"));
+        }
+
+        let wants_h3 = matches!(host.alpn, Some(AlpnType::H3 | AlpnType::H3_H2_COMBINED | AlpnType::H3_H2_H1_COMBINED));
+        if let Some(network) = inbound.and_then(|inbound| inbound.network.as_deref()) {
+            if wants_h3 && network != "xhttp" {
+                diagnostics.push(Diagnostic::warning("h3-without-xhttp", "/alpn", format!("h3 needs the xhttp transport, inbound uses {network}")));
+            }
+            if host.x_http_extra_params.is_some() && network != "xhttp" {
+                diagnostics.push(Diagnostic::warning("xhttp-params-without-xhttp", "/xHttpExtraParams", format!("xHTTP extra params are ignored for {network} inbounds")));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Builds without checking the inbound; fails if [`validate`](Self::validate) reports an error.
+    pub fn build(self) -> Result<CreateHostRequestDto, HostBuildError> {
+        self.build_for(None)
+    }
+
+    /// Builds after validating against `inbound`.
+    pub fn build_for(self, inbound: Option<&Inbound>) -> Result<CreateHostRequestDto, HostBuildError> {
+        let diagnostics = self.validate(inbound);
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(HostBuildError::Invalid(diagnostics));
+        }
+        Ok(self.host)
+    }
+}
+
+impl RemnawaveApiClient {
+    /// Looks up the host's inbound with `ConfigProfilesController::get_inbounds_by_profile_uuid`, validates the
+    /// host against it and creates it. Nothing is created if the inbound does not exist or validation fails.
+    pub async fn create_host(&self, builder: HostBuilder) -> Result<CreateHostResponseDto, HostBuildError> {
+        let reference = builder.host.inbound.clone();
+        let inbounds = self.config_profiles.get_inbounds_by_profile_uuid(reference.config_profile_uuid).await?.response.inbounds;
+        let Some(inbound) = inbounds.iter().find(|inbound| inbound.uuid == reference.config_profile_inbound_uuid) else {
+            let mut diagnostics = builder.validate(None);
+            diagnostics.push(Diagnostic::error(
+                "unknown-inbound",
+                "/inbound/configProfileInboundUuid",
+                format!("config profile {} has no inbound {}", reference.config_profile_uuid, reference.config_profile_inbound_uuid),
+            ));
+            return Err(HostBuildError::Invalid(diagnostics));
+        };
+        let request = builder.build_for(Some(inbound))?;
+        Ok(self.hosts.create(request).await?)
+    }
+}
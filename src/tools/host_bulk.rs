@@ -0,0 +1,155 @@
+use crate::api::controllers::HostsController;
+use crate::api::types::hosts::{
+    BulkDeleteHostsRequestDto, BulkDisableHostsRequestDto, BulkEnableHostsRequestDto, HostDto, HostInboundRequest, SetInboundToManyHostsRequestDto, SetPortToManyHostsRequestDto,
+};
+use crate::ApiError;
+use uuid::Uuid;
+
+/// One of the `/api/hosts/bulk/*` endpoints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostBulkAction {
+    Delete,
+    Enable,
+    Disable,
+    SetInbound(HostInboundRequest),
+    SetPort(u16),
+}
+
+/// Common host criteria; every set criterion must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostFilter {
+    pub tag: Option<String>,
+    pub address: Option<String>,
+    pub port: Option<u16>,
+    pub sni: Option<String>,
+    pub config_profile_inbound_uuid: Option<Uuid>,
+    pub is_disabled: Option<bool>,
+}
+
+impl HostFilter {
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn address(mut self, address: impl Into<String>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn sni(mut self, sni: impl Into<String>) -> Self {
+        self.sni = Some(sni.into());
+        self
+    }
+
+    pub fn inbound(mut self, config_profile_inbound_uuid: Uuid) -> Self {
+        self.config_profile_inbound_uuid = Some(config_profile_inbound_uuid);
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.is_disabled = Some(disabled);
+        self
+    }
+
+    pub fn matches(&self, host: &HostDto) -> bool {
+        self.tag.as_ref().is_none_or(|tag| host.tag.as_ref() == Some(tag))
+            && self.address.as_ref().is_none_or(|address| &host.address == address)
+            && self.port.is_none_or(|port| host.port == port)
+            && self.sni.as_ref().is_none_or(|sni| host.sni.as_ref() == Some(sni))
+            && self.config_profile_inbound_uuid.is_none_or(|uuid| host.inbound.config_profile_inbound_uuid == Some(uuid))
+            && self.is_disabled.is_none_or(|disabled| host.is_disabled == disabled)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostBulkOutcome {
+    pub action: HostBulkAction,
+    /// Hosts the predicate selected, as fetched before the action.
+    pub matched: Vec<HostDto>,
+    /// Response of the bulk endpoint; `None` on a dry run or if nothing matched.
+    pub applied: Option<Vec<HostDto>>,
+}
+
+impl HostBulkOutcome {
+    pub fn matched_uuids(&self) -> Vec<Uuid> {
+        self.matched.iter().map(|host| host.uuid).collect()
+    }
+}
+
+impl HostsController {
+    /// Resolves the hosts matching `predicate` from `get_all` and applies `action` to them with the matching bulk
+    /// endpoint. With `dry_run` only the matched hosts are returned.
+    pub async fn bulk_where(&self, predicate: impl Fn(&HostDto) -> bool, action: HostBulkAction, dry_run: bool) -> Result<HostBulkOutcome, ApiError> {
+        let matched: Vec<HostDto> = self.get_all().await?.response.into_iter().filter(|host| predicate(host)).collect();
+        let applied = if dry_run || matched.is_empty() {
+            None
+        } else {
+            Some(self.apply_bulk(matched.iter().map(|host| host.uuid).collect(), &action).await?)
+        };
+        Ok(HostBulkOutcome {
+            action,
+            matched,
+            applied,
+        })
+    }
+
+    /// [`bulk_where`](Self::bulk_where) for the hosts tagged `tag`.
+    pub async fn bulk_by_tag(&self, tag: &str, action: HostBulkAction, dry_run: bool) -> Result<HostBulkOutcome, ApiError> {
+        self.bulk_where(|host| host.tag.as_deref() == Some(tag), action, dry_run).await
+    }
+
+    /// [`bulk_where`](Self::bulk_where) for the hosts matching `filter`.
+    pub async fn bulk_filtered(&self, filter: &HostFilter, action: HostBulkAction, dry_run: bool) -> Result<HostBulkOutcome, ApiError> {
+        self.bulk_where(|host| filter.matches(host), action, dry_run).await
+    }
+
+    async fn apply_bulk(&self, uuids: Vec<Uuid>, action: &HostBulkAction) -> Result<Vec<HostDto>, ApiError> {
+        let hosts = match action {
+            HostBulkAction::Delete => {
+                self.bulk_delete(BulkDeleteHostsRequestDto {
+                    uuids,
+                })
+                .await?
+                .response
+            }
+            HostBulkAction::Enable => {
+                self.bulk_enable(BulkEnableHostsRequestDto {
+                    uuids,
+                })
+                .await?
+                .response
+            }
+            HostBulkAction::Disable => {
+                self.bulk_disable(BulkDisableHostsRequestDto {
+                    uuids,
+                })
+                .await?
+                .response
+            }
+            HostBulkAction::SetInbound(inbound) => {
+                self.bulk_set_inbound(SetInboundToManyHostsRequestDto {
+                    uuids,
+                    config_profile_uuid: inbound.config_profile_uuid,
+                    config_profile_inbound_uuid: inbound.config_profile_inbound_uuid,
+                })
+                .await?
+                .response
+            }
+            HostBulkAction::SetPort(port) => {
+                self.bulk_set_port(SetPortToManyHostsRequestDto {
+                    uuids,
+                    port: *port,
+                })
+                .await?
+                .response
+            }
+        };
+        Ok(hosts)
+    }
+}
@@ -0,0 +1,47 @@
+pub mod country;
+#[cfg(feature = "geoip")]
+pub mod geoip;
+pub mod host_batch;
+pub mod host_builder;
+pub mod host_bulk;
+pub mod node_bundle;
+pub mod node_watch;
+pub mod ordering;
+#[cfg(feature = "probe")]
+pub mod probe;
+pub mod profile_lint;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod quota;
+#[cfg(feature = "formats")]
+pub mod render;
+pub mod rolling_restart;
+pub mod sharing;
+pub mod srr;
+pub mod throughput;
+pub mod user_agent;
+
+pub use crate::diagnostics;
+pub use crate::diagnostics::{Diagnostic, Severity};
+pub use country::{group_by_country, summarize_nodes_by_country, summarize_usage_by_country, CountryUsage, NodeCountrySummary};
+#[cfg(feature = "geoip")]
+pub use geoip::{AsnCount, CountryCount, GeoIp, GeoIpAggregates, GeoIpError, GeoIpInfo, GeoIpRecord, RequestIp};
+pub use host_batch::{HostBatch, HostBatchError, HostBatchItem, HostOverrides};
+pub use host_builder::{HostBuildError, HostBuilder};
+pub use host_bulk::{HostBulkAction, HostBulkOutcome, HostFilter};
+pub use node_bundle::{render_node_bundle, BundleFile, NodeBundle, NodeBundleOptions, REMNANODE_IMAGE};
+pub use node_watch::{NodeEvent, NodeEventKind, NodeWatchField, NodeWatchOptions, NodeWatcher};
+pub use ordering::{Ordered, OrderingError, ViewOrder};
+#[cfg(feature = "probe")]
+pub use probe::{probe, probe_all, ProbeFailure, ProbeOptions, ProbeResult, ProbeTarget, ProbeTargetKind, ReachabilityDiagnosis, TlsProbe};
+pub use profile_lint::{lint_xray_config, ProfileLintContext, ProfileLintError};
+#[cfg(feature = "qr")]
+pub use qr::{QrCode, QrError, QrErrorCorrection, QrOptions, MAX_PNG_SIDE};
+pub use quota::{forecast_node_quotas, next_reset_date, NodeQuotaForecast, QuotaForecastOptions, QuotaStatus};
+#[cfg(feature = "formats")]
+pub use render::{render_template, substitute_placeholders, RenderError};
+pub use rolling_restart::{plan_rolling_restart, RestartFailure, RestartFailurePolicy, RestartOrder, RollingRestartControl, RollingRestartEvent, RollingRestartOptions};
+pub use sharing::{SharingAnalyzer, SharingEvidence, SharingStats, SharingSuspect, SharingThresholds};
+pub use srr::{validate_response_rules, SrrEvaluator, SrrRequest, SubscriptionResponseRuleBuilder, SubscriptionResponseRuleConditionBuilder, SRR_DEFAULT_RESPONSE_TYPE};
+pub use throughput::{Rate, ThroughputOptions, ThroughputSample, ThroughputSampler, ThroughputStats};
+pub use user_agent::{detect_client_app, ClientAppInfo};
@@ -0,0 +1,148 @@
+use crate::api::types::nodes::{CreateNodeRequestDto, NodeDto};
+use crate::RemnawaveApiClient;
+use std::io::Write;
+use std::path::Path;
+
+pub const REMNANODE_IMAGE: &str = "remnawave/node:latest";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeBundleOptions {
+    pub image: String,
+    pub container_name: String,
+    /// Where the files are copied to on the server; used by the systemd unit.
+    pub install_dir: String,
+    pub systemd: bool,
+}
+
+impl Default for NodeBundleOptions {
+    fn default() -> Self {
+        Self {
+            image: REMNANODE_IMAGE.to_string(),
+            container_name: "remnanode".to_string(),
+            install_dir: "/opt/remnanode".to_string(),
+            systemd: false,
+        }
+    }
+}
+
+impl NodeBundleOptions {
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.image = image.into();
+        self
+    }
+
+    pub fn container_name(mut self, container_name: impl Into<String>) -> Self {
+        self.container_name = container_name.into();
+        self
+    }
+
+    pub fn install_dir(mut self, install_dir: impl Into<String>) -> Self {
+        self.install_dir = install_dir.into();
+        self
+    }
+
+    pub fn systemd(mut self, systemd: bool) -> Self {
+        self.systemd = systemd;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleFile {
+    /// Relative to the bundle directory.
+    pub path: String,
+    pub contents: String,
+    /// Unix permissions; `.env` holds the secret key and is `0o600`.
+    pub mode: u32,
+}
+
+/// Result of [`RemnawaveApiClient::deploy_node`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeBundle {
+    pub node: NodeDto,
+    pub files: Vec<BundleFile>,
+}
+
+impl NodeBundle {
+    pub fn file(&self, path: &str) -> Option<&BundleFile> {
+        self.files.iter().find(|f| f.path == path)
+    }
+
+    /// Writes every file into `dir`, creating it if needed. On Unix files are created with their `mode`, and
+    /// existing files get it before anything is written, so `.env` is never readable by others.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        for file in &self.files {
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(file.mode);
+            }
+            let mut handle = options.open(dir.as_ref().join(&file.path))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                handle.set_permissions(std::fs::Permissions::from_mode(file.mode))?;
+            }
+            handle.write_all(file.contents.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders `docker-compose.yml`, `.env` and, if enabled, `remnanode.service` for a remnanode listening on
+/// `node_port` and authenticating the panel with `secret_key` (the `KeygenController::generate_key` public key).
+pub fn render_node_bundle(node_port: u16, secret_key: &str, options: &NodeBundleOptions) -> Vec<BundleFile> {
+    let compose = format!(
+        "services:\n  {name}:\n    container_name: {name}\n    hostname: {name}\n    image: {image}\n    network_mode: host\n    restart: always\n    env_file:\n      - .env\n",
+        name = options.container_name,
+        image = options.image,
+    );
+    let env = format!("NODE_PORT={node_port}\nSECRET_KEY={secret_key}\n");
+
+    let mut files = vec![
+        BundleFile {
+            path: "docker-compose.yml".to_string(),
+            contents: compose,
+            mode: 0o644,
+        },
+        BundleFile {
+            path: ".env".to_string(),
+            contents: env,
+            mode: 0o600,
+        },
+    ];
+
+    if options.systemd {
+        let unit = format!(
+            "[Unit]\nDescription=Remnawave node ({name})\nRequires=docker.service\nAfter=docker.service network-online.target\nWants=network-online.target\n\n\
+             [Service]\nType=oneshot\nRemainAfterExit=yes\nWorkingDirectory={dir}\nExecStart=/usr/bin/docker compose up -d\nExecStop=/usr/bin/docker compose down\n\n\
+             [Install]\nWantedBy=multi-user.target\n",
+            name = options.container_name,
+            dir = options.install_dir,
+        );
+        files.push(BundleFile {
+            path: format!("{}.service", options.container_name),
+            contents: unit,
+            mode: 0o644,
+        });
+    }
+
+    files
+}
+
+impl RemnawaveApiClient {
+    /// Generates the panel key, creates the node and renders its deployment bundle. The key is fetched first,
+    /// so a failure there leaves nothing behind.
+    pub async fn deploy_node(&self, request: CreateNodeRequestDto, options: &NodeBundleOptions) -> Result<NodeBundle, crate::ApiError> {
+        let secret_key = self.keygen.generate_key().await?.response.pub_key;
+        let node = self.nodes.create(request).await?.response;
+        let files = render_node_bundle(node.port, &secret_key, options);
+        Ok(NodeBundle {
+            node,
+            files,
+        })
+    }
+}
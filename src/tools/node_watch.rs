@@ -0,0 +1,336 @@
+use crate::api::controllers::NodesController;
+use crate::api::types::nodes::NodeDto;
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeWatchOptions {
+    /// Time between polls; zero is treated as one millisecond.
+    pub interval: Duration,
+    /// A changed value must be seen in this many consecutive polls before it is reported. `1` reports immediately.
+    pub debounce_polls: u32,
+    /// A field that changes more than `max_flaps` times within `flap_window` is reported once as flapping and
+    /// stays silent until it has been stable for a whole `flap_window`.
+    pub flap_window: Duration,
+    pub max_flaps: usize,
+}
+
+impl Default for NodeWatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            debounce_polls: 2,
+            flap_window: Duration::from_secs(600),
+            max_flaps: 3,
+        }
+    }
+}
+
+impl NodeWatchOptions {
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn debounce_polls(mut self, debounce_polls: u32) -> Self {
+        self.debounce_polls = debounce_polls;
+        self
+    }
+
+    pub fn flap_suppression(mut self, flap_window: Duration, max_flaps: usize) -> Self {
+        self.flap_window = flap_window;
+        self.max_flaps = max_flaps;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeWatchField {
+    IsConnected,
+    IsNodeOnline,
+    IsXrayRunning,
+    IsDisabled,
+    XrayVersion,
+    LastStatusMessage,
+}
+
+impl NodeWatchField {
+    pub const ALL: [NodeWatchField; 6] = [Self::IsConnected, Self::IsNodeOnline, Self::IsXrayRunning, Self::IsDisabled, Self::XrayVersion, Self::LastStatusMessage];
+
+    fn read(self, node: &NodeDto) -> FieldValue {
+        match self {
+            Self::IsConnected => FieldValue::Bool(node.is_connected),
+            Self::IsNodeOnline => FieldValue::Bool(node.is_node_online),
+            Self::IsXrayRunning => FieldValue::Bool(node.is_xray_running),
+            Self::IsDisabled => FieldValue::Bool(node.is_disabled),
+            Self::XrayVersion => FieldValue::Text(node.xray_version.clone()),
+            Self::LastStatusMessage => FieldValue::Text(node.last_status_message.clone()),
+        }
+    }
+}
+
+impl std::fmt::Display for NodeWatchField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_plain::to_string(self).unwrap())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeEvent {
+    pub node_uuid: Uuid,
+    pub node_name: String,
+    pub kind: NodeEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NodeEventKind {
+    Added,
+    Removed,
+    /// One of the boolean fields flipped.
+    StatusChanged {
+        field: NodeWatchField,
+        value: bool,
+    },
+    XrayVersionChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    LastStatusMessageChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// `traffic_used_bytes` reached `notify_percent` of `traffic_limit_bytes`. Fires again after a traffic reset.
+    TrafficThresholdCrossed {
+        notify_percent: i32,
+        used_bytes: usize,
+        limit_bytes: usize,
+    },
+    /// Further changes of `field` are suppressed until it is stable again.
+    Flapping {
+        field: NodeWatchField,
+    },
+    /// Followed by a change event if the value differs from the one reported before flapping.
+    Stabilized {
+        field: NodeWatchField,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldValue {
+    Bool(bool),
+    Text(Option<String>),
+}
+
+#[derive(Debug, Clone)]
+struct FieldState {
+    confirmed: FieldValue,
+    /// Last value consumers were told about; differs from `confirmed` while flapping.
+    reported: FieldValue,
+    pending: Option<(FieldValue, u32)>,
+    flips: VecDeque<Instant>,
+    flapping: bool,
+}
+
+impl FieldState {
+    fn new(value: FieldValue) -> Self {
+        Self {
+            confirmed: value.clone(),
+            reported: value,
+            pending: None,
+            flips: VecDeque::new(),
+            flapping: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NodeState {
+    name: String,
+    fields: HashMap<NodeWatchField, FieldState>,
+    traffic_percent: Option<u128>,
+}
+
+/// The polling-independent part of [`NodesController::watch`]: feed it every `get_all` result and it returns the
+/// events since the previous call. The first call only records a baseline.
+#[derive(Debug, Clone, Default)]
+pub struct NodeWatcher {
+    options: NodeWatchOptions,
+    nodes: HashMap<Uuid, NodeState>,
+    initialized: bool,
+}
+
+impl NodeWatcher {
+    pub fn new(options: NodeWatchOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    pub fn observe(&mut self, nodes: &[NodeDto], now: Instant) -> Vec<NodeEvent> {
+        let mut events = Vec::new();
+        let initialized = std::mem::replace(&mut self.initialized, true);
+
+        for node in nodes {
+            let Some(state) = self.nodes.get_mut(&node.uuid) else {
+                self.nodes.insert(node.uuid, baseline(node));
+                if initialized {
+                    events.push(event(node.uuid, &node.name, NodeEventKind::Added));
+                }
+                continue;
+            };
+            state.name.clone_from(&node.name);
+
+            for field in NodeWatchField::ALL {
+                let field_state = state.fields.get_mut(&field).expect("every field has a baseline");
+                for kind in step(field, field_state, field.read(node), now, &self.options) {
+                    events.push(event(node.uuid, &node.name, kind));
+                }
+            }
+
+            let percent = traffic_percent(node);
+            if let (Some(notify), Some(before), Some(after)) = (node.notify_percent, state.traffic_percent, percent) {
+                if notify > 0 && before < notify as u128 && after >= notify as u128 {
+                    let kind = NodeEventKind::TrafficThresholdCrossed {
+                        notify_percent: notify,
+                        used_bytes: node.traffic_used_bytes.unwrap_or_default(),
+                        limit_bytes: node.traffic_limit_bytes.unwrap_or_default(),
+                    };
+                    events.push(event(node.uuid, &node.name, kind));
+                }
+            }
+            state.traffic_percent = percent;
+        }
+
+        let removed: Vec<Uuid> = self.nodes.keys().filter(|uuid| !nodes.iter().any(|n| n.uuid == **uuid)).copied().collect();
+        for uuid in removed {
+            let state = self.nodes.remove(&uuid).expect("key was just listed");
+            events.push(event(uuid, &state.name, NodeEventKind::Removed));
+        }
+
+        events
+    }
+}
+
+fn baseline(node: &NodeDto) -> NodeState {
+    NodeState {
+        name: node.name.clone(),
+        fields: NodeWatchField::ALL.into_iter().map(|field| (field, FieldState::new(field.read(node)))).collect(),
+        traffic_percent: traffic_percent(node),
+    }
+}
+
+fn traffic_percent(node: &NodeDto) -> Option<u128> {
+    let limit = node.traffic_limit_bytes.filter(|limit| *limit > 0)?;
+    Some(node.traffic_used_bytes.unwrap_or_default() as u128 * 100 / limit as u128)
+}
+
+fn event(node_uuid: Uuid, node_name: &str, kind: NodeEventKind) -> NodeEvent {
+    NodeEvent {
+        node_uuid,
+        node_name: node_name.to_string(),
+        kind,
+    }
+}
+
+fn step(field: NodeWatchField, state: &mut FieldState, observed: FieldValue, now: Instant, options: &NodeWatchOptions) -> Vec<NodeEventKind> {
+    let mut events = Vec::new();
+
+    if observed == state.confirmed {
+        state.pending = None;
+    } else {
+        let seen = match &state.pending {
+            Some((value, count)) if *value == observed => count + 1,
+            _ => 1,
+        };
+        if seen >= options.debounce_polls.max(1) {
+            state.pending = None;
+            state.confirmed = observed;
+            state.flips.push_back(now);
+        } else {
+            state.pending = Some((observed, seen));
+        }
+    }
+
+    while state.flips.front().is_some_and(|flip| now.duration_since(*flip) > options.flap_window) {
+        state.flips.pop_front();
+    }
+
+    if state.flapping {
+        if state.flips.is_empty() {
+            state.flapping = false;
+            events.push(NodeEventKind::Stabilized {
+                field,
+            });
+        }
+    } else if state.flips.len() > options.max_flaps {
+        state.flapping = true;
+        events.push(NodeEventKind::Flapping {
+            field,
+        });
+    }
+
+    if !state.flapping && state.confirmed != state.reported {
+        let from = std::mem::replace(&mut state.reported, state.confirmed.clone());
+        events.extend(change(field, from, state.confirmed.clone()));
+    }
+
+    events
+}
+
+/// `None` for a text value of a field without a text change event.
+fn change(field: NodeWatchField, from: FieldValue, to: FieldValue) -> Option<NodeEventKind> {
+    let kind = match (from, to) {
+        (_, FieldValue::Bool(value)) => NodeEventKind::StatusChanged {
+            field,
+            value,
+        },
+        (FieldValue::Text(from), FieldValue::Text(to)) => match field {
+            NodeWatchField::XrayVersion => NodeEventKind::XrayVersionChanged {
+                from,
+                to,
+            },
+            NodeWatchField::LastStatusMessage => NodeEventKind::LastStatusMessageChanged {
+                from,
+                to,
+            },
+            _ => return None,
+        },
+        (FieldValue::Bool(_), FieldValue::Text(_)) => return None,
+    };
+    Some(kind)
+}
+
+impl NodesController {
+    /// Polls `get_all` every `interval` and yields [`NodeEvent`]s, see [`NodesController::watch_with_options`].
+    pub fn watch(&self, interval: Duration) -> impl Stream<Item = Result<NodeEvent, crate::ApiError>> + '_ {
+        self.watch_with_options(NodeWatchOptions::default().interval(interval))
+    }
+
+    /// Polls `get_all` and yields [`NodeEvent`]s as detected by a [`NodeWatcher`]. Failed polls are yielded as
+    /// errors and polling continues; the stream never ends on its own.
+    pub fn watch_with_options(&self, options: NodeWatchOptions) -> impl Stream<Item = Result<NodeEvent, crate::ApiError>> + '_ {
+        let mut ticker = tokio::time::interval(options.interval.max(Duration::from_millis(1)));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let state = (ticker, NodeWatcher::new(options), VecDeque::new());
+
+        stream::unfold(state, move |(mut ticker, mut watcher, mut queue)| async move {
+            loop {
+                if let Some(event) = queue.pop_front() {
+                    return Some((Ok(event), (ticker, watcher, queue)));
+                }
+                ticker.tick().await;
+                match self.get_all().await {
+                    Ok(response) => queue.extend(watcher.observe(&response.response, Instant::now())),
+                    Err(e) => return Some((Err(e), (ticker, watcher, queue))),
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,260 @@
+use crate::api::controllers::{HostsController, NodesController};
+use crate::api::types::hosts::{HostDto, HostOrderItem, ReorderHostRequestDto};
+use crate::api::types::nodes::{NodeDto, NodeOrderItem, ReorderNodeRequestDto};
+use crate::ApiError;
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum OrderingError {
+    Api(Box<ApiError>),
+    UnknownUuid(Uuid),
+    /// Node view positions are `u8`; the reordering needs a larger one.
+    PositionOutOfRange(i64),
+}
+
+impl fmt::Display for OrderingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(e) => write!(f, "{e}"),
+            Self::UnknownUuid(uuid) => write!(f, "No item with UUID {uuid}"),
+            Self::PositionOutOfRange(position) => write!(f, "View position {position} is out of range"),
+        }
+    }
+}
+
+impl std::error::Error for OrderingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api(e) => Some(e.as_ref()),
+            Self::UnknownUuid(_) | Self::PositionOutOfRange(_) => None,
+        }
+    }
+}
+
+impl From<ApiError> for OrderingError {
+    fn from(e: ApiError) -> Self {
+        Self::Api(Box::new(e))
+    }
+}
+
+/// Items with a `view_position` that can be reordered.
+pub trait Ordered {
+    fn uuid(&self) -> Uuid;
+    fn view_position(&self) -> i64;
+}
+
+impl Ordered for NodeDto {
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn view_position(&self) -> i64 {
+        self.view_position.into()
+    }
+}
+
+impl Ordered for HostDto {
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn view_position(&self) -> i64 {
+        self.view_position.into()
+    }
+}
+
+/// Offline reordering of nodes or hosts. The new order is mapped onto the existing view positions, so items that
+/// keep their place keep their position; positions are only renumbered if the current ones contain duplicates.
+#[derive(Debug, Clone)]
+pub struct ViewOrder<T> {
+    items: Vec<T>,
+    original: HashMap<Uuid, i64>,
+    slots: Vec<i64>,
+}
+
+impl<T: Ordered> ViewOrder<T> {
+    /// Sorts `items` by their current view position.
+    pub fn new(mut items: Vec<T>) -> Self {
+        items.sort_by_key(|item| item.view_position());
+        let original = items.iter().map(|item| (item.uuid(), item.view_position())).collect();
+        let mut slots: Vec<i64> = items.iter().map(Ordered::view_position).collect();
+        if slots.windows(2).any(|w| w[0] == w[1]) {
+            let first = slots.first().copied().unwrap_or_default();
+            slots = (0..items.len() as i64).map(|i| first + i).collect();
+        }
+        Self {
+            items,
+            original,
+            slots,
+        }
+    }
+
+    /// Items in their new order.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    fn index_of(&self, uuid: Uuid) -> Result<usize, OrderingError> {
+        self.items.iter().position(|item| item.uuid() == uuid).ok_or(OrderingError::UnknownUuid(uuid))
+    }
+
+    /// Moves `uuid` to `index` (clamped to the end) in the new order.
+    pub fn move_to(&mut self, uuid: Uuid, index: usize) -> Result<&mut Self, OrderingError> {
+        let item = self.items.remove(self.index_of(uuid)?);
+        self.items.insert(index.min(self.items.len()), item);
+        Ok(self)
+    }
+
+    /// Moves `uuid` directly in front of `anchor`.
+    pub fn move_before(&mut self, uuid: Uuid, anchor: Uuid) -> Result<&mut Self, OrderingError> {
+        self.index_of(anchor)?;
+        if uuid == anchor {
+            return Ok(self);
+        }
+        let item = self.items.remove(self.index_of(uuid)?);
+        let index = self.index_of(anchor)?;
+        self.items.insert(index, item);
+        Ok(self)
+    }
+
+    /// Stable sort, so items with equal keys keep their relative order.
+    pub fn sort_by<K: Ord>(&mut self, key: impl FnMut(&T) -> K) -> &mut Self {
+        self.items.sort_by_key(key);
+        self
+    }
+
+    /// Pulls items with the same key together at the place of the group's first item. Items without a key stay
+    /// where they are relative to the groups.
+    pub fn group_by<K: Eq + std::hash::Hash>(&mut self, mut key: impl FnMut(&T) -> Option<K>) -> &mut Self {
+        let keys: Vec<Option<K>> = self.items.iter().map(&mut key).collect();
+        let mut group_of: HashMap<&K, usize> = HashMap::new();
+        let ranks: Vec<usize> = keys.iter().enumerate().map(|(i, key)| key.as_ref().map_or(i, |key| *group_of.entry(key).or_insert(i))).collect();
+
+        let mut ranked: Vec<(usize, T)> = ranks.into_iter().zip(self.items.drain(..)).collect();
+        ranked.sort_by_key(|(rank, _)| *rank);
+        self.items = ranked.into_iter().map(|(_, item)| item).collect();
+        self
+    }
+
+    /// Every item with its new view position.
+    pub fn positions(&self) -> Vec<(Uuid, i64)> {
+        self.items.iter().zip(&self.slots).map(|(item, slot)| (item.uuid(), *slot)).collect()
+    }
+
+    /// Items whose view position differs from the current one.
+    pub fn changed(&self) -> Vec<Uuid> {
+        self.positions().into_iter().filter(|(uuid, position)| self.original.get(uuid) != Some(position)).map(|(uuid, _)| uuid).collect()
+    }
+}
+
+impl ViewOrder<NodeDto> {
+    pub fn to_request(&self) -> Result<ReorderNodeRequestDto, OrderingError> {
+        let nodes = self
+            .positions()
+            .into_iter()
+            .map(|(uuid, position)| {
+                Ok(NodeOrderItem {
+                    view_position: u8::try_from(position).map_err(|_| OrderingError::PositionOutOfRange(position))?,
+                    uuid,
+                })
+            })
+            .collect::<Result<_, OrderingError>>()?;
+        Ok(ReorderNodeRequestDto {
+            nodes,
+        })
+    }
+}
+
+impl ViewOrder<HostDto> {
+    /// Groups hosts by [`HostDto::tag`].
+    pub fn group_by_tag(&mut self) -> &mut Self {
+        self.group_by(|host| host.tag.clone())
+    }
+
+    pub fn to_request(&self) -> Result<ReorderHostRequestDto, OrderingError> {
+        let hosts = self
+            .positions()
+            .into_iter()
+            .map(|(uuid, position)| {
+                Ok(HostOrderItem {
+                    view_position: i32::try_from(position).map_err(|_| OrderingError::PositionOutOfRange(position))?,
+                    uuid,
+                })
+            })
+            .collect::<Result<_, OrderingError>>()?;
+        Ok(ReorderHostRequestDto {
+            hosts,
+        })
+    }
+}
+
+impl NodesController {
+    /// Fetches all nodes, applies `reorder` and submits the result. Nothing is sent if no position changed.
+    /// Returns whether a reorder was submitted.
+    pub async fn reorder_with(&self, reorder: impl FnOnce(&mut ViewOrder<NodeDto>) -> Result<(), OrderingError>) -> Result<bool, OrderingError> {
+        let mut order = ViewOrder::new(self.get_all().await?.response);
+        reorder(&mut order)?;
+        if order.changed().is_empty() {
+            return Ok(false);
+        }
+        self.reorder(order.to_request()?).await?;
+        Ok(true)
+    }
+
+    pub async fn move_to(&self, uuid: Uuid, index: usize) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| order.move_to(uuid, index).map(drop)).await
+    }
+
+    pub async fn move_before(&self, uuid: Uuid, anchor: Uuid) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| order.move_before(uuid, anchor).map(drop)).await
+    }
+
+    /// E.g. `sort_by(|node| node.country_code)`.
+    pub async fn sort_by<K: Ord>(&self, key: impl FnMut(&NodeDto) -> K) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| {
+            order.sort_by(key);
+            Ok(())
+        })
+        .await
+    }
+}
+
+impl HostsController {
+    /// Fetches all hosts, applies `reorder` and submits the result. Nothing is sent if no position changed.
+    /// Returns whether a reorder was submitted.
+    pub async fn reorder_with(&self, reorder: impl FnOnce(&mut ViewOrder<HostDto>) -> Result<(), OrderingError>) -> Result<bool, OrderingError> {
+        let mut order = ViewOrder::new(self.get_all().await?.response);
+        reorder(&mut order)?;
+        if order.changed().is_empty() {
+            return Ok(false);
+        }
+        self.reorder(order.to_request()?).await?;
+        Ok(true)
+    }
+
+    pub async fn move_to(&self, uuid: Uuid, index: usize) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| order.move_to(uuid, index).map(drop)).await
+    }
+
+    pub async fn move_before(&self, uuid: Uuid, anchor: Uuid) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| order.move_before(uuid, anchor).map(drop)).await
+    }
+
+    pub async fn sort_by<K: Ord>(&self, key: impl FnMut(&HostDto) -> K) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| {
+            order.sort_by(key);
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn group_by_tag(&self) -> Result<bool, OrderingError> {
+        self.reorder_with(|order| {
+            order.group_by_tag();
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,342 @@
+use crate::api::types::config_profiles::Inbound;
+use crate::api::types::hosts::{HostDto, SecurityLayerType};
+use crate::api::types::nodes::NodeDto;
+use crate::RemnawaveApiClient;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use tokio_rustls::rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProbeTargetKind {
+    Node,
+    Host,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TlsProbe {
+    pub sni: String,
+    /// Offered in this order; empty to skip ALPN.
+    pub alpn: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProbeTarget {
+    pub kind: ProbeTargetKind,
+    pub uuid: Uuid,
+    pub name: String,
+    pub address: String,
+    pub port: u16,
+    /// `None` probes TCP only.
+    pub tls: Option<TlsProbe>,
+    /// `NodeDto::is_connected` at the time of the probe; `None` for hosts.
+    pub panel_connected: Option<bool>,
+}
+
+impl ProbeTarget {
+    /// TCP connect to the node API port. The handshake there needs the panel's client certificate, so TLS is not
+    /// attempted.
+    pub fn from_node(node: &NodeDto) -> Self {
+        Self {
+            kind: ProbeTargetKind::Node,
+            uuid: node.uuid,
+            name: node.name.clone(),
+            address: node.address.clone(),
+            port: node.port,
+            tls: None,
+            panel_connected: Some(node.is_connected),
+        }
+    }
+
+    /// Hosts with [`SecurityLayerType::TLS`] get a TLS handshake with their `sni` (or address) and comma-separated
+    /// `alpn`. Other security layers are probed with TCP only, including `DEFAULT`, whose security depends on the
+    /// inbound; see [`ProbeTarget::from_host_with_inbound`]. Use [`ProbeTarget::with_tls`] to override.
+    pub fn from_host(host: &HostDto) -> Self {
+        Self::from_host_with_inbound(host, None)
+    }
+
+    /// Like [`ProbeTarget::from_host`], but a `DEFAULT` security layer follows the `security` of the host's
+    /// `inbound`: `tls` and `reality` inbounds get a TLS handshake.
+    pub fn from_host_with_inbound(host: &HostDto, inbound: Option<&Inbound>) -> Self {
+        let uses_tls = match host.security_layer {
+            SecurityLayerType::TLS => true,
+            SecurityLayerType::DEFAULT => matches!(inbound.and_then(|inbound| inbound.security.as_deref()), Some("tls" | "reality")),
+            SecurityLayerType::NONE => false,
+        };
+        let tls = uses_tls.then(|| TlsProbe {
+            sni: host.sni.clone().filter(|sni| !sni.is_empty()).unwrap_or_else(|| host.address.clone()),
+            alpn: host.alpn.iter().flat_map(|alpn| alpn.split(',')).map(str::trim).filter(|p| !p.is_empty()).map(str::to_string).collect(),
+        });
+        Self {
+            kind: ProbeTargetKind::Host,
+            uuid: host.uuid,
+            name: host.remark.clone(),
+            address: host.address.clone(),
+            port: host.port,
+            tls,
+            panel_connected: None,
+        }
+    }
+
+    pub fn with_tls(mut self, tls: Option<TlsProbe>) -> Self {
+        self.tls = tls;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum ProbeFailure {
+    /// DNS resolution or TCP connect failed.
+    Connect(String),
+    ConnectTimeout,
+    InvalidServerName(String),
+    /// Handshake failed, including certificate verification.
+    Tls(String),
+    TlsTimeout,
+}
+
+impl fmt::Display for ProbeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(e) => write!(f, "TCP connect failed: {e}"),
+            Self::ConnectTimeout => write!(f, "TCP connect timed out"),
+            Self::InvalidServerName(sni) => write!(f, "Invalid TLS server name: {sni}"),
+            Self::Tls(e) => write!(f, "TLS handshake failed: {e}"),
+            Self::TlsTimeout => write!(f, "TLS handshake timed out"),
+        }
+    }
+}
+
+impl std::error::Error for ProbeFailure {}
+
+/// Where a node problem is, from the panel's `is_connected` and the local probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReachabilityDiagnosis {
+    Healthy,
+    /// Reachable from here but the panel has no connection: panel-to-node link or node auth.
+    PanelLinkDown,
+    /// The panel is connected but the node is not reachable from here: firewall or routing on the client side.
+    ClientUnreachable,
+    /// Neither the panel nor the probe reach the node.
+    Down,
+}
+
+impl fmt::Display for ReachabilityDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_plain::to_string(self).unwrap())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProbeResult {
+    pub target: ProbeTarget,
+    pub connect_latency: Option<Duration>,
+    pub tls_latency: Option<Duration>,
+    pub negotiated_alpn: Option<String>,
+    pub failure: Option<ProbeFailure>,
+}
+
+impl ProbeResult {
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Connect plus handshake time.
+    pub fn total_latency(&self) -> Option<Duration> {
+        Some(self.connect_latency? + self.tls_latency.unwrap_or_default())
+    }
+
+    /// `None` for hosts.
+    pub fn diagnosis(&self) -> Option<ReachabilityDiagnosis> {
+        Some(match (self.target.panel_connected?, self.is_ok()) {
+            (true, true) => ReachabilityDiagnosis::Healthy,
+            (false, true) => ReachabilityDiagnosis::PanelLinkDown,
+            (true, false) => ReachabilityDiagnosis::ClientUnreachable,
+            (false, false) => ReachabilityDiagnosis::Down,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeOptions {
+    pub connect_timeout: Duration,
+    pub tls_timeout: Duration,
+    /// Certificates are checked against the webpki roots plus these DER certificates.
+    pub extra_roots: Vec<Vec<u8>>,
+    /// Skip certificate verification, e.g. for self-signed or Reality hosts. The handshake itself is still checked.
+    pub accept_invalid_certs: bool,
+    /// Targets probed at the same time.
+    pub concurrency: usize,
+    /// Skip disabled nodes and hosts in [`RemnawaveApiClient::probe_nodes_and_hosts`].
+    pub skip_disabled: bool,
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(5),
+            tls_timeout: Duration::from_secs(5),
+            extra_roots: Vec::new(),
+            accept_invalid_certs: false,
+            concurrency: 16,
+            skip_disabled: true,
+        }
+    }
+}
+
+impl ProbeOptions {
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn tls_timeout(mut self, tls_timeout: Duration) -> Self {
+        self.tls_timeout = tls_timeout;
+        self
+    }
+
+    pub fn add_root_certificate(mut self, der: impl Into<Vec<u8>>) -> Self {
+        self.extra_roots.push(der.into());
+        self
+    }
+
+    pub fn accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn skip_disabled(mut self, skip_disabled: bool) -> Self {
+        self.skip_disabled = skip_disabled;
+        self
+    }
+
+    fn client_config(&self, alpn: &[String]) -> ClientConfig {
+        let provider = Arc::new(ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions().expect("ring supports the default protocol versions");
+        let mut config = if self.accept_invalid_certs {
+            builder.dangerous().with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider))).with_no_client_auth()
+        } else {
+            let mut roots = RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            // Invalid extra roots are reported by the handshake as unknown issuer.
+            roots.add_parsable_certificates(self.extra_roots.iter().map(|der| CertificateDer::from(der.clone())));
+            builder.with_root_certificates(roots).with_no_client_auth()
+        };
+        config.alpn_protocols = alpn.iter().map(|p| p.as_bytes().to_vec()).collect();
+        config
+    }
+}
+
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _: &CertificateDer<'_>,
+        _: &[CertificateDer<'_>],
+        _: &ServerName<'_>,
+        _: &[u8],
+        _: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Connects to `target`, then runs the TLS handshake if configured. Failures are reported in the result.
+pub async fn probe(target: &ProbeTarget, options: &ProbeOptions) -> ProbeResult {
+    let mut result = ProbeResult {
+        target: target.clone(),
+        connect_latency: None,
+        tls_latency: None,
+        negotiated_alpn: None,
+        failure: None,
+    };
+
+    let started = Instant::now();
+    let tcp = match tokio::time::timeout(options.connect_timeout, TcpStream::connect((target.address.as_str(), target.port))).await {
+        Ok(Ok(tcp)) => tcp,
+        Ok(Err(e)) => {
+            result.failure = Some(ProbeFailure::Connect(e.to_string()));
+            return result;
+        }
+        Err(_) => {
+            result.failure = Some(ProbeFailure::ConnectTimeout);
+            return result;
+        }
+    };
+    result.connect_latency = Some(started.elapsed());
+
+    let Some(tls) = &target.tls else {
+        return result;
+    };
+    let Ok(server_name) = ServerName::try_from(tls.sni.clone()) else {
+        result.failure = Some(ProbeFailure::InvalidServerName(tls.sni.clone()));
+        return result;
+    };
+
+    let connector = TlsConnector::from(Arc::new(options.client_config(&tls.alpn)));
+    let started = Instant::now();
+    match tokio::time::timeout(options.tls_timeout, connector.connect(server_name, tcp)).await {
+        Ok(Ok(stream)) => {
+            result.tls_latency = Some(started.elapsed());
+            result.negotiated_alpn = stream.get_ref().1.alpn_protocol().map(|p| String::from_utf8_lossy(p).into_owned());
+        }
+        Ok(Err(e)) => result.failure = Some(ProbeFailure::Tls(e.to_string())),
+        Err(_) => result.failure = Some(ProbeFailure::TlsTimeout),
+    }
+    result
+}
+
+/// Probes up to `options.concurrency` targets at a time; results are in target order.
+pub async fn probe_all(targets: &[ProbeTarget], options: &ProbeOptions) -> Vec<ProbeResult> {
+    stream::iter(targets).map(|target| probe(target, options)).buffered(options.concurrency.max(1)).collect().await
+}
+
+impl RemnawaveApiClient {
+    /// Fetches all nodes, hosts and inbounds and probes the nodes and hosts from this machine, nodes first.
+    pub async fn probe_nodes_and_hosts(&self, options: &ProbeOptions) -> Result<Vec<ProbeResult>, crate::ApiError> {
+        let nodes = self.nodes.get_all().await?.response;
+        let hosts = self.hosts.get_all().await?.response;
+        let inbounds = self.config_profiles.get_all_inbounds().await?.response.inbounds;
+        let host_target =
+            |host: &HostDto| ProbeTarget::from_host_with_inbound(host, inbounds.iter().find(|inbound| host.inbound.config_profile_inbound_uuid == Some(inbound.uuid)));
+
+        let targets: Vec<ProbeTarget> = nodes
+            .iter()
+            .filter(|node| !(options.skip_disabled && node.is_disabled))
+            .map(ProbeTarget::from_node)
+            .chain(hosts.iter().filter(|host| !(options.skip_disabled && host.is_disabled)).map(host_target))
+            .collect();
+        Ok(probe_all(&targets, options).await)
+    }
+}
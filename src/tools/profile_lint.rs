@@ -0,0 +1,204 @@
+use crate::api::types::config_profiles::{Inbound, UpdateConfigProfileRequestDto, UpdateConfigProfileResponseDto};
+use crate::api::types::hosts::HostDto;
+use crate::api::types::internal_squads::InternalSquadDto;
+use crate::api::types::xray::{XrayConfig, XrayInbound, XrayPort};
+use crate::diagnostics::Diagnostic;
+use crate::{ApiError, RemnawaveApiClient};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum ProfileLintError {
+    Api(Box<ApiError>),
+    /// Diagnostics of the failed lint; contains at least one error.
+    Invalid(Vec<Diagnostic>),
+}
+
+impl fmt::Display for ProfileLintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(e) => write!(f, "{e}"),
+            Self::Invalid(diagnostics) => {
+                write!(f, "Invalid config profile")?;
+                for diagnostic in diagnostics.iter().filter(|d| d.is_error()) {
+                    write!(f, "; {diagnostic}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileLintError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Api(e) => Some(e.as_ref()),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<ApiError> for ProfileLintError {
+    fn from(e: ApiError) -> Self {
+        Self::Api(Box::new(e))
+    }
+}
+
+/// Panel state a profile is linted against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileLintContext {
+    pub profile_uuid: Uuid,
+    /// Inbounds of all profiles, as returned by `ConfigProfilesController::get_all_inbounds`.
+    pub inbounds: Vec<Inbound>,
+    pub hosts: Vec<HostDto>,
+    pub internal_squads: Vec<InternalSquadDto>,
+}
+
+impl ProfileLintContext {
+    /// [`lint_xray_config`] plus port collisions with other profiles and inbounds that no host or internal squad
+    /// uses. Inbounds the panel does not know yet are only checked locally.
+    pub fn lint(&self, config: &XrayConfig) -> Vec<Diagnostic> {
+        let mut diagnostics = lint_xray_config(config);
+
+        for (index, inbound) in config.inbounds.iter().enumerate() {
+            let pointer = format!("/inbounds/{index}");
+            let ranges = inbound.port.as_ref().map(port_ranges).unwrap_or_default();
+            for other in self.inbounds.iter().filter(|other| other.profile_uuid != self.profile_uuid) {
+                if other.port.is_some_and(|port| ranges.iter().any(|range| range.contains(&port))) {
+                    diagnostics.push(Diagnostic::warning(
+                        "port-used-by-other-profile",
+                        format!("{pointer}/port"),
+                        format!("port {} is also used by inbound '{}' of profile {}", other.port.unwrap_or_default(), other.tag, other.profile_uuid),
+                    ));
+                }
+            }
+
+            let Some(known) = inbound.tag.as_deref().and_then(|tag| self.inbounds.iter().find(|known| known.profile_uuid == self.profile_uuid && known.tag == tag)) else {
+                continue;
+            };
+            if !self.hosts.iter().any(|host| host.inbound.config_profile_inbound_uuid == Some(known.uuid)) {
+                diagnostics.push(Diagnostic::warning("inbound-without-host", format!("{pointer}/tag"), format!("no host uses inbound '{}'", known.tag)));
+            }
+            if !self.internal_squads.iter().any(|squad| squad.inbounds.iter().any(|squad_inbound| squad_inbound.uuid == known.uuid)) {
+                diagnostics.push(Diagnostic::warning("inbound-without-squad", format!("{pointer}/tag"), format!("no internal squad includes inbound '{}'", known.tag)));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Checks a config on its own: duplicate inbound tags, overlapping ports, incomplete Reality settings and routing
+/// rules that reference unknown tags. Pointers are into the serialized [`XrayConfig`].
+pub fn lint_xray_config(config: &XrayConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let mut tags: HashMap<&str, usize> = HashMap::new();
+    for (index, inbound) in config.inbounds.iter().enumerate() {
+        let Some(tag) = inbound.tag.as_deref() else {
+            continue;
+        };
+        if let Some(first) = tags.get(tag) {
+            diagnostics.push(Diagnostic::error("duplicate-inbound-tag", format!("/inbounds/{index}/tag"), format!("tag '{tag}' is already used by /inbounds/{first}")));
+        } else {
+            tags.insert(tag, index);
+        }
+    }
+
+    let ports: Vec<Vec<RangeInclusive<u16>>> = config.inbounds.iter().map(|inbound| inbound.port.as_ref().map(port_ranges).unwrap_or_default()).collect();
+    for (index, inbound) in config.inbounds.iter().enumerate() {
+        for (first, other) in config.inbounds.iter().enumerate().take(index) {
+            let overlap = ports[index].iter().any(|a| ports[first].iter().any(|b| a.start() <= b.end() && b.start() <= a.end()));
+            if overlap && listens_overlap(inbound, other) {
+                diagnostics.push(Diagnostic::error("port-collision", format!("/inbounds/{index}/port"), format!("port overlaps with /inbounds/{first}")));
+            }
+        }
+    }
+
+    for (index, inbound) in config.inbounds.iter().enumerate() {
+        if inbound.security() != Some("reality") {
+            continue;
+        }
+        let pointer = format!("/inbounds/{index}/streamSettings/realitySettings");
+        let Some(reality) = inbound.stream_settings.as_ref().and_then(|s| s.reality_settings.as_ref()) else {
+            diagnostics.push(Diagnostic::error("reality-missing-settings", pointer, "security is reality but realitySettings is missing"));
+            continue;
+        };
+        if reality.private_key.as_deref().is_none_or(str::is_empty) {
+            diagnostics.push(Diagnostic::error("reality-missing-private-key", format!("{pointer}/privateKey"), "Reality needs a privateKey"));
+        }
+        if reality.short_ids.is_empty() {
+            diagnostics.push(Diagnostic::error("reality-missing-short-ids", format!("{pointer}/shortIds"), "Reality needs at least one shortId"));
+        }
+        if reality.server_names.is_empty() {
+            diagnostics.push(Diagnostic::error("reality-missing-server-names", format!("{pointer}/serverNames"), "Reality needs at least one serverName"));
+        }
+    }
+
+    if let Some(routing) = &config.routing {
+        let outbounds: HashSet<&str> = config.outbounds.iter().filter_map(|o| o.tag.as_deref()).collect();
+        let balancers: HashSet<&str> = routing.balancers.iter().filter_map(|b| b.get("tag").and_then(|tag| tag.as_str())).collect();
+        let inbounds: HashSet<&str> = config.inbounds.iter().filter_map(|i| i.tag.as_deref()).chain(config.api.as_ref().and_then(|api| api.tag.as_deref())).collect();
+        for (index, rule) in routing.rules.iter().enumerate() {
+            let pointer = format!("/routing/rules/{index}");
+            if let Some(tag) = rule.outbound_tag.as_deref().filter(|tag| !outbounds.contains(tag)) {
+                diagnostics.push(Diagnostic::error("unknown-outbound-tag", format!("{pointer}/outboundTag"), format!("no outbound is tagged '{tag}'")));
+            }
+            if let Some(tag) = rule.balancer_tag.as_deref().filter(|tag| !balancers.contains(tag)) {
+                diagnostics.push(Diagnostic::error("unknown-balancer-tag", format!("{pointer}/balancerTag"), format!("no balancer is tagged '{tag}'")));
+            }
+            for (tag_index, tag) in rule.inbound_tag.iter().enumerate().filter(|(_, tag)| !inbounds.contains(tag.as_str())) {
+                diagnostics.push(Diagnostic::warning("unknown-inbound-tag", format!("{pointer}/inboundTag/{tag_index}"), format!("no inbound is tagged '{tag}'")));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Ports of `"443"`, `"1000-2000"` or `"53,443"`; empty for `env:` and other unparsable values.
+fn port_ranges(port: &XrayPort) -> Vec<RangeInclusive<u16>> {
+    match port {
+        XrayPort::Number(port) => vec![*port..=*port],
+        XrayPort::Text(text) => text
+            .split(',')
+            .filter_map(|part| match part.split_once('-') {
+                Some((from, to)) => Some(from.trim().parse().ok()?..=to.trim().parse().ok()?),
+                None => part.trim().parse().ok().map(|port| port..=port),
+            })
+            .collect(),
+    }
+}
+
+fn listens_overlap(a: &XrayInbound, b: &XrayInbound) -> bool {
+    let any = |listen: Option<&str>| matches!(listen, None | Some("" | "0.0.0.0" | "::"));
+    let (a, b) = (a.listen.as_deref(), b.listen.as_deref());
+    any(a) || any(b) || a == b
+}
+
+impl RemnawaveApiClient {
+    /// Fetches all inbounds, hosts and internal squads for linting profile `profile_uuid`.
+    pub async fn profile_lint_context(&self, profile_uuid: Uuid) -> Result<ProfileLintContext, ApiError> {
+        Ok(ProfileLintContext {
+            profile_uuid,
+            inbounds: self.config_profiles.get_all_inbounds().await?.response.inbounds,
+            hosts: self.hosts.get_all().await?.response,
+            internal_squads: self.internal_squads.get_all().await?.response.internal_squads,
+        })
+    }
+
+    pub async fn lint_config_profile(&self, profile_uuid: Uuid, config: &XrayConfig) -> Result<Vec<Diagnostic>, ApiError> {
+        Ok(self.profile_lint_context(profile_uuid).await?.lint(config))
+    }
+
+    /// Lints the new config against the panel and updates the profile only if there are no errors.
+    pub async fn update_config_profile_checked(&self, request: UpdateConfigProfileRequestDto) -> Result<UpdateConfigProfileResponseDto, ProfileLintError> {
+        let diagnostics = self.lint_config_profile(request.uuid, &request.config).await?;
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(ProfileLintError::Invalid(diagnostics));
+        }
+        Ok(self.config_profiles.update(request).await?)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::api::types::subscriptions::{RawSubscriptionUser, ShortUuidSubscriptionResponse, Subscription, UsernameSubscriptionResponse, UuidSubscriptionResponse};
+use crate::api::types::users::UserData;
+use qrcode::render::{svg, unicode};
+use qrcode::{Color, EcLevel};
+use std::fmt;
+
+/// Modules of blank border the QR spec asks for around the code.
+const QUIET_ZONE_MODULES: u32 = 4;
+/// Largest [`QrOptions::size`] that [`QrCode::to_png`] renders. The PNG can be a few pixels wider because its side is
+/// rounded up to whole modules.
+pub const MAX_PNG_SIDE: u32 = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QrErrorCorrection {
+    /// ~7% of the code can be restored.
+    Low,
+    /// ~15% of the code can be restored.
+    #[default]
+    Medium,
+    /// ~25% of the code can be restored.
+    Quartile,
+    /// ~30% of the code can be restored.
+    High,
+}
+
+impl From<QrErrorCorrection> for EcLevel {
+    fn from(level: QrErrorCorrection) -> Self {
+        match level {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QrOptions {
+    /// Minimum width and height of PNG and SVG output in pixels, at most [`MAX_PNG_SIDE`] for PNG. Terminal output is
+    /// always one character per module.
+    pub size: u32,
+    pub error_correction: QrErrorCorrection,
+    pub quiet_zone: bool,
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            size: 256,
+            error_correction: QrErrorCorrection::Medium,
+            quiet_zone: true,
+        }
+    }
+}
+
+impl QrOptions {
+    pub fn size(mut self, size: u32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn error_correction(mut self, error_correction: QrErrorCorrection) -> Self {
+        self.error_correction = error_correction;
+        self
+    }
+
+    pub fn quiet_zone(mut self, quiet_zone: bool) -> Self {
+        self.quiet_zone = quiet_zone;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum QrError {
+    /// The data does not fit into a QR code at the requested error correction level.
+    Encode(qrcode::types::QrError),
+    Png(png::EncodingError),
+    /// The requested PNG size is above [`MAX_PNG_SIDE`]; holds the requested size.
+    TooLarge(u32),
+}
+
+impl fmt::Display for QrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(e) => write!(f, "QR encoding error: {e}"),
+            Self::Png(e) => write!(f, "PNG encoding error: {e}"),
+            Self::TooLarge(size) => write!(f, "QR image of {size}x{size} pixels is too large"),
+        }
+    }
+}
+
+impl std::error::Error for QrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Encode(e) => Some(e),
+            Self::Png(e) => Some(e),
+            Self::TooLarge(_) => None,
+        }
+    }
+}
+
+impl From<qrcode::types::QrError> for QrError {
+    fn from(e: qrcode::types::QrError) -> Self {
+        Self::Encode(e)
+    }
+}
+
+impl From<png::EncodingError> for QrError {
+    fn from(e: png::EncodingError) -> Self {
+        Self::Png(e)
+    }
+}
+
+/// An encoded QR code for a subscription URL, Happ crypto link or share link.
+#[derive(Clone)]
+pub struct QrCode {
+    code: qrcode::QrCode,
+    options: QrOptions,
+}
+
+impl fmt::Debug for QrCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QrCode").field("width", &self.code.width()).field("options", &self.options).finish()
+    }
+}
+
+impl QrCode {
+    pub fn new(data: impl AsRef<[u8]>) -> Result<Self, QrError> {
+        Self::with_options(data, QrOptions::default())
+    }
+
+    pub fn with_options(data: impl AsRef<[u8]>, options: QrOptions) -> Result<Self, QrError> {
+        let code = qrcode::QrCode::with_error_correction_level(data, options.error_correction.into())?;
+        Ok(Self {
+            code,
+            options,
+        })
+    }
+
+    /// Number of modules per side, without the quiet zone.
+    pub fn width(&self) -> usize {
+        self.code.width()
+    }
+
+    /// Grayscale PNG, black modules on white. Fails with [`QrError::TooLarge`] for sizes above [`MAX_PNG_SIDE`].
+    pub fn to_png(&self) -> Result<Vec<u8>, QrError> {
+        if self.options.size > MAX_PNG_SIDE {
+            return Err(QrError::TooLarge(self.options.size));
+        }
+        let width = self.code.width() as u32;
+        let border = if self.options.quiet_zone {
+            QUIET_ZONE_MODULES
+        } else {
+            0
+        };
+        let modules = width + 2 * border;
+        let scale = self.options.size.div_ceil(modules).max(1);
+        let side = modules * scale;
+
+        let colors = self.code.to_colors();
+        let mut pixels = vec![255u8; side as usize * side as usize];
+        for (y, row) in pixels.chunks_exact_mut(side as usize).enumerate() {
+            let module_y = (y as u32 / scale).checked_sub(border).filter(|m| *m < width);
+            let Some(module_y) = module_y else {
+                continue;
+            };
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let module_x = (x as u32 / scale).checked_sub(border).filter(|m| *m < width);
+                if module_x.is_some_and(|module_x| colors[(module_y * width + module_x) as usize] == Color::Dark) {
+                    *pixel = 0;
+                }
+            }
+        }
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, side, side);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&pixels)?;
+        Ok(png)
+    }
+
+    pub fn to_svg(&self) -> String {
+        self.code.render::<svg::Color<'_>>().min_dimensions(self.options.size, self.options.size).quiet_zone(self.options.quiet_zone).build()
+    }
+
+    /// Unicode half blocks, two modules per character row. Light modules are drawn, so the code reads
+    /// correctly on dark terminal backgrounds.
+    pub fn to_terminal(&self) -> String {
+        self.code.render::<unicode::Dense1x2>().dark_color(unicode::Dense1x2::Light).light_color(unicode::Dense1x2::Dark).quiet_zone(self.options.quiet_zone).build()
+    }
+}
+
+macro_rules! impl_subscription_qr {
+    ($type:ty) => {
+        impl $type {
+            pub fn subscription_url_qr(&self, options: QrOptions) -> Result<QrCode, QrError> {
+                QrCode::with_options(&self.subscription_url, options)
+            }
+
+            pub fn happ_crypto_link_qr(&self, options: QrOptions) -> Result<QrCode, QrError> {
+                QrCode::with_options(&self.happ.crypto_link, options)
+            }
+        }
+    };
+}
+
+impl_subscription_qr!(UserData);
+impl_subscription_qr!(RawSubscriptionUser);
+impl_subscription_qr!(ShortUuidSubscriptionResponse);
+impl_subscription_qr!(UuidSubscriptionResponse);
+impl_subscription_qr!(UsernameSubscriptionResponse);
+
+impl Subscription {
+    pub fn subscription_url_qr(&self, options: QrOptions) -> Result<QrCode, QrError> {
+        QrCode::with_options(&self.subscription_url, options)
+    }
+
+    /// `None` if the panel did not return a Happ crypto link.
+    pub fn happ_crypto_link_qr(&self, options: QrOptions) -> Option<Result<QrCode, QrError>> {
+        self.happ.as_ref().map(|happ| QrCode::with_options(&happ.crypto_link, options))
+    }
+}
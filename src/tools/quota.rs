@@ -0,0 +1,207 @@
+use crate::api::types::nodes::{NodeDto, NodesUsageData};
+use crate::RemnawaveApiClient;
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuotaForecastOptions {
+    /// Defaults to the current UTC date.
+    pub today: Option<NaiveDate>,
+    /// Days of usage history averaged into the daily rate. The window is shortened to start no earlier than the
+    /// node's last reset and its creation date.
+    pub history_days: u32,
+    /// Projection horizon for nodes without a `traffic_reset_day`.
+    pub horizon_days: u32,
+    /// A node is at risk once its projected usage at reset reaches this share of the limit.
+    pub warn_ratio: f64,
+}
+
+impl Default for QuotaForecastOptions {
+    fn default() -> Self {
+        Self {
+            today: None,
+            history_days: 7,
+            horizon_days: 30,
+            warn_ratio: 0.9,
+        }
+    }
+}
+
+impl QuotaForecastOptions {
+    pub fn today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    pub fn history_days(mut self, history_days: u32) -> Self {
+        self.history_days = history_days;
+        self
+    }
+
+    pub fn horizon_days(mut self, horizon_days: u32) -> Self {
+        self.horizon_days = horizon_days;
+        self
+    }
+
+    pub fn warn_ratio(mut self, warn_ratio: f64) -> Self {
+        self.warn_ratio = warn_ratio;
+        self
+    }
+}
+
+/// Ordered from most to least urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaStatus {
+    Exceeded,
+    WillExceed,
+    AtRisk,
+    Ok,
+    /// Traffic tracking is off or there is no limit.
+    Unlimited,
+}
+
+impl std::fmt::Display for QuotaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_plain::to_string(self).unwrap())
+    }
+}
+
+/// Projection of one node's traffic. The quota is checked against raw node traffic; the `billed_*` fields show
+/// the same traffic after `consumption_multiplier`, i.e. what users on this node are charged.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeQuotaForecast {
+    pub node_uuid: uuid::Uuid,
+    pub node_name: String,
+    pub status: QuotaStatus,
+    pub limit_bytes: Option<usize>,
+    pub used_bytes: usize,
+    /// Usage in the history window divided by its calendar days; days without records count as zero.
+    pub daily_rate_bytes: f64,
+    /// `None` without a `traffic_reset_day`; the projection then covers `horizon_days`.
+    pub next_reset: Option<NaiveDate>,
+    pub projection_days: i64,
+    pub projected_bytes: u64,
+    /// First day on which the projected usage reaches the limit, if before the end of the projection.
+    pub limit_reached_on: Option<NaiveDate>,
+    pub consumption_multiplier: f32,
+    pub billed_daily_rate_bytes: f64,
+    pub billed_projected_bytes: u64,
+}
+
+/// `reset_day` in the given month, clamped to the length of the month.
+fn reset_in_month(year: i32, month: u32, reset_day: u32) -> NaiveDate {
+    let days_in_month = NaiveDate::from_ymd_opt(year + month as i32 / 12, month % 12 + 1, 1).expect("valid month").pred_opt().expect("valid date").day();
+    NaiveDate::from_ymd_opt(year, month, reset_day.clamp(1, days_in_month)).expect("clamped day")
+}
+
+/// Next occurrence of `reset_day` strictly after `today`, clamped to the length of the month.
+pub fn next_reset_date(today: NaiveDate, reset_day: u32) -> NaiveDate {
+    let this_month = reset_in_month(today.year(), today.month(), reset_day);
+    if this_month > today {
+        this_month
+    } else if today.month() == 12 {
+        reset_in_month(today.year() + 1, 1, reset_day)
+    } else {
+        reset_in_month(today.year(), today.month() + 1, reset_day)
+    }
+}
+
+/// Latest occurrence of `reset_day` on or before `today`.
+fn last_reset_date(today: NaiveDate, reset_day: u32) -> NaiveDate {
+    let this_month = reset_in_month(today.year(), today.month(), reset_day);
+    if this_month <= today {
+        this_month
+    } else if today.month() == 1 {
+        reset_in_month(today.year() - 1, 12, reset_day)
+    } else {
+        reset_in_month(today.year(), today.month() - 1, reset_day)
+    }
+}
+
+/// Daily usage rows per node, keyed by date.
+fn daily_usage(usage: &[NodesUsageData]) -> HashMap<uuid::Uuid, Vec<(NaiveDate, usize)>> {
+    let mut days: HashMap<uuid::Uuid, Vec<(NaiveDate, usize)>> = HashMap::new();
+    for row in usage {
+        if let Some(date) = row.date.get(..10).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) {
+            days.entry(row.node_uuid).or_default().push((date, row.total));
+        }
+    }
+    days
+}
+
+/// Average daily traffic from `start` up to, but not including, `today`.
+fn daily_rate(days: &[(NaiveDate, usize)], start: NaiveDate, today: NaiveDate) -> f64 {
+    let total: u128 = days.iter().filter(|(date, _)| *date >= start && *date < today).map(|(_, total)| *total as u128).sum();
+    total as f64 / (today - start).num_days().max(1) as f64
+}
+
+/// Forecasts every node's traffic up to its next reset, most urgent first.
+pub fn forecast_node_quotas(nodes: &[NodeDto], usage: &[NodesUsageData], options: &QuotaForecastOptions) -> Vec<NodeQuotaForecast> {
+    let today = options.today.unwrap_or_else(|| Utc::now().date_naive());
+    let since = today - Days::new(options.history_days.into());
+    let usage = daily_usage(usage);
+
+    let mut forecasts: Vec<NodeQuotaForecast> = nodes
+        .iter()
+        .map(|node| {
+            let used = node.traffic_used_bytes.unwrap_or_default();
+            let limit = node.traffic_limit_bytes.filter(|limit| node.is_traffic_tracking_active && *limit > 0);
+            let reset_day = node.traffic_reset_day.and_then(|day| u32::try_from(day).ok()).filter(|day| (1..=31).contains(day));
+            let created = DateTime::parse_from_rfc3339(&node.created_at).ok().map(|created| created.date_naive());
+            let start = [reset_day.map(|day| last_reset_date(today, day)), created].into_iter().flatten().fold(since, NaiveDate::max);
+            let rate = usage.get(&node.uuid).map_or(0.0, |days| daily_rate(days, start, today));
+            let next_reset = reset_day.map(|day| next_reset_date(today, day));
+            let projection_days = next_reset.map_or(options.horizon_days.into(), |reset| (reset - today).num_days());
+            let projected = used as f64 + rate * projection_days as f64;
+            let multiplier = node.consumption_multiplier as f64;
+
+            let limit_reached_on = limit.and_then(|limit| {
+                if used >= limit {
+                    return Some(today);
+                }
+                let days = ((limit - used) as f64 / rate).ceil();
+                (rate > 0.0 && days <= projection_days as f64).then(|| today + Days::new(days as u64))
+            });
+            let status = match limit {
+                None => QuotaStatus::Unlimited,
+                Some(limit) if used >= limit => QuotaStatus::Exceeded,
+                Some(limit) if projected >= limit as f64 => QuotaStatus::WillExceed,
+                Some(limit) if projected >= limit as f64 * options.warn_ratio => QuotaStatus::AtRisk,
+                Some(_) => QuotaStatus::Ok,
+            };
+
+            NodeQuotaForecast {
+                node_uuid: node.uuid,
+                node_name: node.name.clone(),
+                status,
+                limit_bytes: limit,
+                used_bytes: used,
+                daily_rate_bytes: rate,
+                next_reset,
+                projection_days,
+                projected_bytes: projected.round() as u64,
+                limit_reached_on,
+                consumption_multiplier: node.consumption_multiplier,
+                billed_daily_rate_bytes: rate * multiplier,
+                billed_projected_bytes: (projected * multiplier).round() as u64,
+            }
+        })
+        .collect();
+
+    forecasts.sort_by(|a, b| a.status.cmp(&b.status).then(a.limit_reached_on.cmp(&b.limit_reached_on)).then_with(|| a.node_name.cmp(&b.node_name)));
+    forecasts
+}
+
+impl RemnawaveApiClient {
+    /// Fetches all nodes and their usage over `history_days` and runs [`forecast_node_quotas`].
+    pub async fn forecast_node_quotas(&self, options: &QuotaForecastOptions) -> Result<Vec<NodeQuotaForecast>, crate::ApiError> {
+        let today = options.today.unwrap_or_else(|| Utc::now().date_naive());
+        let start = today - Days::new(options.history_days.into());
+
+        let nodes = self.nodes.get_all().await?.response;
+        let usage = self.nodes_usage.get_usage_by_range(Some(start.format("%Y-%m-%d").to_string()), Some(today.format("%Y-%m-%d").to_string())).await?.response;
+        Ok(forecast_node_quotas(&nodes, &usage, &options.today(today)))
+    }
+}
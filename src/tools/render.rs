@@ -0,0 +1,441 @@
+use crate::api::types::formats::{MihomoConfig, MihomoProxy, SingBoxConfig, SingBoxOutbound, SubscriptionConfig, SubscriptionFormatError, XrayJsonConfig, XrayJsonOutbound};
+use crate::api::types::subscriptions::{RawHost, RawSubscriptionResponse, SubscriptionTemplateType, TemplateSummary};
+use base64::Engine;
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+/// Tag of the proxy outbound inserted into every Xray JSON config.
+pub const XRAY_PROXY_OUTBOUND_TAG: &str = "proxy";
+
+#[derive(Debug)]
+pub enum RenderError {
+    /// `XRAY_BASE64` templates have no body to render into.
+    UnsupportedTemplateType(SubscriptionTemplateType),
+    /// The template has neither `template_json` nor `encoded_template_yaml` for its type.
+    MissingTemplateBody(SubscriptionTemplateType),
+    Base64(base64::DecodeError),
+    Utf8(std::string::FromUtf8Error),
+    Format(SubscriptionFormatError),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedTemplateType(t) => write!(f, "templates of type {t} cannot be rendered"),
+            Self::MissingTemplateBody(t) => write!(f, "{t} template has no body"),
+            Self::Base64(e) => write!(f, "invalid base64 template: {e}"),
+            Self::Utf8(e) => write!(f, "template is not valid UTF-8: {e}"),
+            Self::Format(e) => write!(f, "invalid template: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnsupportedTemplateType(_) | Self::MissingTemplateBody(_) => None,
+            Self::Base64(e) => Some(e),
+            Self::Utf8(e) => Some(e),
+            Self::Format(e) => Some(e),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for RenderError {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Base64(e)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for RenderError {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        Self::Utf8(e)
+    }
+}
+
+impl From<serde_yaml::Error> for RenderError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Format(e.into())
+    }
+}
+
+impl From<serde_json::Error> for RenderError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Format(e.into())
+    }
+}
+
+/// Renders `template` for the user and hosts of `subscription` the way the panel does:
+///
+/// - Mihomo / Clash / Stash: every host is appended to `proxies`; proxy groups marked with
+///   `remnawave: { include-proxies: true }` get all host names, and the `remnawave` key is stripped.
+/// - sing-box: every host is appended to `outbounds`, and its tag is added to every `selector` and `urltest` outbound.
+/// - Xray JSON: one config per host, the template with a `proxy` outbound prepended and `remarks` set.
+///
+/// Disabled and hidden hosts (per `db_data`) and hosts whose protocol or transport the target format
+/// cannot express are skipped. `{{USERNAME}}`-style placeholders in remarks are substituted, see [`substitute_placeholders`].
+pub fn render_template(template: &TemplateSummary, subscription: &RawSubscriptionResponse) -> Result<SubscriptionConfig, RenderError> {
+    let hosts: Vec<(String, &RawHost)> = subscription
+        .raw_hosts
+        .iter()
+        .filter(|host| host.is_served())
+        .map(|host| (substitute_placeholders(host.remark.as_deref().unwrap_or_default(), subscription), host))
+        .collect();
+
+    match template.template_type {
+        SubscriptionTemplateType::Mihomo | SubscriptionTemplateType::Stash | SubscriptionTemplateType::Clash => {
+            let encoded = template.encoded_template_yaml.as_deref().ok_or(RenderError::MissingTemplateBody(template.template_type.clone()))?;
+            let yaml = String::from_utf8(base64::engine::general_purpose::STANDARD.decode(encoded.trim())?)?;
+            let mut config: MihomoConfig = serde_yaml::from_str(&yaml)?;
+            let proxies: Vec<MihomoProxy> = hosts.iter().filter_map(|(name, host)| mihomo_proxy(name, host)).collect();
+            render_mihomo(&mut config, proxies);
+            Ok(SubscriptionConfig::Mihomo(config))
+        }
+        SubscriptionTemplateType::Singbox => {
+            let body = template.template_json.clone().ok_or(RenderError::MissingTemplateBody(template.template_type.clone()))?;
+            let mut config: SingBoxConfig = serde_json::from_value(body)?;
+            let outbounds: Vec<SingBoxOutbound> = hosts.iter().filter_map(|(tag, host)| singbox_outbound(tag, host)).collect();
+            render_singbox(&mut config, outbounds);
+            Ok(SubscriptionConfig::SingBox(config))
+        }
+        SubscriptionTemplateType::XrayJson => {
+            let body = template.template_json.clone().ok_or(RenderError::MissingTemplateBody(template.template_type.clone()))?;
+            let base: XrayJsonConfig = serde_json::from_value(body)?;
+            let configs = hosts
+                .iter()
+                .filter_map(|(remarks, host)| {
+                    let mut config = base.clone();
+                    config.remarks = Some(remarks.clone());
+                    config.outbounds.insert(0, xray_outbound(host)?);
+                    Some(config)
+                })
+                .collect();
+            Ok(SubscriptionConfig::XrayJson(configs))
+        }
+        SubscriptionTemplateType::XrayBase64 => Err(RenderError::UnsupportedTemplateType(SubscriptionTemplateType::XrayBase64)),
+    }
+}
+
+impl TemplateSummary {
+    /// See [`render_template`].
+    pub fn render(&self, subscription: &RawSubscriptionResponse) -> Result<SubscriptionConfig, RenderError> {
+        render_template(self, subscription)
+    }
+}
+
+/// Replaces the panel's user placeholders (`{{USERNAME}}`, `{{EMAIL}}`, `{{TELEGRAM_ID}}`, `{{TAG}}`,
+/// `{{SUBSCRIPTION_URL}}`, `{{STATUS}}`, `{{DAYS_LEFT}}`, `{{TRAFFIC_USED}}`, `{{TOTAL_TRAFFIC}}`, `{{EXPIRE_UNIX}}`).
+/// Unknown placeholders are left as is; optional values that are unset become empty.
+pub fn substitute_placeholders(text: &str, subscription: &RawSubscriptionResponse) -> String {
+    if !text.contains("{{") {
+        return text.to_string();
+    }
+
+    let user = &subscription.user;
+    let info = &subscription.converted_user_info;
+    let values = [
+        ("USERNAME", user.username.clone()),
+        ("EMAIL", user.email.clone().unwrap_or_default()),
+        ("TELEGRAM_ID", user.telegram_id.map(|id| id.to_string()).unwrap_or_default()),
+        ("TAG", user.tag.clone().unwrap_or_default()),
+        ("SUBSCRIPTION_URL", user.subscription_url.clone()),
+        ("STATUS", user.status.to_string()),
+        ("DAYS_LEFT", (info.days_left.max(0.0).floor() as i64).to_string()),
+        ("TRAFFIC_USED", info.traffic_used.clone()),
+        ("TOTAL_TRAFFIC", info.traffic_limit.clone()),
+        ("EXPIRE_UNIX", user.expire_at.timestamp().to_string()),
+    ];
+
+    let mut result = text.to_string();
+    for (key, value) in values {
+        result = result.replace(&format!("{{{{{key}}}}}"), &value);
+    }
+    result
+}
+
+fn render_mihomo(config: &mut MihomoConfig, proxies: Vec<MihomoProxy>) {
+    let names: Vec<String> = proxies.iter().map(|p| p.name.clone()).collect();
+    for group in config.proxy_groups.iter_mut() {
+        let Some(marker) = group.extra.remove("remnawave") else {
+            continue;
+        };
+        if marker.get("include-proxies").and_then(Value::as_bool).unwrap_or(false) {
+            group.proxies.extend(names.iter().cloned());
+        }
+    }
+    config.proxies.extend(proxies);
+}
+
+fn render_singbox(config: &mut SingBoxConfig, outbounds: Vec<SingBoxOutbound>) {
+    let tags: Vec<String> = outbounds.iter().map(|o| o.tag.clone()).collect();
+    for outbound in config.outbounds.iter_mut().filter(|o| o.outbound_type == "selector" || o.outbound_type == "urltest") {
+        outbound.outbounds.extend(tags.iter().cloned());
+    }
+    config.outbounds.extend(outbounds);
+}
+
+fn network(host: &RawHost) -> &str {
+    match host.network.as_deref() {
+        None | Some("raw") | Some("") => "tcp",
+        Some(network) => network,
+    }
+}
+
+fn alpn(host: &RawHost) -> Vec<String> {
+    host.alpn.as_deref().unwrap_or_default().split(',').map(str::trim).filter(|a| !a.is_empty()).map(String::from).collect()
+}
+
+fn ss_method(host: &RawHost) -> String {
+    host.protocol_options.as_ref().and_then(|o| o.ss.as_ref()).and_then(|ss| ss.method.clone()).unwrap_or_else(|| "chacha20-ietf-poly1305".to_string())
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
+fn insert_some(map: &mut Map<String, Value>, key: &str, value: Option<impl Into<Value>>) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), value.into());
+    }
+}
+
+fn mihomo_proxy(name: &str, host: &RawHost) -> Option<MihomoProxy> {
+    let mut extra = Map::new();
+    let security = host.tls.as_deref().unwrap_or_default();
+
+    let proxy_type = match host.protocol.as_deref()? {
+        "vless" => {
+            extra.insert("uuid".into(), host.password.vless.clone().into());
+            insert_some(&mut extra, "flow", non_empty(&host.flow));
+            "vless"
+        }
+        "trojan" => {
+            extra.insert("password".into(), host.password.trojan.clone().into());
+            "trojan"
+        }
+        "shadowsocks" => {
+            extra.insert("cipher".into(), ss_method(host).into());
+            extra.insert("password".into(), host.password.ss.clone().into());
+            extra.insert("udp".into(), true.into());
+            return Some(MihomoProxy {
+                name: name.to_string(),
+                proxy_type: "ss".to_string(),
+                server: host.address.clone(),
+                port: host.port,
+                extra,
+            });
+        }
+        _ => return None,
+    };
+
+    extra.insert("udp".into(), true.into());
+    match network(host) {
+        "tcp" => {}
+        "ws" => {
+            extra.insert("network".into(), "ws".into());
+            extra.insert("ws-opts".into(), json!({ "path": host.path.clone().unwrap_or_default(), "headers": { "Host": host.host.clone().unwrap_or_default() } }));
+        }
+        "httpupgrade" => {
+            extra.insert("network".into(), "ws".into());
+            extra.insert(
+                "ws-opts".into(),
+                json!({ "path": host.path.clone().unwrap_or_default(), "headers": { "Host": host.host.clone().unwrap_or_default() }, "v2ray-http-upgrade": true }),
+            );
+        }
+        "grpc" => {
+            extra.insert("network".into(), "grpc".into());
+            extra.insert("grpc-opts".into(), json!({ "grpc-service-name": host.path.clone().unwrap_or_default() }));
+        }
+        _ => return None,
+    }
+
+    if security == "tls" || security == "reality" {
+        extra.insert("tls".into(), true.into());
+        insert_some(
+            &mut extra,
+            if proxy_type == "trojan" {
+                "sni"
+            } else {
+                "servername"
+            },
+            non_empty(&host.sni),
+        );
+        insert_some(&mut extra, "client-fingerprint", non_empty(&host.fingerprint));
+        let alpn = alpn(host);
+        if !alpn.is_empty() {
+            extra.insert("alpn".into(), alpn.into());
+        }
+        if host.allow_insecure == Some(true) {
+            extra.insert("skip-cert-verify".into(), true.into());
+        }
+    }
+    if security == "reality" {
+        extra.insert("reality-opts".into(), json!({ "public-key": host.public_key.clone().unwrap_or_default(), "short-id": host.short_id.clone().unwrap_or_default() }));
+    }
+
+    Some(MihomoProxy {
+        name: name.to_string(),
+        proxy_type: proxy_type.to_string(),
+        server: host.address.clone(),
+        port: host.port,
+        extra,
+    })
+}
+
+fn singbox_outbound(tag: &str, host: &RawHost) -> Option<SingBoxOutbound> {
+    let mut extra = Map::new();
+    let security = host.tls.as_deref().unwrap_or_default();
+
+    let outbound_type = match host.protocol.as_deref()? {
+        "vless" => {
+            extra.insert("uuid".into(), host.password.vless.clone().into());
+            insert_some(&mut extra, "flow", non_empty(&host.flow));
+            extra.insert("packet_encoding".into(), "xudp".into());
+            "vless"
+        }
+        "trojan" => {
+            extra.insert("password".into(), host.password.trojan.clone().into());
+            "trojan"
+        }
+        "shadowsocks" => {
+            extra.insert("method".into(), ss_method(host).into());
+            extra.insert("password".into(), host.password.ss.clone().into());
+            return Some(SingBoxOutbound {
+                outbound_type: "shadowsocks".to_string(),
+                tag: tag.to_string(),
+                server: host.address.clone(),
+                server_port: host.port,
+                outbounds: Vec::new(),
+                extra,
+            });
+        }
+        _ => return None,
+    };
+
+    let transport = match network(host) {
+        "tcp" => None,
+        "ws" => Some(json!({ "type": "ws", "path": host.path.clone().unwrap_or_default(), "headers": { "Host": host.host.clone().unwrap_or_default() } })),
+        "httpupgrade" => Some(json!({ "type": "httpupgrade", "path": host.path.clone().unwrap_or_default(), "host": host.host.clone().unwrap_or_default() })),
+        "grpc" => Some(json!({ "type": "grpc", "service_name": host.path.clone().unwrap_or_default() })),
+        _ => return None,
+    };
+    insert_some(&mut extra, "transport", transport);
+
+    if security == "tls" || security == "reality" {
+        let mut tls = Map::new();
+        tls.insert("enabled".into(), true.into());
+        insert_some(&mut tls, "server_name", non_empty(&host.sni));
+        if host.allow_insecure == Some(true) {
+            tls.insert("insecure".into(), true.into());
+        }
+        let alpn = alpn(host);
+        if !alpn.is_empty() {
+            tls.insert("alpn".into(), alpn.into());
+        }
+        if let Some(fingerprint) = non_empty(&host.fingerprint) {
+            tls.insert("utls".into(), json!({ "enabled": true, "fingerprint": fingerprint }));
+        }
+        if security == "reality" {
+            tls.insert(
+                "reality".into(),
+                json!({ "enabled": true, "public_key": host.public_key.clone().unwrap_or_default(), "short_id": host.short_id.clone().unwrap_or_default() }),
+            );
+        }
+        extra.insert("tls".into(), tls.into());
+    }
+
+    Some(SingBoxOutbound {
+        outbound_type: outbound_type.to_string(),
+        tag: tag.to_string(),
+        server: host.address.clone(),
+        server_port: host.port,
+        outbounds: Vec::new(),
+        extra,
+    })
+}
+
+fn xray_outbound(host: &RawHost) -> Option<XrayJsonOutbound> {
+    let address = host.address.clone().unwrap_or_default();
+    let port = host.port.unwrap_or_default();
+
+    let (protocol, settings) = match host.protocol.as_deref()? {
+        "vless" => {
+            let mut user = Map::new();
+            user.insert("id".into(), host.password.vless.clone().into());
+            user.insert("encryption".into(), non_empty(&host.encryption).unwrap_or("none").into());
+            insert_some(&mut user, "flow", non_empty(&host.flow));
+            ("vless", json!({ "vnext": [{ "address": address, "port": port, "users": [user] }] }))
+        }
+        "trojan" => ("trojan", json!({ "servers": [{ "address": address, "port": port, "password": host.password.trojan }] })),
+        "shadowsocks" => ("shadowsocks", json!({ "servers": [{ "address": address, "port": port, "password": host.password.ss, "method": ss_method(host) }] })),
+        _ => return None,
+    };
+
+    let network = network(host);
+    let path = host.path.clone().unwrap_or_default();
+    let host_header = host.host.clone().unwrap_or_default();
+    let mut stream = Map::new();
+    stream.insert("network".into(), network.into());
+    match network {
+        "tcp" => {}
+        "ws" => {
+            stream.insert("wsSettings".into(), json!({ "path": path, "headers": { "Host": host_header } }));
+        }
+        "httpupgrade" => {
+            stream.insert("httpupgradeSettings".into(), json!({ "path": path, "host": host_header }));
+        }
+        "grpc" => {
+            stream.insert("grpcSettings".into(), json!({ "serviceName": path }));
+        }
+        "xhttp" => {
+            let mut xhttp = Map::new();
+            xhttp.insert("path".into(), path.into());
+            xhttp.insert("host".into(), host_header.into());
+            insert_some(&mut xhttp, "mode", host.additional_params.as_ref().and_then(|p| p.mode.clone()));
+            insert_some(&mut xhttp, "extra", host.x_http_extra_params.clone().map(|extra| extra.into_iter().collect::<Map<String, Value>>()));
+            stream.insert("xhttpSettings".into(), xhttp.into());
+        }
+        _ => return None,
+    }
+
+    let security = host.tls.as_deref().filter(|s| *s == "tls" || *s == "reality").unwrap_or("none");
+    stream.insert("security".into(), security.into());
+    match security {
+        "tls" => {
+            let mut tls = Map::new();
+            insert_some(&mut tls, "serverName", non_empty(&host.sni));
+            insert_some(&mut tls, "fingerprint", non_empty(&host.fingerprint));
+            let alpn = alpn(host);
+            if !alpn.is_empty() {
+                tls.insert("alpn".into(), alpn.into());
+            }
+            if host.allow_insecure == Some(true) {
+                tls.insert("allowInsecure".into(), true.into());
+            }
+            stream.insert("tlsSettings".into(), tls.into());
+        }
+        "reality" => {
+            let mut reality = Map::new();
+            insert_some(&mut reality, "serverName", non_empty(&host.sni));
+            insert_some(&mut reality, "fingerprint", non_empty(&host.fingerprint));
+            insert_some(&mut reality, "publicKey", host.public_key.clone());
+            insert_some(&mut reality, "shortId", host.short_id.clone());
+            insert_some(&mut reality, "spiderX", non_empty(&host.spider_x));
+            insert_some(&mut reality, "mldsa65Verify", non_empty(&host.mldsa_65_verify));
+            stream.insert("realitySettings".into(), reality.into());
+        }
+        _ => {}
+    }
+    insert_some(&mut stream, "sockopt", host.sockopt_params.clone());
+
+    let mut extra = Map::new();
+    insert_some(&mut extra, "mux", host.mux_params.clone());
+
+    Some(XrayJsonOutbound {
+        tag: Some(XRAY_PROXY_OUTBOUND_TAG.to_string()),
+        protocol: protocol.to_string(),
+        settings: Some(settings),
+        stream_settings: Some(stream.into()),
+        extra,
+    })
+}
@@ -0,0 +1,429 @@
+use crate::api::controllers::NodesController;
+use crate::api::types::nodes::NodeDto;
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestartOrder {
+    #[default]
+    ViewPosition,
+    /// By `country_code`, then `view_position`, so one country is restarted before the next.
+    Country,
+    /// The order of [`RollingRestartOptions::nodes`]; falls back to `view_position` without an explicit list.
+    AsGiven,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestartFailurePolicy {
+    /// Stop after the batch with the failure; remaining nodes are not restarted.
+    #[default]
+    Abort,
+    /// Pause after the batch until [`RollingRestartControl::resume`] or [`RollingRestartControl::abort`] is called.
+    Pause,
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollingRestartOptions {
+    pub batch_size: usize,
+    pub order: RestartOrder,
+    /// Restrict the restart to these nodes. Defaults to every node that is not disabled.
+    pub nodes: Option<Vec<Uuid>>,
+    pub poll_interval: Duration,
+    /// Per batch, from sending the restarts until every node in it is ready.
+    pub timeout: Duration,
+    /// A node that is connected with Xray running is taken as restarted after this long even if the restart
+    /// itself was never observed (no downtime seen, uptime and status timestamp unchanged).
+    pub settle_time: Duration,
+    pub on_failure: RestartFailurePolicy,
+}
+
+impl Default for RollingRestartOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            order: RestartOrder::default(),
+            nodes: None,
+            poll_interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(120),
+            settle_time: Duration::from_secs(30),
+            on_failure: RestartFailurePolicy::default(),
+        }
+    }
+}
+
+impl RollingRestartOptions {
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn order(mut self, order: RestartOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn nodes(mut self, nodes: Vec<Uuid>) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+
+    pub fn on_failure(mut self, on_failure: RestartFailurePolicy) -> Self {
+        self.on_failure = on_failure;
+        self
+    }
+}
+
+/// Splits `nodes` into restart batches according to `options`, without restarting anything.
+pub fn plan_rolling_restart(nodes: &[NodeDto], options: &RollingRestartOptions) -> Vec<Vec<Uuid>> {
+    let mut selected: Vec<&NodeDto> = match &options.nodes {
+        Some(uuids) => uuids.iter().filter_map(|uuid| nodes.iter().find(|n| n.uuid == *uuid)).collect(),
+        None => nodes.iter().filter(|n| !n.is_disabled).collect(),
+    };
+    match options.order {
+        RestartOrder::ViewPosition => selected.sort_by_key(|n| n.view_position),
+        RestartOrder::Country => selected.sort_by(|a, b| a.country_code.cmp(&b.country_code).then(a.view_position.cmp(&b.view_position))),
+        RestartOrder::AsGiven if options.nodes.is_none() => selected.sort_by_key(|n| n.view_position),
+        RestartOrder::AsGiven => {}
+    }
+    selected.chunks(options.batch_size.max(1)).map(|batch| batch.iter().map(|n| n.uuid).collect()).collect()
+}
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const ABORTED: u8 = 2;
+
+/// Pauses, resumes or aborts a running [`NodesController::rolling_restart`]. Pausing takes effect before the next
+/// batch; aborting also stops waiting for the current one.
+#[derive(Debug, Clone, Default)]
+pub struct RollingRestartControl(Arc<AtomicU8>);
+
+impl RollingRestartControl {
+    pub fn pause(&self) {
+        let _ = self.0.compare_exchange(RUNNING, PAUSED, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.0.compare_exchange(PAUSED, RUNNING, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    pub fn abort(&self) {
+        self.0.store(ABORTED, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.load(Ordering::SeqCst) == PAUSED
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.0.load(Ordering::SeqCst) == ABORTED
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum RestartFailure {
+    Request(String),
+    /// The panel answered `eventSent: false`.
+    NotSent,
+    Timeout,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RollingRestartEvent {
+    Planned {
+        batches: Vec<Vec<Uuid>>,
+    },
+    BatchStarted {
+        index: usize,
+        nodes: Vec<Uuid>,
+    },
+    NodeReady {
+        node_uuid: Uuid,
+        elapsed: Duration,
+    },
+    NodeFailed {
+        node_uuid: Uuid,
+        failure: RestartFailure,
+    },
+    BatchCompleted {
+        index: usize,
+    },
+    Paused,
+    Resumed,
+    /// Terminal. `remaining` were never restarted.
+    Aborted {
+        remaining: Vec<Uuid>,
+    },
+    /// Terminal.
+    Finished {
+        restarted: Vec<Uuid>,
+        failed: Vec<Uuid>,
+    },
+}
+
+/// What a node looked like right before its restart was sent.
+#[derive(Debug, Clone)]
+struct Baseline {
+    uptime: Option<f64>,
+    last_status_change: Option<String>,
+    seen_down: bool,
+}
+
+impl Baseline {
+    fn new(node: Option<&NodeDto>) -> Self {
+        Self {
+            uptime: node.and_then(|n| n.xray_uptime.parse().ok()),
+            last_status_change: node.and_then(|n| n.last_status_change.clone()),
+            seen_down: false,
+        }
+    }
+
+    fn is_ready(&mut self, node: &NodeDto, elapsed: Duration, settle_time: Duration) -> bool {
+        let up = node.is_connected && node.is_xray_running;
+        self.seen_down |= !up;
+        let uptime_reset = matches!((self.uptime, node.xray_uptime.parse::<f64>().ok()), (Some(before), Some(now)) if now < before);
+        let status_changed = node.last_status_change.is_some() && node.last_status_change != self.last_status_change;
+        up && (self.seen_down || uptime_reset || status_changed || elapsed >= settle_time)
+    }
+}
+
+enum Phase {
+    Plan,
+    StartBatch,
+    Wait {
+        started: Instant,
+        pending: HashMap<Uuid, Baseline>,
+    },
+    Done,
+}
+
+struct Run<'a> {
+    controller: &'a NodesController,
+    options: RollingRestartOptions,
+    control: RollingRestartControl,
+    phase: Phase,
+    queue: VecDeque<Result<RollingRestartEvent, crate::ApiError>>,
+    batches: VecDeque<Vec<Uuid>>,
+    batch_index: usize,
+    batch_failed: bool,
+    paused: bool,
+    latest: HashMap<Uuid, NodeDto>,
+    restarted: Vec<Uuid>,
+    failed: Vec<Uuid>,
+}
+
+impl Run<'_> {
+    fn emit(&mut self, event: RollingRestartEvent) {
+        self.queue.push_back(Ok(event));
+    }
+
+    fn abort(&mut self) {
+        let remaining = self.batches.drain(..).flatten().collect();
+        self.emit(RollingRestartEvent::Aborted {
+            remaining,
+        });
+        self.phase = Phase::Done;
+    }
+
+    fn fail(&mut self, node_uuid: Uuid, failure: RestartFailure) {
+        self.batch_failed = true;
+        self.failed.push(node_uuid);
+        self.emit(RollingRestartEvent::NodeFailed {
+            node_uuid,
+            failure,
+        });
+    }
+
+    async fn refresh(&mut self) -> Result<(), crate::ApiError> {
+        let nodes = self.controller.get_all().await?.response;
+        self.latest = nodes.into_iter().map(|n| (n.uuid, n)).collect();
+        Ok(())
+    }
+
+    async fn advance(&mut self) {
+        match std::mem::replace(&mut self.phase, Phase::Done) {
+            Phase::Plan => {
+                if let Err(e) = self.refresh().await {
+                    self.queue.push_back(Err(e));
+                    return;
+                }
+                let mut nodes: Vec<NodeDto> = self.latest.values().cloned().collect();
+                nodes.sort_by_key(|n| n.uuid);
+                let batches = plan_rolling_restart(&nodes, &self.options);
+                self.emit(RollingRestartEvent::Planned {
+                    batches: batches.clone(),
+                });
+                self.batches = batches.into();
+                self.phase = Phase::StartBatch;
+            }
+            Phase::StartBatch => self.start_batch().await,
+            Phase::Wait {
+                started,
+                pending,
+            } => self.wait(started, pending).await,
+            Phase::Done => {}
+        }
+    }
+
+    async fn start_batch(&mut self) {
+        if self.control.is_aborted() {
+            return self.abort();
+        }
+        if self.control.is_paused() {
+            if !std::mem::replace(&mut self.paused, true) {
+                self.emit(RollingRestartEvent::Paused);
+            }
+            tokio::time::sleep(self.options.poll_interval).await;
+            self.phase = Phase::StartBatch;
+            return;
+        }
+        if std::mem::replace(&mut self.paused, false) {
+            self.emit(RollingRestartEvent::Resumed);
+        }
+
+        let Some(batch) = self.batches.pop_front() else {
+            let (restarted, failed) = (std::mem::take(&mut self.restarted), std::mem::take(&mut self.failed));
+            return self.emit(RollingRestartEvent::Finished {
+                restarted,
+                failed,
+            });
+        };
+        self.batch_failed = false;
+        self.emit(RollingRestartEvent::BatchStarted {
+            index: self.batch_index,
+            nodes: batch.clone(),
+        });
+
+        let mut pending = HashMap::new();
+        for uuid in batch {
+            let baseline = Baseline::new(self.latest.get(&uuid));
+            match self.controller.restart(uuid).await {
+                Ok(response) if response.response.event_sent => {
+                    pending.insert(uuid, baseline);
+                }
+                Ok(_) => self.fail(uuid, RestartFailure::NotSent),
+                Err(e) => self.fail(uuid, RestartFailure::Request(e.to_string())),
+            }
+        }
+        self.phase = Phase::Wait {
+            started: Instant::now(),
+            pending,
+        };
+    }
+
+    async fn wait(&mut self, started: Instant, mut pending: HashMap<Uuid, Baseline>) {
+        if !pending.is_empty() {
+            tokio::time::sleep(self.options.poll_interval).await;
+            if self.control.is_aborted() {
+                self.failed.extend(pending.into_keys());
+                return self.abort();
+            }
+
+            let elapsed = started.elapsed();
+            // A failed poll just means another try on the next interval, until the timeout.
+            if self.refresh().await.is_ok() {
+                let mut ready: Vec<Uuid> = pending
+                    .iter_mut()
+                    .filter_map(|(uuid, baseline)| self.latest.get(uuid).is_some_and(|node| baseline.is_ready(node, elapsed, self.options.settle_time)).then_some(*uuid))
+                    .collect();
+                ready.sort();
+                for node_uuid in ready {
+                    pending.remove(&node_uuid);
+                    self.restarted.push(node_uuid);
+                    self.emit(RollingRestartEvent::NodeReady {
+                        node_uuid,
+                        elapsed,
+                    });
+                }
+            }
+
+            if elapsed >= self.options.timeout {
+                let mut timed_out: Vec<Uuid> = pending.drain().map(|(uuid, _)| uuid).collect();
+                timed_out.sort();
+                for uuid in timed_out {
+                    self.fail(uuid, RestartFailure::Timeout);
+                }
+            }
+            if !pending.is_empty() {
+                self.phase = Phase::Wait {
+                    started,
+                    pending,
+                };
+                return;
+            }
+        }
+
+        self.emit(RollingRestartEvent::BatchCompleted {
+            index: self.batch_index,
+        });
+        self.batch_index += 1;
+        self.phase = Phase::StartBatch;
+        if self.batch_failed && !self.batches.is_empty() {
+            match self.options.on_failure {
+                RestartFailurePolicy::Abort => self.abort(),
+                RestartFailurePolicy::Pause => self.control.pause(),
+                RestartFailurePolicy::Continue => {}
+            }
+        }
+    }
+}
+
+impl NodesController {
+    /// Restarts nodes batch by batch, waiting for each batch to report `is_connected && is_xray_running` again
+    /// before starting the next. Progress is streamed; the stream ends after `Finished` or `Aborted`, or after an
+    /// error listing the nodes up front.
+    pub fn rolling_restart(&self, options: RollingRestartOptions) -> (RollingRestartControl, impl Stream<Item = Result<RollingRestartEvent, crate::ApiError>> + '_) {
+        let control = RollingRestartControl::default();
+        let run = Run {
+            controller: self,
+            options,
+            control: control.clone(),
+            phase: Phase::Plan,
+            queue: VecDeque::new(),
+            batches: VecDeque::new(),
+            batch_index: 0,
+            batch_failed: false,
+            paused: false,
+            latest: HashMap::new(),
+            restarted: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let events = stream::unfold(run, |mut run| async move {
+            loop {
+                if let Some(event) = run.queue.pop_front() {
+                    return Some((event, run));
+                }
+                if matches!(run.phase, Phase::Done) {
+                    return None;
+                }
+                run.advance().await;
+            }
+        });
+        (control, events)
+    }
+}
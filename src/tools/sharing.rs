@@ -0,0 +1,338 @@
+use crate::api::controllers::SubscriptionRequestHistoryController;
+use crate::api::types::hwid::HwidDeviceDto;
+use crate::api::types::subscription_request_history::SubscriptionRequestRecord;
+use crate::api::types::users::UserSubscriptionRequestRecord;
+use crate::tools::user_agent::detect_client_app;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Limits above which a signal counts as evidence. Every signal adds `value / limit - 1` to the score
+/// once it exceeds its limit, so a score of `1.0` means "one signal at twice its limit".
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharingThresholds {
+    /// Only requests within this window before the newest request (or `window_end`) are analyzed.
+    pub window: Duration,
+    pub window_end: Option<DateTime<Utc>>,
+    pub max_ips: usize,
+    /// IPv4 /24 or IPv6 /48 networks.
+    pub max_subnets: usize,
+    pub max_apps: usize,
+    pub max_requests_per_hour: f64,
+    /// Switches between different subnets in consecutive requests, per day. Subnets rather than IPs so that
+    /// carrier-grade NAT pools rotating within one network do not count.
+    pub max_ip_changes_per_day: f64,
+    pub max_hwid_devices: usize,
+    /// Requests closer together than this count as simultaneous when comparing subnets with HWID devices, so that one
+    /// device roaming between home, mobile and office networks is not mistaken for several.
+    pub concurrent_window: Duration,
+    /// Users scoring below this are not reported.
+    pub min_score: f64,
+}
+
+impl Default for SharingThresholds {
+    fn default() -> Self {
+        Self {
+            window: Duration::days(7),
+            window_end: None,
+            max_ips: 6,
+            max_subnets: 4,
+            max_apps: 3,
+            max_requests_per_hour: 4.0,
+            max_ip_changes_per_day: 6.0,
+            max_hwid_devices: 3,
+            concurrent_window: Duration::hours(1),
+            min_score: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SharingStats {
+    pub requests: usize,
+    pub distinct_ips: usize,
+    pub distinct_subnets: usize,
+    /// Most distinct subnets seen within any `concurrent_window`.
+    pub concurrent_subnets: usize,
+    /// App names from [`detect_client_app`], or the raw `User-Agent` for unknown clients.
+    pub apps: BTreeSet<String>,
+    pub requests_per_hour: f64,
+    pub ip_changes_per_day: f64,
+    pub hwid_devices: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "signal", rename_all = "camelCase")]
+pub enum SharingEvidence {
+    DistinctIps {
+        count: usize,
+        limit: usize,
+    },
+    DistinctSubnets {
+        count: usize,
+        limit: usize,
+    },
+    DistinctApps {
+        apps: Vec<String>,
+        limit: usize,
+    },
+    RequestFrequency {
+        per_hour: f64,
+        limit: f64,
+    },
+    IpChurn {
+        changes_per_day: f64,
+        limit: f64,
+    },
+    HwidDevices {
+        count: usize,
+        limit: usize,
+    },
+    /// Requests come from more networks at once than the user has registered devices.
+    SubnetsExceedDevices {
+        subnets: usize,
+        devices: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SharingSuspect {
+    pub user_uuid: Uuid,
+    pub score: f64,
+    pub evidence: Vec<SharingEvidence>,
+    pub stats: SharingStats,
+}
+
+#[derive(Debug, Clone)]
+struct Request {
+    ip: Option<IpAddr>,
+    app: Option<String>,
+    at: DateTime<Utc>,
+}
+
+/// Scores users for likely account sharing from subscription request history and HWID devices.
+#[derive(Debug, Clone, Default)]
+pub struct SharingAnalyzer {
+    thresholds: SharingThresholds,
+    requests: HashMap<Uuid, Vec<Request>>,
+    hwid_devices: HashMap<Uuid, BTreeSet<String>>,
+}
+
+impl SharingAnalyzer {
+    pub fn new(thresholds: SharingThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_request(&mut self, user_uuid: Uuid, request_ip: Option<&str>, user_agent: Option<&str>, request_at: DateTime<Utc>) {
+        let request = Request {
+            ip: request_ip.and_then(|ip| ip.trim().parse().ok()),
+            app: user_agent.filter(|ua| !ua.trim().is_empty()).map(|ua| detect_client_app(ua).map(|app| app.name.to_string()).unwrap_or_else(|| ua.trim().to_string())),
+            at: request_at,
+        };
+        self.requests.entry(user_uuid).or_default().push(request);
+    }
+
+    /// Records from `SubscriptionRequestHistoryController::get_all`.
+    pub fn add_records<'a>(&mut self, records: impl IntoIterator<Item = &'a SubscriptionRequestRecord>) {
+        for r in records {
+            self.add_request(r.user_uuid, r.request_ip.as_deref(), r.user_agent.as_deref(), r.request_at);
+        }
+    }
+
+    /// Records from `UsersController::get_subscription_request_history`.
+    pub fn add_user_records<'a>(&mut self, records: impl IntoIterator<Item = &'a UserSubscriptionRequestRecord>) {
+        for r in records {
+            self.add_request(r.user_uuid, r.request_ip.as_deref(), r.user_agent.as_deref(), r.request_at);
+        }
+    }
+
+    /// Devices from `HwidUserDevicesController::get_all` or `get`.
+    pub fn add_hwid_devices<'a>(&mut self, devices: impl IntoIterator<Item = &'a HwidDeviceDto>) {
+        for device in devices {
+            self.hwid_devices.entry(device.user_uuid).or_default().insert(device.hwid.clone());
+        }
+    }
+
+    /// Statistics for one user within the window, or `None` if the user has no requests in it.
+    pub fn stats(&self, user_uuid: &Uuid) -> Option<SharingStats> {
+        let requests = self.requests.get(user_uuid)?;
+        let end = self.thresholds.window_end.or_else(|| requests.iter().map(|r| r.at).max())?;
+        let start = end - self.thresholds.window;
+
+        let mut in_window: Vec<&Request> = requests.iter().filter(|r| r.at >= start && r.at <= end).collect();
+        if in_window.is_empty() {
+            return None;
+        }
+        in_window.sort_by_key(|r| r.at);
+
+        let ips: BTreeSet<IpAddr> = in_window.iter().filter_map(|r| r.ip).collect();
+        let subnets: BTreeSet<IpAddr> = ips.iter().map(|ip| subnet(*ip)).collect();
+        let apps: BTreeSet<String> = in_window.iter().filter_map(|r| r.app.clone()).collect();
+
+        let span = in_window.last().unwrap().at - in_window.first().unwrap().at;
+        let hours = (span.num_seconds() as f64 / 3600.0).max(1.0);
+        let days = (hours / 24.0).max(1.0);
+
+        let located: Vec<(DateTime<Utc>, IpAddr)> = in_window.iter().filter_map(|r| r.ip.map(|ip| (r.at, subnet(ip)))).collect();
+        let subnet_changes = located.windows(2).filter(|pair| pair[0].1 != pair[1].1).count();
+
+        Some(SharingStats {
+            requests: in_window.len(),
+            distinct_ips: ips.len(),
+            distinct_subnets: subnets.len(),
+            concurrent_subnets: concurrent_subnets(&located, self.thresholds.concurrent_window),
+            apps,
+            requests_per_hour: in_window.len() as f64 / hours,
+            ip_changes_per_day: subnet_changes as f64 / days,
+            hwid_devices: self.hwid_devices.get(user_uuid).map(BTreeSet::len),
+        })
+    }
+
+    /// Users with at least one signal over its limit and a score of at least `min_score`, highest score first.
+    pub fn suspects(&self) -> Vec<SharingSuspect> {
+        let mut suspects: Vec<SharingSuspect> = self.requests.keys().filter_map(|user_uuid| self.score(user_uuid)).collect();
+        suspects.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.user_uuid.cmp(&b.user_uuid)));
+        suspects
+    }
+
+    fn score(&self, user_uuid: &Uuid) -> Option<SharingSuspect> {
+        let t = &self.thresholds;
+        let stats = self.stats(user_uuid)?;
+        let mut score = 0.0;
+        let mut evidence = Vec::new();
+
+        let mut signal = |value: f64, limit: f64, item: SharingEvidence| {
+            if value > limit {
+                score += value / limit.max(f64::EPSILON) - 1.0;
+                evidence.push(item);
+            }
+        };
+
+        signal(
+            stats.distinct_ips as f64,
+            t.max_ips as f64,
+            SharingEvidence::DistinctIps {
+                count: stats.distinct_ips,
+                limit: t.max_ips,
+            },
+        );
+        signal(
+            stats.distinct_subnets as f64,
+            t.max_subnets as f64,
+            SharingEvidence::DistinctSubnets {
+                count: stats.distinct_subnets,
+                limit: t.max_subnets,
+            },
+        );
+        signal(
+            stats.apps.len() as f64,
+            t.max_apps as f64,
+            SharingEvidence::DistinctApps {
+                apps: stats.apps.iter().cloned().collect(),
+                limit: t.max_apps,
+            },
+        );
+        signal(
+            stats.requests_per_hour,
+            t.max_requests_per_hour,
+            SharingEvidence::RequestFrequency {
+                per_hour: stats.requests_per_hour,
+                limit: t.max_requests_per_hour,
+            },
+        );
+        signal(
+            stats.ip_changes_per_day,
+            t.max_ip_changes_per_day,
+            SharingEvidence::IpChurn {
+                changes_per_day: stats.ip_changes_per_day,
+                limit: t.max_ip_changes_per_day,
+            },
+        );
+        if let Some(devices) = stats.hwid_devices {
+            signal(
+                devices as f64,
+                t.max_hwid_devices as f64,
+                SharingEvidence::HwidDevices {
+                    count: devices,
+                    limit: t.max_hwid_devices,
+                },
+            );
+            if devices > 0 {
+                signal(
+                    stats.concurrent_subnets as f64,
+                    devices as f64,
+                    SharingEvidence::SubnetsExceedDevices {
+                        subnets: stats.concurrent_subnets,
+                        devices,
+                    },
+                );
+            }
+        }
+
+        (!evidence.is_empty() && score >= t.min_score).then_some(SharingSuspect {
+            user_uuid: *user_uuid,
+            score,
+            evidence,
+            stats,
+        })
+    }
+}
+
+/// Most distinct subnets among requests less than `window` apart, over time-sorted `(at, subnet)` pairs.
+fn concurrent_subnets(located: &[(DateTime<Utc>, IpAddr)], window: Duration) -> usize {
+    let mut counts: HashMap<IpAddr, usize> = HashMap::new();
+    let mut first = 0;
+    let mut most = 0;
+    for (index, &(at, subnet)) in located.iter().enumerate() {
+        *counts.entry(subnet).or_default() += 1;
+        while first < index && at - located[first].0 >= window {
+            let oldest = located[first].1;
+            if let Some(count) = counts.get_mut(&oldest) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(&oldest);
+                }
+            }
+            first += 1;
+        }
+        most = most.max(counts.len());
+    }
+    most
+}
+
+/// IPv4 addresses are grouped by /24, IPv6 addresses by /48.
+fn subnet(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => IpAddr::V4((u32::from(v4) & 0xFFFF_FF00).into()),
+        IpAddr::V6(v6) => IpAddr::V6((u128::from(v6) & !((1u128 << 80) - 1)).into()),
+    }
+}
+
+impl SubscriptionRequestHistoryController {
+    /// Pages through `get_all` and returns every record requested at or after `since`.
+    ///
+    /// Paging stops at the end of the history or at the first page that is entirely older than `since`,
+    /// as the panel returns the newest records first.
+    pub async fn get_all_since(&self, since: DateTime<Utc>, page_size: usize) -> Result<Vec<SubscriptionRequestRecord>, crate::ApiError> {
+        let page_size = page_size.max(1);
+        let mut records = Vec::new();
+        let mut start = 0;
+
+        loop {
+            let page = self.get_all(Some(page_size), Some(start)).await?.response;
+            let fetched = page.records.len();
+            let before = records.len();
+            records.extend(page.records.into_iter().filter(|r| r.request_at >= since));
+
+            start += fetched;
+            if fetched < page_size || start >= page.total || records.len() == before {
+                return Ok(records);
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::api::types::subscriptions::{
+    SubscriptionResponseRule, SubscriptionResponseRuleCondition, SubscriptionResponseRuleConditionOperator, SubscriptionResponseRuleHeaderModification,
+    SubscriptionResponseRuleModifications, SubscriptionResponseRuleOperator, SubscriptionResponseRuleType,
+};
+
+/// Fluent builder for [`SubscriptionResponseRule`]. Rules are enabled and use the `AND` operator by default.
+#[derive(Debug, Clone)]
+pub struct SubscriptionResponseRuleBuilder {
+    rule: SubscriptionResponseRule,
+}
+
+impl SubscriptionResponseRule {
+    pub fn builder(name: impl Into<String>, response_type: SubscriptionResponseRuleType) -> SubscriptionResponseRuleBuilder {
+        SubscriptionResponseRuleBuilder {
+            rule: SubscriptionResponseRule {
+                name: name.into(),
+                description: None,
+                enabled: true,
+                operator: SubscriptionResponseRuleOperator::And,
+                conditions: Vec::new(),
+                response_type,
+                response_modifications: None,
+            },
+        }
+    }
+}
+
+impl SubscriptionResponseRuleBuilder {
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.rule.description = Some(description.into());
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.rule.enabled = enabled;
+        self
+    }
+
+    pub fn disabled(self) -> Self {
+        self.enabled(false)
+    }
+
+    /// Every condition has to match (`AND`).
+    pub fn all(mut self) -> Self {
+        self.rule.operator = SubscriptionResponseRuleOperator::And;
+        self
+    }
+
+    /// At least one condition has to match (`OR`).
+    pub fn any(mut self) -> Self {
+        self.rule.operator = SubscriptionResponseRuleOperator::Or;
+        self
+    }
+
+    pub fn condition(mut self, condition: SubscriptionResponseRuleCondition) -> Self {
+        self.rule.conditions.push(condition);
+        self
+    }
+
+    pub fn conditions(mut self, conditions: impl IntoIterator<Item = SubscriptionResponseRuleCondition>) -> Self {
+        self.rule.conditions.extend(conditions);
+        self
+    }
+
+    /// Adds a response header to the rule's `response_modifications`.
+    pub fn response_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let header = SubscriptionResponseRuleHeaderModification {
+            key: key.into(),
+            value: value.into(),
+        };
+        self.modifications().headers.get_or_insert_with(Vec::new).push(header);
+        self
+    }
+
+    /// Renders the response with the subscription template of this name instead of the default one.
+    pub fn subscription_template(mut self, name: impl Into<String>) -> Self {
+        self.modifications().subscription_template = Some(name.into());
+        self
+    }
+
+    pub fn build(self) -> SubscriptionResponseRule {
+        self.rule
+    }
+
+    fn modifications(&mut self) -> &mut SubscriptionResponseRuleModifications {
+        self.rule.response_modifications.get_or_insert(SubscriptionResponseRuleModifications {
+            headers: None,
+            subscription_template: None,
+        })
+    }
+}
+
+impl From<SubscriptionResponseRuleBuilder> for SubscriptionResponseRule {
+    fn from(builder: SubscriptionResponseRuleBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Builder returned by [`SubscriptionResponseRuleCondition::header`]; finish it with an operator method.
+/// Comparisons are case-insensitive unless [`case_sensitive`](Self::case_sensitive) is called.
+#[derive(Debug, Clone)]
+pub struct SubscriptionResponseRuleConditionBuilder {
+    header_name: String,
+    case_sensitive: bool,
+}
+
+impl SubscriptionResponseRuleCondition {
+    pub fn header(header_name: impl Into<String>) -> SubscriptionResponseRuleConditionBuilder {
+        SubscriptionResponseRuleConditionBuilder {
+            header_name: header_name.into(),
+            case_sensitive: false,
+        }
+    }
+
+    pub fn user_agent() -> SubscriptionResponseRuleConditionBuilder {
+        Self::header("user-agent")
+    }
+}
+
+impl SubscriptionResponseRuleConditionBuilder {
+    pub fn case_sensitive(mut self) -> Self {
+        self.case_sensitive = true;
+        self
+    }
+
+    pub fn operator(self, operator: SubscriptionResponseRuleConditionOperator, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        SubscriptionResponseRuleCondition {
+            header_name: self.header_name,
+            operator,
+            value: value.into(),
+            case_sensitive: self.case_sensitive,
+        }
+    }
+
+    pub fn equals(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::Equals, value)
+    }
+
+    pub fn not_equals(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::NotEquals, value)
+    }
+
+    pub fn contains(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::Contains, value)
+    }
+
+    pub fn not_contains(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::NotContains, value)
+    }
+
+    pub fn starts_with(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::StartsWith, value)
+    }
+
+    pub fn not_starts_with(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::NotStartsWith, value)
+    }
+
+    pub fn ends_with(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::EndsWith, value)
+    }
+
+    pub fn not_ends_with(self, value: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::NotEndsWith, value)
+    }
+
+    pub fn regex(self, pattern: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::Regex, pattern)
+    }
+
+    pub fn not_regex(self, pattern: impl Into<String>) -> SubscriptionResponseRuleCondition {
+        self.operator(SubscriptionResponseRuleConditionOperator::NotRegex, pattern)
+    }
+}
@@ -0,0 +1,162 @@
+pub mod builder;
+pub mod validate;
+
+pub use builder::{SubscriptionResponseRuleBuilder, SubscriptionResponseRuleConditionBuilder};
+pub use validate::validate_response_rules;
+
+use crate::api::types::subscriptions::{
+    SubscriptionResponseRule, SubscriptionResponseRuleCondition, SubscriptionResponseRuleConditionOperator, SubscriptionResponseRuleOperator, SubscriptionResponseRuleType,
+    SubscriptionResponseRulesConfig,
+};
+use crate::api::types::system::DebugSrrMatcherResponse;
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+
+/// Response type used when no enabled rule matches the request.
+pub const SRR_DEFAULT_RESPONSE_TYPE: SubscriptionResponseRuleType = SubscriptionResponseRuleType::XrayBase64;
+
+/// Request headers as seen by the Subscription Response Rules matcher. Header names are case-insensitive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SrrRequest {
+    headers: HashMap<String, String>,
+}
+
+impl SrrRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_user_agent(user_agent: impl Into<String>) -> Self {
+        Self::new().header("user-agent", user_agent)
+    }
+
+    pub fn from_headers<K, V>(headers: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        headers.into_iter().fold(Self::new(), |request, (name, value)| request.header(name.as_ref(), value))
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.insert(name.to_ascii_lowercase(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.get("user-agent")
+    }
+}
+
+/// Local equivalent of `SystemController::debug_srr_matcher`.
+///
+/// Rules are checked in order and the first enabled matching rule wins. A rule with the `AND` operator
+/// needs every condition to match and `OR` needs at least one, so a rule without conditions matches
+/// everything under `AND` and nothing under `OR`. A condition on a header that is absent from the
+/// request only matches for the negated (`NOT_*`) operators. Regexes are unanchored searches; a regex
+/// that fails to compile never matches.
+#[derive(Debug, Clone)]
+pub struct SrrEvaluator<'a> {
+    config: &'a SubscriptionResponseRulesConfig,
+    regexes: Vec<Vec<Option<Regex>>>,
+}
+
+impl<'a> SrrEvaluator<'a> {
+    pub fn new(config: &'a SubscriptionResponseRulesConfig) -> Self {
+        let regexes = config.rules.iter().map(|rule| rule.conditions.iter().map(|c| compile_condition_regex(c).and_then(Result::ok)).collect()).collect();
+
+        Self {
+            config,
+            regexes,
+        }
+    }
+
+    /// Returns the index and the rule that handles the request, if any.
+    pub fn matched_rule(&self, request: &SrrRequest) -> Option<(usize, &'a SubscriptionResponseRule)> {
+        self.config.rules.iter().enumerate().find(|(index, rule)| rule.enabled && self.rule_matches(*index, rule, request))
+    }
+
+    pub fn evaluate(&self, request: &SrrRequest) -> DebugSrrMatcherResponse {
+        match self.matched_rule(request) {
+            Some((_, rule)) => DebugSrrMatcherResponse {
+                matched: true,
+                response_type: rule.response_type.clone(),
+                matched_rule: Some(rule.clone()),
+            },
+            None => DebugSrrMatcherResponse {
+                matched: false,
+                response_type: SRR_DEFAULT_RESPONSE_TYPE,
+                matched_rule: None,
+            },
+        }
+    }
+
+    fn rule_matches(&self, index: usize, rule: &SubscriptionResponseRule, request: &SrrRequest) -> bool {
+        let mut results =
+            rule.conditions.iter().zip(&self.regexes[index]).map(|(condition, regex)| condition_matches_with(condition, regex.as_ref(), request.get(&condition.header_name)));
+
+        match rule.operator {
+            SubscriptionResponseRuleOperator::And => results.all(|matched| matched),
+            SubscriptionResponseRuleOperator::Or => results.any(|matched| matched),
+        }
+    }
+}
+
+impl SubscriptionResponseRulesConfig {
+    pub fn evaluate(&self, request: &SrrRequest) -> DebugSrrMatcherResponse {
+        SrrEvaluator::new(self).evaluate(request)
+    }
+}
+
+impl SubscriptionResponseRuleCondition {
+    /// Checks the condition against a single header value (`None` if the header is absent).
+    pub fn matches(&self, header_value: Option<&str>) -> bool {
+        let regex = compile_condition_regex(self).and_then(Result::ok);
+        condition_matches_with(self, regex.as_ref(), header_value)
+    }
+}
+
+/// Compiles the pattern of a `REGEX` / `NOT_REGEX` condition, honouring `case_sensitive`.
+pub(crate) fn compile_condition_regex(condition: &SubscriptionResponseRuleCondition) -> Option<Result<Regex, regex::Error>> {
+    match condition.operator {
+        SubscriptionResponseRuleConditionOperator::Regex | SubscriptionResponseRuleConditionOperator::NotRegex => {
+            Some(RegexBuilder::new(&condition.value).case_insensitive(!condition.case_sensitive).build())
+        }
+        _ => None,
+    }
+}
+
+fn condition_matches_with(condition: &SubscriptionResponseRuleCondition, regex: Option<&Regex>, header_value: Option<&str>) -> bool {
+    use SubscriptionResponseRuleConditionOperator as Op;
+
+    let negated = matches!(condition.operator, Op::NotEquals | Op::NotContains | Op::NotStartsWith | Op::NotEndsWith | Op::NotRegex);
+    let Some(header_value) = header_value else {
+        return negated;
+    };
+
+    let fold = |s: &str| {
+        if condition.case_sensitive {
+            s.to_string()
+        } else {
+            s.to_lowercase()
+        }
+    };
+    let (haystack, needle) = (fold(header_value), fold(&condition.value));
+
+    let positive = match condition.operator {
+        Op::Equals | Op::NotEquals => haystack == needle,
+        Op::Contains | Op::NotContains => haystack.contains(&needle),
+        Op::StartsWith | Op::NotStartsWith => haystack.starts_with(&needle),
+        Op::EndsWith | Op::NotEndsWith => haystack.ends_with(&needle),
+        Op::Regex | Op::NotRegex => match regex {
+            Some(regex) => regex.is_match(header_value),
+            None => return false,
+        },
+    };
+
+    positive != negated
+}
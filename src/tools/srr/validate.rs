@@ -0,0 +1,304 @@
+use super::compile_condition_regex;
+use crate::api::controllers::SubscriptionTemplateController;
+use crate::api::types::subscriptions::{
+    SubscriptionResponseRule, SubscriptionResponseRuleCondition, SubscriptionResponseRuleConditionOperator as Op, SubscriptionResponseRuleOperator, SubscriptionResponseRuleType,
+    SubscriptionResponseRulesConfig, SubscriptionTemplateType, TemplateSummary,
+};
+use crate::diagnostics::Diagnostic;
+use std::collections::HashMap;
+
+/// Statically checks a rules config: broken regexes and header modifications, rules that can never match,
+/// rules that are unreachable or shadowed by an earlier rule, and (if `templates` is given) that every
+/// `subscription_template` exists with a type matching the rule's `response_type`.
+///
+/// Shadowing is detected conservatively: only implications between literal conditions on the same header
+/// are considered, so a clean report does not prove that every rule is reachable.
+pub fn validate_response_rules(config: &SubscriptionResponseRulesConfig, templates: Option<&[TemplateSummary]>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    let mut catch_all: Option<usize> = None;
+    let mut reachable: Vec<usize> = Vec::new();
+
+    for (index, rule) in config.rules.iter().enumerate() {
+        let pointer = format!("/rules/{index}");
+
+        if rule.name.trim().is_empty() {
+            diagnostics.push(Diagnostic::error("empty-rule-name", format!("{pointer}/name"), "rule name must not be empty"));
+        } else if let Some(first) = names.insert(rule.name.as_str(), index) {
+            diagnostics.push(Diagnostic::warning("duplicate-rule-name", format!("{pointer}/name"), format!("rule name '{}' is already used by rule #{first}", rule.name)));
+        }
+
+        let mut has_invalid_regex = false;
+        for (condition_index, condition) in rule.conditions.iter().enumerate() {
+            let condition_pointer = format!("{pointer}/conditions/{condition_index}");
+            if condition.header_name.trim().is_empty() {
+                diagnostics.push(Diagnostic::error("empty-header-name", format!("{condition_pointer}/headerName"), "condition header name must not be empty"));
+            }
+            if let Some(Err(e)) = compile_condition_regex(condition) {
+                has_invalid_regex = true;
+                diagnostics.push(Diagnostic::error("invalid-regex", format!("{condition_pointer}/value"), format!("regex does not compile: {e}")));
+            }
+        }
+
+        check_modifications(rule, &pointer, templates, &mut diagnostics);
+
+        if !rule.enabled {
+            continue;
+        }
+
+        if let Some(catch_all) = catch_all {
+            diagnostics.push(Diagnostic::warning("unreachable-rule", pointer, format!("rule '{}' is unreachable: rule #{catch_all} matches every request", rule.name)));
+            continue;
+        }
+
+        let satisfiability = satisfiability(rule);
+        if let Satisfiability::Never(reason) = &satisfiability {
+            diagnostics.push(Diagnostic::error("rule-never-matches", pointer, format!("rule '{}' can never match: {reason}", rule.name)));
+            continue;
+        }
+        if has_invalid_regex && rule.operator == SubscriptionResponseRuleOperator::And {
+            continue;
+        }
+
+        if let Some(&shadowing) = reachable.iter().find(|&&earlier| covers(&config.rules[earlier], rule)) {
+            diagnostics.push(Diagnostic::warning(
+                "shadowed-rule",
+                pointer,
+                format!("rule '{}' is shadowed by rule #{shadowing} '{}', which matches every request it would match", rule.name, config.rules[shadowing].name),
+            ));
+            continue;
+        }
+
+        if satisfiability == Satisfiability::Always {
+            catch_all = Some(index);
+        }
+        reachable.push(index);
+    }
+
+    diagnostics
+}
+
+impl SubscriptionResponseRulesConfig {
+    /// See [`validate_response_rules`]; templates are not cross-checked.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate_response_rules(self, None)
+    }
+
+    pub fn validate_with_templates(&self, templates: &[TemplateSummary]) -> Vec<Diagnostic> {
+        validate_response_rules(self, Some(templates))
+    }
+}
+
+impl SubscriptionTemplateController {
+    /// Validates `config` and cross-checks its `subscription_template` references against `get_all`.
+    pub async fn validate_response_rules(&self, config: &SubscriptionResponseRulesConfig) -> Result<Vec<Diagnostic>, crate::ApiError> {
+        let templates = self.get_all().await?.response.templates;
+        Ok(validate_response_rules(config, Some(&templates)))
+    }
+}
+
+fn template_type_for(response_type: &SubscriptionResponseRuleType) -> Option<SubscriptionTemplateType> {
+    match response_type {
+        SubscriptionResponseRuleType::XrayJson => Some(SubscriptionTemplateType::XrayJson),
+        SubscriptionResponseRuleType::XrayBase64 => Some(SubscriptionTemplateType::XrayBase64),
+        SubscriptionResponseRuleType::Mihomo => Some(SubscriptionTemplateType::Mihomo),
+        SubscriptionResponseRuleType::Stash => Some(SubscriptionTemplateType::Stash),
+        SubscriptionResponseRuleType::Clash => Some(SubscriptionTemplateType::Clash),
+        SubscriptionResponseRuleType::Singbox => Some(SubscriptionTemplateType::Singbox),
+        _ => None,
+    }
+}
+
+fn check_modifications(rule: &SubscriptionResponseRule, pointer: &str, templates: Option<&[TemplateSummary]>, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(modifications) = &rule.response_modifications else {
+        return;
+    };
+
+    for (index, header) in modifications.headers.iter().flatten().enumerate() {
+        if header.key.trim().is_empty() {
+            diagnostics.push(Diagnostic::error("empty-response-header", format!("{pointer}/responseModifications/headers/{index}/key"), "response header name must not be empty"));
+        }
+    }
+
+    let Some(template_name) = &modifications.subscription_template else {
+        return;
+    };
+    let template_pointer = format!("{pointer}/responseModifications/subscriptionTemplate");
+
+    let Some(expected_type) = template_type_for(&rule.response_type) else {
+        diagnostics.push(Diagnostic::warning(
+            "template-ignored",
+            template_pointer,
+            format!("subscription template '{template_name}' is ignored for response type {}", rule.response_type),
+        ));
+        return;
+    };
+
+    let Some(templates) = templates else {
+        return;
+    };
+
+    let candidates: Vec<&TemplateSummary> = templates.iter().filter(|t| &t.name == template_name).collect();
+    if candidates.is_empty() {
+        diagnostics.push(Diagnostic::error("unknown-template", template_pointer, format!("subscription template '{template_name}' does not exist")));
+    } else if !candidates.iter().any(|t| t.template_type == expected_type) {
+        let found: Vec<String> = candidates.iter().map(|t| t.template_type.to_string()).collect();
+        diagnostics.push(Diagnostic::error(
+            "template-type-mismatch",
+            template_pointer,
+            format!("subscription template '{template_name}' is {} but the rule responds with {expected_type}", found.join(", ")),
+        ));
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Satisfiability {
+    Always,
+    Sometimes,
+    Never(String),
+}
+
+fn satisfiability(rule: &SubscriptionResponseRule) -> Satisfiability {
+    let conditions = &rule.conditions;
+
+    match rule.operator {
+        SubscriptionResponseRuleOperator::And => {
+            if conditions.is_empty() {
+                return Satisfiability::Always;
+            }
+            for (i, a) in conditions.iter().enumerate() {
+                for (j, b) in conditions.iter().enumerate().skip(i + 1) {
+                    if contradicts(a, b) {
+                        return Satisfiability::Never(format!("conditions #{i} and #{j} contradict each other"));
+                    }
+                }
+            }
+            Satisfiability::Sometimes
+        }
+        SubscriptionResponseRuleOperator::Or => {
+            if conditions.is_empty() {
+                return Satisfiability::Never("OR rule has no conditions".to_string());
+            }
+            if conditions.iter().all(|c| matches!(compile_condition_regex(c), Some(Err(_)))) {
+                return Satisfiability::Never("every condition has an invalid regex".to_string());
+            }
+            let tautology = conditions.iter().any(|a| conditions.iter().any(|b| is_negated(b) && !is_negated(a) && positive_implies(b, positive_operator(&b.operator), a)));
+            if tautology {
+                Satisfiability::Always
+            } else {
+                Satisfiability::Sometimes
+            }
+        }
+    }
+}
+
+/// Whether every request matched by `later` is also matched by `earlier`.
+fn covers(earlier: &SubscriptionResponseRule, later: &SubscriptionResponseRule) -> bool {
+    use SubscriptionResponseRuleOperator::{And, Or};
+
+    let (e, l) = (&earlier.conditions, &later.conditions);
+    match (&earlier.operator, &later.operator) {
+        (And, And) => e.iter().all(|ce| l.iter().any(|cl| implies(cl, ce))),
+        (Or, Or) => !l.is_empty() && l.iter().all(|cl| e.iter().any(|ce| implies(cl, ce))),
+        (Or, And) => e.iter().any(|ce| l.iter().any(|cl| implies(cl, ce))),
+        (And, Or) => !l.is_empty() && l.iter().all(|cl| e.iter().all(|ce| implies(cl, ce))),
+    }
+}
+
+fn is_negated(condition: &SubscriptionResponseRuleCondition) -> bool {
+    matches!(condition.operator, Op::NotEquals | Op::NotContains | Op::NotStartsWith | Op::NotEndsWith | Op::NotRegex)
+}
+
+fn positive_operator(operator: &Op) -> Op {
+    match operator {
+        Op::NotEquals => Op::Equals,
+        Op::NotContains => Op::Contains,
+        Op::NotStartsWith => Op::StartsWith,
+        Op::NotEndsWith => Op::EndsWith,
+        Op::NotRegex => Op::Regex,
+        other => other.clone(),
+    }
+}
+
+fn same_header(a: &SubscriptionResponseRuleCondition, b: &SubscriptionResponseRuleCondition) -> bool {
+    a.header_name.eq_ignore_ascii_case(&b.header_name)
+}
+
+fn fold(value: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        value.to_string()
+    } else {
+        value.to_lowercase()
+    }
+}
+
+fn identical(a: &SubscriptionResponseRuleCondition, b: &SubscriptionResponseRuleCondition) -> bool {
+    same_header(a, b) && a.operator == b.operator && a.case_sensitive == b.case_sensitive && fold(&a.value, a.case_sensitive) == fold(&b.value, b.case_sensitive)
+}
+
+/// Whether `a` matching guarantees that `b` matches.
+fn implies(a: &SubscriptionResponseRuleCondition, b: &SubscriptionResponseRuleCondition) -> bool {
+    if identical(a, b) {
+        return true;
+    }
+    !is_negated(a) && !is_negated(b) && positive_implies(a, a.operator.clone(), b)
+}
+
+/// Whether a header value satisfying `a` (evaluated with `a_operator`, which must be positive) always satisfies
+/// the positive condition `b`.
+fn positive_implies(a: &SubscriptionResponseRuleCondition, a_operator: Op, b: &SubscriptionResponseRuleCondition) -> bool {
+    // A case-insensitive `a` says nothing about the exact casing a case-sensitive `b` needs.
+    if !same_header(a, b) || (!a.case_sensitive && b.case_sensitive) {
+        return false;
+    }
+
+    let b_operator = positive_operator(&b.operator);
+    let a_value = fold(&a.value, b.case_sensitive);
+    let b_value = fold(&b.value, b.case_sensitive);
+
+    match (a_operator, b_operator) {
+        (Op::Equals, b_operator) => {
+            let positive_b = SubscriptionResponseRuleCondition {
+                operator: b_operator,
+                ..b.clone()
+            };
+            positive_b.matches(Some(&a.value))
+        }
+        (Op::StartsWith, Op::StartsWith) => a_value.starts_with(&b_value),
+        (Op::EndsWith, Op::EndsWith) => a_value.ends_with(&b_value),
+        (Op::StartsWith | Op::EndsWith | Op::Contains, Op::Contains) => a_value.contains(&b_value),
+        (Op::Regex, Op::Regex) => a.value == b.value && a.case_sensitive == b.case_sensitive,
+        _ => false,
+    }
+}
+
+/// Whether two conditions of an `AND` rule can never both match.
+fn contradicts(a: &SubscriptionResponseRuleCondition, b: &SubscriptionResponseRuleCondition) -> bool {
+    if !same_header(a, b) {
+        return false;
+    }
+
+    let negation_of = |positive: &SubscriptionResponseRuleCondition, negated: &SubscriptionResponseRuleCondition| {
+        !is_negated(positive) && is_negated(negated) && positive_implies(positive, positive.operator.clone(), negated)
+    };
+    if negation_of(a, b) || negation_of(b, a) {
+        return true;
+    }
+
+    let equals_rejects = |equals: &SubscriptionResponseRuleCondition, other: &SubscriptionResponseRuleCondition| {
+        equals.operator == Op::Equals && (equals.case_sensitive || !other.case_sensitive) && !other.matches(Some(&equals.value))
+    };
+    if equals_rejects(a, b) || equals_rejects(b, a) {
+        return true;
+    }
+
+    if a.case_sensitive != b.case_sensitive {
+        return false;
+    }
+    let (a_value, b_value) = (fold(&a.value, a.case_sensitive), fold(&b.value, b.case_sensitive));
+    match (&a.operator, &b.operator) {
+        (Op::StartsWith, Op::StartsWith) => !a_value.starts_with(&b_value) && !b_value.starts_with(&a_value),
+        (Op::EndsWith, Op::EndsWith) => !a_value.ends_with(&b_value) && !b_value.ends_with(&a_value),
+        _ => false,
+    }
+}
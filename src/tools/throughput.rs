@@ -0,0 +1,236 @@
+use crate::api::controllers::NodesUsageController;
+use crate::api::types::nodes::NodeRealtimeUsage;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+/// Bytes per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Rate {
+    pub download: f64,
+    pub upload: f64,
+}
+
+impl Rate {
+    pub fn total(&self) -> f64 {
+        self.download + self.upload
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ThroughputSample {
+    pub at: DateTime<Utc>,
+    /// Average rate since the previous snapshot.
+    pub rate: Rate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThroughputStats {
+    pub node_uuid: Uuid,
+    pub node_name: String,
+    pub current: Rate,
+    /// `download_speed_bps` / `upload_speed_bps` of the latest snapshot, as reported by the panel.
+    pub reported: Rate,
+    /// Mean of the last `average_window` samples.
+    pub average: Rate,
+    /// Highest download and upload rates in the buffer, not necessarily from the same sample.
+    pub peak: Rate,
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThroughputOptions {
+    /// Time between polls; zero is treated as one millisecond.
+    pub interval: Duration,
+    /// Samples kept per node.
+    pub capacity: usize,
+    pub average_window: usize,
+}
+
+impl Default for ThroughputOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            capacity: 120,
+            average_window: 12,
+        }
+    }
+}
+
+impl ThroughputOptions {
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn average_window(mut self, average_window: usize) -> Self {
+        self.average_window = average_window;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NodeSeries {
+    name: String,
+    last: (DateTime<Utc>, usize, usize),
+    reported: Rate,
+    samples: VecDeque<ThroughputSample>,
+}
+
+/// Turns successive `get_realtime_usage` snapshots into per-node rates kept in bounded ring buffers.
+///
+/// Rates are derived from the byte counters rather than taken from the reported speed fields: the panel's speed is
+/// a reading over its own polling interval, so sampling it at a different interval skips or double-counts bursts,
+/// while counter deltas give the exact average between two snapshots. The reported speed is still available as
+/// [`ThroughputStats::reported`].
+#[derive(Debug, Clone, Default)]
+pub struct ThroughputSampler {
+    options: ThroughputOptions,
+    nodes: HashMap<Uuid, NodeSeries>,
+}
+
+impl ThroughputSampler {
+    pub fn new(options: ThroughputOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a snapshot taken at `at`. The first snapshot of a node only sets its baseline; counters that went
+    /// backwards (node restart) reset the baseline without a sample. Nodes missing from the snapshot are dropped.
+    pub fn record(&mut self, usage: &[NodeRealtimeUsage], at: DateTime<Utc>) {
+        self.nodes.retain(|uuid, _| usage.iter().any(|u| u.node_uuid == *uuid));
+
+        for node in usage {
+            let current = (at, node.download_bytes, node.upload_bytes);
+            let reported = Rate {
+                download: node.download_speed_bps as f64,
+                upload: node.upload_speed_bps as f64,
+            };
+            let Some(series) = self.nodes.get_mut(&node.node_uuid) else {
+                self.nodes.insert(
+                    node.node_uuid,
+                    NodeSeries {
+                        name: node.node_name.clone(),
+                        last: current,
+                        reported,
+                        samples: VecDeque::new(),
+                    },
+                );
+                continue;
+            };
+            series.name.clone_from(&node.node_name);
+            series.reported = reported;
+
+            let (last_at, last_down, last_up) = std::mem::replace(&mut series.last, current);
+            let seconds = (at - last_at).num_milliseconds() as f64 / 1000.0;
+            let (Some(down), Some(up)) = (node.download_bytes.checked_sub(last_down), node.upload_bytes.checked_sub(last_up)) else {
+                continue;
+            };
+            if seconds <= 0.0 {
+                continue;
+            }
+
+            series.samples.push_back(ThroughputSample {
+                at,
+                rate: Rate {
+                    download: down as f64 / seconds,
+                    upload: up as f64 / seconds,
+                },
+            });
+            while series.samples.len() > self.options.capacity.max(1) {
+                series.samples.pop_front();
+            }
+        }
+    }
+
+    /// Buffered samples of a node, oldest first.
+    pub fn samples(&self, node_uuid: &Uuid) -> impl Iterator<Item = &ThroughputSample> {
+        self.nodes.get(node_uuid).into_iter().flat_map(|series| series.samples.iter())
+    }
+
+    /// Mean rate over the last `window` samples of a node.
+    pub fn moving_average(&self, node_uuid: &Uuid, window: usize) -> Option<Rate> {
+        let samples = &self.nodes.get(node_uuid)?.samples;
+        let recent: Vec<&ThroughputSample> = samples.iter().rev().take(window.max(1)).collect();
+        if recent.is_empty() {
+            return None;
+        }
+        let n = recent.len() as f64;
+        Some(Rate {
+            download: recent.iter().map(|s| s.rate.download).sum::<f64>() / n,
+            upload: recent.iter().map(|s| s.rate.upload).sum::<f64>() / n,
+        })
+    }
+
+    /// `None` until the node has at least one sample.
+    pub fn stats(&self, node_uuid: &Uuid) -> Option<ThroughputStats> {
+        let series = self.nodes.get(node_uuid)?;
+        let current = series.samples.back()?.rate;
+        let peak = series.samples.iter().fold(Rate::default(), |peak, s| Rate {
+            download: peak.download.max(s.rate.download),
+            upload: peak.upload.max(s.rate.upload),
+        });
+        Some(ThroughputStats {
+            node_uuid: *node_uuid,
+            node_name: series.name.clone(),
+            current,
+            reported: series.reported,
+            average: self.moving_average(node_uuid, self.options.average_window)?,
+            peak,
+            samples: series.samples.len(),
+        })
+    }
+
+    /// Stats of every node with samples, highest current total rate first.
+    pub fn all_stats(&self) -> Vec<ThroughputStats> {
+        let mut stats: Vec<ThroughputStats> = self.nodes.keys().filter_map(|uuid| self.stats(uuid)).collect();
+        stats.sort_by(|a, b| b.current.total().total_cmp(&a.current.total()).then_with(|| a.node_name.cmp(&b.node_name)));
+        stats
+    }
+
+    /// Sum of the current rates of all nodes.
+    pub fn total(&self) -> Rate {
+        self.nodes.values().filter_map(|series| series.samples.back()).fold(Rate::default(), |total, s| Rate {
+            download: total.download + s.rate.download,
+            upload: total.upload + s.rate.upload,
+        })
+    }
+}
+
+impl NodesUsageController {
+    /// Polls `get_realtime_usage` every `options.interval` and yields [`ThroughputSampler::all_stats`] after each
+    /// poll. The returned sampler is shared with the stream for ad-hoc queries. Failed polls are yielded as errors
+    /// and polling continues.
+    pub fn sample_throughput(&self, options: ThroughputOptions) -> (Arc<Mutex<ThroughputSampler>>, impl Stream<Item = Result<Vec<ThroughputStats>, crate::ApiError>> + '_) {
+        let sampler = Arc::new(Mutex::new(ThroughputSampler::new(options)));
+        let mut ticker = tokio::time::interval(options.interval.max(Duration::from_millis(1)));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let shared = sampler.clone();
+        let stats = stream::unfold(ticker, move |mut ticker| {
+            let sampler = shared.clone();
+            async move {
+                ticker.tick().await;
+                let item = self.get_realtime_usage().await.map(|response| {
+                    let mut sampler = sampler.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    sampler.record(&response.response, Utc::now());
+                    sampler.all_stats()
+                });
+                Some((item, ticker))
+            }
+        });
+        (sampler, stats)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::api::types::subscriptions::SubscriptionClientType;
+use serde::Serialize;
+
+/// Client app recognised from a subscription request's `User-Agent`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ClientAppInfo {
+    pub name: &'static str,
+    pub version: Option<String>,
+    /// Format to request through `get_by_client_type`; `None` for apps that take the default base64 links.
+    pub client_type: Option<SubscriptionClientType>,
+}
+
+/// `(name, lowercase prefixes, format)`, matched in order against the `User-Agent`, so more specific prefixes come first.
+const KNOWN_APPS: &[(&str, &[&str], Option<SubscriptionClientType>)] = &[
+    ("Happ", &["happ"], None),
+    ("Streisand", &["streisand"], None),
+    ("v2rayNG", &["v2rayng"], None),
+    ("v2rayN", &["v2rayn"], None),
+    ("V2Box", &["v2box"], None),
+    ("Hiddify", &["hiddifynext", "hiddify"], None),
+    ("NekoBox", &["nekobox"], None),
+    ("NekoRay", &["nekoray"], None),
+    ("Shadowrocket", &["shadowrocket"], None),
+    ("FoXray", &["foxray"], None),
+    ("Clash Verge", &["clash-verge", "clash verge"], Some(SubscriptionClientType::Mihomo)),
+    ("Clash Meta", &["clashmeta", "clash.meta", "clash-meta"], Some(SubscriptionClientType::Mihomo)),
+    ("FlClash", &["flclash"], Some(SubscriptionClientType::Mihomo)),
+    ("Koala Clash", &["koala-clash"], Some(SubscriptionClientType::Mihomo)),
+    ("mihomo", &["mihomo"], Some(SubscriptionClientType::Mihomo)),
+    ("Stash", &["stash"], Some(SubscriptionClientType::Stash)),
+    ("Clash", &["clash"], Some(SubscriptionClientType::Clash)),
+    ("Karing", &["karing"], Some(SubscriptionClientType::SingBox)),
+    ("sing-box", &["sing-box", "sfa/", "sfi/", "sfm/", "sft/"], Some(SubscriptionClientType::SingBox)),
+];
+
+/// First sing-box release served by the `singbox` format; older cores get `singbox-legacy`.
+const SINGBOX_MIN_VERSION: (u32, u32) = (1, 11);
+
+/// Identifies the client app behind a `User-Agent`. Returns `None` for unknown agents.
+///
+/// sing-box clients older than 1.11 are mapped to [`SubscriptionClientType::SingBoxLegacy`].
+pub fn detect_client_app(user_agent: &str) -> Option<ClientAppInfo> {
+    let user_agent = user_agent.trim();
+    let lowercase = user_agent.to_ascii_lowercase();
+
+    let (name, prefix, mut client_type) =
+        KNOWN_APPS.iter().find_map(|(name, prefixes, client_type)| prefixes.iter().find(|p| lowercase.starts_with(*p)).map(|p| (*name, *p, client_type.clone())))?;
+    let version = parse_version(&user_agent[prefix.len()..]);
+
+    if name == "sing-box" {
+        let core = sing_box_core_version(&lowercase).or(version.as_deref());
+        if core.and_then(major_minor).is_some_and(|v| v < SINGBOX_MIN_VERSION) {
+            client_type = Some(SubscriptionClientType::SingBoxLegacy);
+        }
+    }
+
+    Some(ClientAppInfo {
+        name,
+        version,
+        client_type,
+    })
+}
+
+impl SubscriptionClientType {
+    /// Format to request for a `User-Agent`, see [`detect_client_app`]. `None` means the default base64 links.
+    pub fn detect(user_agent: &str) -> Option<Self> {
+        detect_client_app(user_agent).and_then(|app| app.client_type)
+    }
+}
+
+/// First token after the app name that looks like a version, e.g. `v1.3.8` in `clash-verge/v1.3.8`.
+fn parse_version(rest: &str) -> Option<String> {
+    rest.split(|c: char| c == '/' || c == ';' || c == '(' || c == ')' || c.is_whitespace())
+        .map(|token| token.trim_start_matches(['v', 'V']))
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+        .map(|token| token.trim_end_matches([',', '.']).to_string())
+}
+
+/// sing-box graphical clients report the core version separately, e.g. `SFA/1.10.3 (…; sing-box 1.10.3)`.
+fn sing_box_core_version(lowercase: &str) -> Option<&str> {
+    let (_, rest) = lowercase.split_once("sing-box ")?;
+    rest.split(|c: char| c == ';' || c == ')' || c.is_whitespace()).next().filter(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+fn major_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.').map(|p| p.chars().take_while(char::is_ascii_digit).collect::<String>().parse::<u32>().ok());
+    Some((parts.next()??, parts.next().flatten().unwrap_or(0)))
+}
@@ -0,0 +1,82 @@
+//! JSON fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// A `NodeDto` named `node-{id}`: connected, enabled, in Germany and without traffic tracking. Top-level keys of
+/// `overrides` replace the defaults.
+pub fn node_json(id: u128, overrides: Value) -> Value {
+    merge(
+        json!({
+            "uuid": Uuid::from_u128(id),
+            "name": format!("node-{id}"),
+            "address": "10.0.0.1",
+            "port": 2222,
+            "isConnected": true,
+            "isDisabled": false,
+            "isConnecting": false,
+            "isNodeOnline": true,
+            "isXrayRunning": true,
+            "lastStatusChange": null,
+            "lastStatusMessage": null,
+            "xrayVersion": null,
+            "nodeVersion": null,
+            "xrayUptime": "0",
+            "isTrafficTrackingActive": false,
+            "trafficResetDay": null,
+            "trafficLimitBytes": null,
+            "trafficUsedBytes": null,
+            "notifyPercent": null,
+            "usersOnline": 0,
+            "viewPosition": 1,
+            "countryCode": "DE",
+            "consumptionMultiplier": 1.0,
+            "cpuCount": null,
+            "cpuModel": null,
+            "totalRam": null,
+            "createdAt": "2025-01-01T00:00:00Z",
+            "updatedAt": "2025-01-01T00:00:00Z",
+            "configProfile": { "activeConfigProfileUuid": null, "activeInbounds": [] },
+            "providerUuid": null,
+            "provider": null
+        }),
+        overrides,
+    )
+}
+
+/// A `HostDto` `host-{id}` on `example.com:443` with the `DEFAULT` security layer and no inbound. Top-level keys of
+/// `overrides` replace the defaults.
+pub fn host_json(id: u128, overrides: Value) -> Value {
+    merge(
+        json!({
+            "uuid": Uuid::from_u128(id),
+            "viewPosition": 0,
+            "remark": format!("host-{id}"),
+            "address": "example.com",
+            "port": 443,
+            "path": null,
+            "sni": null,
+            "host": null,
+            "alpn": null,
+            "fingerprint": null,
+            "isDisabled": false,
+            "securityLayer": "DEFAULT",
+            "xHttpExtraParams": null,
+            "muxParams": null,
+            "sockoptParams": null,
+            "inbound": { "configProfileUuid": null, "configProfileInboundUuid": null },
+            "serverDescription": null,
+            "tag": null,
+            "vlessRouteId": null
+        }),
+        overrides,
+    )
+}
+
+fn merge(mut base: Value, overrides: Value) -> Value {
+    if let (Some(base), Value::Object(overrides)) = (base.as_object_mut(), overrides) {
+        base.extend(overrides);
+    }
+    base
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use remnawave::tools::{group_by_country, summarize_nodes_by_country, summarize_usage_by_country};
+use remnawave::types::{CountryCode, CreateNodeRequestDto, NodeDto, NodesUsageData};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn node_json(id: u128, country: &str, connected: bool, users: i32, used: usize) -> Value {
+    common::node_json(
+        id,
+        json!({
+            "isConnected": connected,
+            "isNodeOnline": connected,
+            "isXrayRunning": connected,
+            "trafficUsedBytes": used,
+            "usersOnline": users,
+            "countryCode": country
+        }),
+    )
+}
+
+fn usage_json(id: u128, country: &str, download: usize, upload: usize) -> Value {
+    json!({
+        "nodeUuid": Uuid::from_u128(id),
+        "nodeName": format!("node-{id}"),
+        "nodeCountryCode": country,
+        "total": download + upload,
+        "totalDownload": download,
+        "totalUpload": upload,
+        "humanReadableTotal": "",
+        "humanReadableTotalDownload": "",
+        "humanReadableTotalUpload": "",
+        "date": "2025-03-20"
+    })
+}
+
+#[test]
+fn validates_codes_and_renders_flags() {
+    let de: CountryCode = "de".parse().unwrap();
+    assert_eq!(de, "DE");
+    assert_eq!((de.name(), de.flag().as_str()), ("Germany", "🇩🇪"));
+    assert_eq!(CountryCode::from_flag("🇩🇪"), Some(de));
+    assert_eq!(CountryCode::from_flag("🇿🇿").map(|code| (code.to_string(), code.is_listed())), Some(("ZZ".to_string(), false)));
+    assert_eq!(CountryCode::from_flag("🇩"), None);
+    assert_eq!(CountryCode::from_flag("DE"), None);
+
+    let unknown = CountryCode::default();
+    assert!(unknown.is_unknown());
+    assert_eq!((unknown.as_str(), unknown.name()), ("XX", "Unknown"));
+    assert_eq!(CountryCode::from_flag(&unknown.flag()), Some(unknown));
+
+    assert!("ZZ".parse::<CountryCode>().is_err());
+    assert!("DEU".parse::<CountryCode>().is_err());
+    assert_eq!(CountryCode::all().count(), 249);
+    assert!(CountryCode::all().all(|code| CountryCode::from_flag(&code.flag()) == Some(code)));
+
+    assert_eq!(serde_json::to_value(de).unwrap(), json!("DE"));
+    let kosovo: NodeDto = serde_json::from_value(node_json(1, "XK", true, 0, 0)).unwrap();
+    assert_eq!((kosovo.country_code.as_str(), kosovo.country_code.is_listed(), kosovo.country_code.name()), ("XK", false, "Unknown"));
+    assert_eq!(CountryCode::from_flag(&kosovo.country_code.flag()), Some(kosovo.country_code));
+    assert_eq!(serde_json::to_value(kosovo.country_code).unwrap(), json!("XK"));
+    assert!("XK".parse::<CountryCode>().is_err());
+    assert!(de.is_listed() && unknown.is_listed());
+    let usage: NodesUsageData = serde_json::from_value(usage_json(1, "xk", 1, 1)).unwrap();
+    assert_eq!(usage.node_country_code, "XK");
+    assert_eq!(serde_json::from_value::<NodeDto>(node_json(1, "Germany", true, 0, 0)).unwrap().country_code, CountryCode::UNKNOWN);
+    let request: CreateNodeRequestDto = serde_json::from_value(json!({
+        "name": "n", "address": "a", "port": 1, "isTrafficTrackingActive": false, "trafficLimitBytes": 0, "notifyPercent": 0,
+        "trafficResetDay": 1, "consumptionMultiplier": 1.0, "configProfile": { "activeConfigProfileUuid": Uuid::nil(), "activeInbounds": [] },
+        "providerUuid": null
+    }))
+    .unwrap();
+    assert_eq!(request.country_code, CountryCode::UNKNOWN);
+}
+
+#[test]
+fn summarizes_nodes_and_usage_per_country() {
+    let nodes: Vec<NodeDto> = [node_json(1, "NL", true, 10, 100), node_json(2, "DE", true, 5, 50), node_json(3, "NL", false, 0, 25), node_json(4, "XX", true, 1, 0)]
+        .into_iter()
+        .map(|n| serde_json::from_value(n).unwrap())
+        .collect();
+
+    let groups = group_by_country(&nodes, |node| node.country_code);
+    assert_eq!(groups.keys().map(CountryCode::as_str).collect::<Vec<_>>(), ["DE", "NL", "XX"]);
+    assert_eq!(groups[&"NL".parse().unwrap()].len(), 2);
+
+    let summaries = summarize_nodes_by_country(&nodes);
+    let nl = &summaries[1];
+    assert_eq!((nl.country.as_str(), nl.nodes, nl.online, nl.users_online, nl.traffic_used_bytes), ("NL", 2, 1, 10, 125));
+
+    let usage: Vec<NodesUsageData> = [usage_json(1, "NL", 10, 1), usage_json(3, "NL", 5, 0), usage_json(1, "NL", 1, 1), usage_json(2, "DE", 30, 3)]
+        .into_iter()
+        .map(|u| serde_json::from_value(u).unwrap())
+        .collect();
+    let usage = summarize_usage_by_country(&usage);
+    assert_eq!(usage.iter().map(|u| (u.country.as_str(), u.nodes, u.total, u.download, u.upload)).collect::<Vec<_>>(), [("DE", 1, 33, 30, 3), ("NL", 2, 18, 16, 2)]);
+}
@@ -0,0 +1,186 @@
+#![cfg(feature = "formats")]
+
+use remnawave::types::{MihomoRule, SubscriptionClientType, SubscriptionConfig};
+use serde_json::json;
+
+const MIHOMO: &str = r#"mixed-port: 7890
+allow-lan: false
+mode: rule
+log-level: info
+dns:
+  enable: true
+  ipv6: false
+  enhanced-mode: fake-ip
+  nameserver:
+    - https://1.1.1.1/dns-query
+proxies:
+  - name: "🇩🇪 Germany"
+    type: vless
+    server: de.example.com
+    port: 443
+    uuid: 0f5c7a5e-4a6b-4d38-9a1f-1c2d3e4f5a6b
+    network: tcp
+    tls: true
+    udp: true
+    flow: xtls-rprx-vision
+    servername: www.example.com
+    client-fingerprint: chrome
+    reality-opts:
+      public-key: pbk
+      short-id: 0123abcd
+  - name: "🇳🇱 Netherlands"
+    type: trojan
+    server: nl.example.com
+    port: 8443
+    password: secret
+    sni: nl.example.com
+    skip-cert-verify: false
+proxy-groups:
+  - name: PROXY
+    type: select
+    proxies:
+      - "🇩🇪 Germany"
+      - "🇳🇱 Netherlands"
+  - name: AUTO
+    type: url-test
+    url: https://www.gstatic.com/generate_204
+    interval: 300
+    proxies:
+      - "🇩🇪 Germany"
+      - "🇳🇱 Netherlands"
+rules:
+  - DOMAIN-SUFFIX,local,DIRECT
+  - IP-CIDR,192.168.0.0/16,DIRECT,no-resolve
+  - AND,((NETWORK,UDP),(DST-PORT,443)),REJECT
+  - GEOIP,private,DIRECT,no-resolve
+  - MATCH,PROXY
+"#;
+
+const SING_BOX: &str = r#"{
+  "log": { "level": "warn" },
+  "dns": { "servers": [{ "tag": "cloudflare", "address": "https://1.1.1.1/dns-query" }], "final": "cloudflare" },
+  "inbounds": [{ "type": "tun", "tag": "tun-in", "address": ["172.19.0.1/30"], "auto_route": true, "stack": "system" }],
+  "outbounds": [
+    { "type": "selector", "tag": "proxy", "outbounds": ["🇩🇪 Germany", "direct"], "interrupt_exist_connections": true },
+    {
+      "type": "vless",
+      "tag": "🇩🇪 Germany",
+      "server": "de.example.com",
+      "server_port": 443,
+      "uuid": "0f5c7a5e-4a6b-4d38-9a1f-1c2d3e4f5a6b",
+      "flow": "xtls-rprx-vision",
+      "tls": { "enabled": true, "server_name": "www.example.com", "utls": { "enabled": true, "fingerprint": "chrome" }, "reality": { "enabled": true, "public_key": "pbk", "short_id": "0123abcd" } }
+    },
+    { "type": "direct", "tag": "direct" }
+  ],
+  "route": {
+    "rules": [{ "action": "sniff" }, { "protocol": "dns", "action": "hijack-dns" }, { "ip_is_private": true, "outbound": "direct" }],
+    "final": "proxy",
+    "auto_detect_interface": true
+  },
+  "experimental": { "cache_file": { "enabled": true } }
+}"#;
+
+const XRAY_JSON: &str = r#"[
+  {
+    "remarks": "🇩🇪 Germany",
+    "dns": { "servers": ["1.1.1.1"] },
+    "inbounds": [{ "tag": "socks", "port": 10808, "listen": "127.0.0.1", "protocol": "socks", "settings": { "udp": true } }],
+    "outbounds": [
+      {
+        "tag": "proxy",
+        "protocol": "vless",
+        "settings": { "vnext": [{ "address": "de.example.com", "port": 443, "users": [{ "id": "0f5c7a5e-4a6b-4d38-9a1f-1c2d3e4f5a6b", "encryption": "none", "flow": "xtls-rprx-vision" }] }] },
+        "streamSettings": { "network": "tcp", "security": "reality", "realitySettings": { "serverName": "www.example.com", "fingerprint": "chrome", "publicKey": "pbk", "shortId": "0123abcd" } },
+        "mux": { "enabled": false }
+      },
+      { "tag": "direct", "protocol": "freedom" },
+      { "tag": "block", "protocol": "blackhole" }
+    ],
+    "routing": { "domainStrategy": "IPIfNonMatch", "rules": [{ "type": "field", "ip": ["geoip:private"], "outboundTag": "direct" }] }
+  }
+]"#;
+
+#[test]
+fn mihomo_subscription_round_trips_through_text() {
+    let config = SubscriptionConfig::parse(&SubscriptionClientType::Mihomo, MIHOMO).unwrap();
+    let SubscriptionConfig::Mihomo(mihomo) = &config else {
+        panic!("expected a mihomo config")
+    };
+    assert_eq!(config.proxy_names(), ["🇩🇪 Germany", "🇳🇱 Netherlands"]);
+    assert_eq!(mihomo.proxy("🇳🇱 Netherlands").and_then(|p| p.port), Some(8443));
+    assert_eq!(mihomo.proxy_group("AUTO").unwrap().extra.get("interval"), Some(&json!(300)));
+    assert_eq!(mihomo.rules[2].payload.as_deref(), Some("((NETWORK,UDP),(DST-PORT,443))"));
+    assert!(mihomo.extra.contains_key("dns"));
+
+    let text = config.to_text().unwrap();
+    assert_eq!(serde_yaml::from_str::<serde_yaml::Value>(&text).unwrap(), serde_yaml::from_str::<serde_yaml::Value>(MIHOMO).unwrap());
+    assert_eq!(SubscriptionConfig::parse(&SubscriptionClientType::Clash, &text).unwrap(), config);
+}
+
+#[test]
+fn sing_box_and_xray_json_subscriptions_round_trip_through_text() {
+    let config = SubscriptionConfig::parse(&SubscriptionClientType::SingBox, SING_BOX).unwrap();
+    let SubscriptionConfig::SingBox(sing_box) = &config else {
+        panic!("expected a sing-box config")
+    };
+    assert_eq!(config.proxy_names(), ["🇩🇪 Germany"]);
+    assert_eq!(sing_box.outbound("proxy").unwrap().outbounds, ["🇩🇪 Germany", "direct"]);
+    let route = sing_box.route.as_ref().unwrap();
+    assert_eq!((route.final_outbound.as_deref(), route.rules[0].action.as_deref()), (Some("proxy"), Some("sniff")));
+    let text = config.to_text().unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&text).unwrap(), serde_json::from_str::<serde_json::Value>(SING_BOX).unwrap());
+
+    let config = SubscriptionConfig::parse(&SubscriptionClientType::V2RayJson, XRAY_JSON).unwrap();
+    let SubscriptionConfig::XrayJson(xray) = &config else {
+        panic!("expected an xray json subscription")
+    };
+    assert_eq!(config.proxy_names(), ["🇩🇪 Germany"]);
+    assert_eq!(xray[0].outbound("proxy").unwrap().extra.get("mux"), Some(&json!({ "enabled": false })));
+    assert_eq!(xray[0].outbound("block").unwrap().settings, None);
+    assert_eq!(xray[0].routing.as_ref().unwrap().rules[0].outbound_tag.as_deref(), Some("direct"));
+    let text = config.to_text().unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&text).unwrap(), serde_json::from_str::<serde_json::Value>(XRAY_JSON).unwrap());
+}
+
+#[test]
+fn parses_mihomo_rules() {
+    let parsed = |rule: &str| {
+        let parsed: MihomoRule = rule.parse().unwrap();
+        assert_eq!(parsed.to_string(), rule);
+        (parsed.rule_type, parsed.payload, parsed.target, parsed.options)
+    };
+    let some = |s: &str| Some(s.to_string());
+
+    assert_eq!(parsed("DOMAIN-SUFFIX,google.com,PROXY"), ("DOMAIN-SUFFIX".into(), some("google.com"), "PROXY".into(), vec![]));
+    assert_eq!(parsed("IP-CIDR6,2001:db8::/32,DIRECT,no-resolve"), ("IP-CIDR6".into(), some("2001:db8::/32"), "DIRECT".into(), vec!["no-resolve".into()]));
+    assert_eq!(parsed("IP-CIDR,10.0.0.0/8,DIRECT,no-resolve,src"), ("IP-CIDR".into(), some("10.0.0.0/8"), "DIRECT".into(), vec!["no-resolve".into(), "src".into()]));
+    assert_eq!(parsed("MATCH,PROXY"), ("MATCH".into(), None, "PROXY".into(), vec![]));
+    assert_eq!(parsed("NOT,((GEOIP,CN)),PROXY"), ("NOT".into(), some("((GEOIP,CN))"), "PROXY".into(), vec![]));
+    assert_eq!(
+        parsed("AND,((DOMAIN-KEYWORD,video),OR,((NETWORK,UDP),(NOT,((DST-PORT,443))))),REJECT,no-resolve"),
+        ("AND".into(), some("((DOMAIN-KEYWORD,video),OR,((NETWORK,UDP),(NOT,((DST-PORT,443)))))"), "REJECT".into(), vec!["no-resolve".into()])
+    );
+    assert_eq!(parsed("SUB-RULE,(NETWORK,tcp),streaming"), ("SUB-RULE".into(), some("(NETWORK,tcp)"), "streaming".into(), vec![]));
+
+    let rule: MihomoRule = " DOMAIN , example.com , DIRECT ".parse().unwrap();
+    assert_eq!(rule, MihomoRule::new("DOMAIN", "example.com", "DIRECT"));
+    assert!(MihomoRule::new("AND", "((NETWORK,UDP))", "REJECT").is_logical());
+    assert_eq!(MihomoRule::match_all("PROXY").with_option("no-resolve").to_string(), "MATCH,PROXY,no-resolve");
+
+    for malformed in [
+        "",
+        "MATCH",
+        "MATCH,",
+        "DOMAIN,example.com",
+        "DOMAIN,example.com,",
+        "AND,((NETWORK,UDP),(DST-PORT,443),REJECT",
+        "AND,(NETWORK,UDP)),REJECT",
+        "OR,((NETWORK,UDP))",
+        "NOT,(GEOIP,CN) PROXY",
+    ] {
+        let error = malformed.parse::<MihomoRule>().unwrap_err();
+        assert_eq!(error.0, malformed);
+    }
+    assert!(serde_yaml::from_str::<Vec<MihomoRule>>("- DOMAIN,example.com").is_err());
+}
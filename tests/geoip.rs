@@ -0,0 +1,205 @@
+#![cfg(feature = "geoip")]
+
+use chrono::Utc;
+use remnawave::tools::{AsnCount, CountryCount, GeoIp, GeoIpInfo};
+use remnawave::types::{SubscriptionRequestHistoryData, SubscriptionRequestRecord};
+use uuid::Uuid;
+
+enum Value {
+    Str(&'static str),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    Map(Vec<(&'static str, Value)>),
+    Array(Vec<Value>),
+}
+
+/// Control byte(s) of a data field; sizes above 28 use one extra byte, which covers every test value.
+fn header(kind: u8, size: usize, out: &mut Vec<u8>) {
+    let (size, extra) = if size < 29 {
+        (size as u8, None)
+    } else {
+        (29, Some((size - 29) as u8))
+    };
+    if kind < 8 {
+        out.push(kind << 5 | size);
+    } else {
+        out.extend([size, kind - 7]);
+    }
+    out.extend(extra);
+}
+
+fn encode(value: &Value, out: &mut Vec<u8>) {
+    let uint = |kind: u8, bytes: &[u8], out: &mut Vec<u8>| {
+        let bytes: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+        header(kind, bytes.len(), out);
+        out.extend(bytes);
+    };
+    match value {
+        Value::Str(s) => {
+            header(2, s.len(), out);
+            out.extend(s.as_bytes());
+        }
+        Value::U16(n) => uint(5, &n.to_be_bytes(), out),
+        Value::U32(n) => uint(6, &n.to_be_bytes(), out),
+        Value::U64(n) => uint(9, &n.to_be_bytes(), out),
+        Value::Map(entries) => {
+            header(7, entries.len(), out);
+            for (key, value) in entries {
+                encode(&Value::Str(key), out);
+                encode(value, out);
+            }
+        }
+        Value::Array(items) => {
+            header(11, items.len(), out);
+            for item in items {
+                encode(item, out);
+            }
+        }
+    }
+}
+
+/// Builds an IPv4 MaxMind DB with 24-bit records from `(network, prefix length, data)` entries.
+fn mmdb(database_type: &'static str, networks: Vec<([u8; 4], u32, Value)>) -> Vec<u8> {
+    enum Slot {
+        Empty,
+        Node(usize),
+        Data(usize),
+    }
+    let mut nodes: Vec<[Slot; 2]> = vec![[Slot::Empty, Slot::Empty]];
+    let mut data = Vec::new();
+
+    for (network, prefix_len, value) in networks {
+        let offset = data.len();
+        encode(&value, &mut data);
+        let bits = u32::from_be_bytes(network);
+        let mut node = 0;
+        for depth in 0..prefix_len {
+            let bit = (bits >> (31 - depth) & 1) as usize;
+            if depth == prefix_len - 1 {
+                nodes[node][bit] = Slot::Data(offset);
+            } else if let Slot::Node(next) = nodes[node][bit] {
+                node = next;
+            } else {
+                nodes.push([Slot::Empty, Slot::Empty]);
+                nodes[node][bit] = Slot::Node(nodes.len() - 1);
+                node = nodes.len() - 1;
+            }
+        }
+    }
+
+    let node_count = nodes.len();
+    let mut out = Vec::new();
+    for slots in &nodes {
+        for slot in slots {
+            let record = match slot {
+                Slot::Empty => node_count,
+                Slot::Node(next) => *next,
+                Slot::Data(offset) => node_count + 16 + offset,
+            } as u32;
+            out.extend(&record.to_be_bytes()[1..]);
+        }
+    }
+    out.extend([0; 16]);
+    out.extend(data);
+    out.extend(b"\xAB\xCD\xEFMaxMind.com");
+    let metadata = Value::Map(vec![
+        ("binary_format_major_version", Value::U16(2)),
+        ("binary_format_minor_version", Value::U16(0)),
+        ("build_epoch", Value::U64(1_700_000_000)),
+        ("database_type", Value::Str(database_type)),
+        ("description", Value::Map(vec![("en", Value::Str("Test"))])),
+        ("ip_version", Value::U16(4)),
+        ("languages", Value::Array(vec![Value::Str("en")])),
+        ("node_count", Value::U32(node_count as u32)),
+        ("record_size", Value::U16(24)),
+    ]);
+    encode(&metadata, &mut out);
+    out
+}
+
+fn city(iso_code: &'static str, country: &'static str, city: &'static str) -> Value {
+    Value::Map(vec![
+        ("city", Value::Map(vec![("names", Value::Map(vec![("en", Value::Str(city)), ("de", Value::Str("x"))]))])),
+        ("country", Value::Map(vec![("iso_code", Value::Str(iso_code)), ("names", Value::Map(vec![("en", Value::Str(country))]))])),
+        ("location", Value::Map(vec![("time_zone", Value::Str("Europe/Berlin"))])),
+    ])
+}
+
+fn asn(number: u32, organization: &'static str) -> Value {
+    Value::Map(vec![("autonomous_system_number", Value::U32(number)), ("autonomous_system_organization", Value::Str(organization))])
+}
+
+fn geoip() -> GeoIp {
+    let cities = mmdb("GeoLite2-City", vec![([10, 0, 0, 0], 24, city("DE", "Germany", "Berlin")), ([10, 0, 1, 0], 24, city("NL", "Netherlands", "Amsterdam"))]);
+    let asns = mmdb("GeoLite2-ASN", vec![([10, 0, 0, 0], 16, asn(64500, "Example Net"))]);
+    GeoIp::from_bytes(cities).unwrap().with_database_bytes(asns).unwrap()
+}
+
+#[test]
+fn looks_up_and_merges_databases() {
+    let geoip = geoip();
+
+    assert_eq!(
+        geoip.lookup_str("10.0.0.7").unwrap(),
+        Some(GeoIpInfo {
+            country_code: Some("DE".to_string()),
+            country: Some("Germany".to_string()),
+            city: Some("Berlin".to_string()),
+            asn: Some(64500),
+            as_organization: Some("Example Net".to_string()),
+        })
+    );
+    assert_eq!(geoip.lookup_str("10.0.9.1").unwrap().unwrap().asn, Some(64500));
+    assert_eq!(geoip.lookup_str("10.0.9.1").unwrap().unwrap().country_code, None);
+    assert_eq!(geoip.lookup_str("192.0.2.1").unwrap(), None);
+    assert_eq!(geoip.lookup_str("2001:db8::1").unwrap(), None);
+    assert!(geoip.lookup_str("not an ip").is_err());
+}
+
+#[test]
+fn enriches_and_aggregates_history() {
+    let geoip = geoip();
+    let record = |id: i64, ip: Option<&str>| SubscriptionRequestRecord {
+        id,
+        user_uuid: Uuid::nil(),
+        request_ip: ip.map(str::to_string),
+        user_agent: None,
+        request_at: Utc::now(),
+    };
+    let history = SubscriptionRequestHistoryData {
+        records: vec![record(1, Some("10.0.1.1")), record(2, Some("10.0.0.1")), record(3, Some("10.0.1.2")), record(4, Some("192.0.2.1")), record(5, None)],
+        total: 5,
+    };
+
+    let enriched = geoip.enrich(&history.records);
+    assert_eq!(enriched[0].record.id, 1);
+    assert_eq!(enriched[0].geo.as_ref().unwrap().city.as_deref(), Some("Amsterdam"));
+    assert!(enriched[3].geo.is_none());
+
+    let aggregates = history.geoip_aggregates(&geoip);
+    assert_eq!(
+        aggregates.by_country,
+        [
+            CountryCount {
+                country_code: "NL".to_string(),
+                country: Some("Netherlands".to_string()),
+                requests: 2
+            },
+            CountryCount {
+                country_code: "DE".to_string(),
+                country: Some("Germany".to_string()),
+                requests: 1
+            },
+        ]
+    );
+    assert_eq!(
+        aggregates.by_asn,
+        [AsnCount {
+            asn: 64500,
+            as_organization: Some("Example Net".to_string()),
+            requests: 3
+        }]
+    );
+    assert_eq!(aggregates.unknown, 2);
+}
@@ -0,0 +1,91 @@
+use base64::Engine;
+use remnawave::types::{decode_happ_announce, encode_happ_announce, HappDnsType, HappError, HappRoutingLinkMode, HappRoutingProfile};
+use serde_json::json;
+
+fn happ_json() -> serde_json::Value {
+    json!({
+        "Name": "RU direct",
+        "GlobalProxy": "true",
+        "RemoteDNSType": "DoH",
+        "RemoteDNSDomain": "https://cloudflare-dns.com/dns-query",
+        "RemoteDNSIP": "1.1.1.1",
+        "DomesticDNSType": "DoU",
+        "DomesticDNSDomain": "",
+        "DomesticDNSIP": "77.88.8.8",
+        "Geoipurl": "https://github.com/hydraponique/roscomvpn-geoip/releases/latest/download/geoip.dat",
+        "Geositeurl": "https://github.com/hydraponique/roscomvpn-geosite/releases/latest/download/geosite.dat",
+        "LastUpdated": "1730000000",
+        "DnsHosts": { "cloudflare-dns.com": "1.1.1.1" },
+        "DirectSites": ["geosite:category-ru"],
+        "DirectIp": ["geoip:ru", "10.0.0.0/8"],
+        "ProxySites": [],
+        "ProxyIp": [],
+        "BlockSites": ["geosite:category-ads"],
+        "BlockIp": [],
+        "DomainStrategy": "IPIfNonMatch",
+        "FakeDNS": "false",
+        "UseChunkFiles": "true"
+    })
+}
+
+#[test]
+fn decodes_happ_json() {
+    let profile: HappRoutingProfile = serde_json::from_value(happ_json()).unwrap();
+
+    assert_eq!(profile.name, "RU direct");
+    assert!(profile.global_proxy);
+    assert!(!profile.fake_dns);
+    assert_eq!(profile.remote_dns_type, Some(HappDnsType::DoH));
+    assert_eq!(profile.direct_ip, ["geoip:ru", "10.0.0.0/8"]);
+    assert_eq!(profile.extra["UseChunkFiles"], "true");
+    assert_eq!(serde_json::to_value(&profile).unwrap(), happ_json());
+    assert!(profile.validate().is_empty());
+}
+
+#[test]
+fn deep_link_round_trip() {
+    let mut profile = HappRoutingProfile::new("Blocklist");
+    profile.block_sites.push("geosite:category-ads-all".to_string());
+    profile.fake_dns = true;
+
+    let link = profile.to_deep_link(HappRoutingLinkMode::OnAdd).unwrap();
+    assert!(link.starts_with("happ://routing/onadd/"));
+    assert_eq!(HappRoutingProfile::parse_deep_link(&link).unwrap(), (HappRoutingLinkMode::OnAdd, profile.clone()));
+
+    let add = profile.to_deep_link(HappRoutingLinkMode::Add).unwrap();
+    assert_eq!(add.parse::<HappRoutingProfile>().unwrap(), profile);
+
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(&happ_json()).unwrap());
+    assert_eq!(HappRoutingProfile::decode(&payload).unwrap().name, "RU direct");
+}
+
+#[test]
+fn rejects_malformed_links() {
+    assert!(matches!(HappRoutingProfile::from_deep_link("happ://crypt/abc"), Err(HappError::InvalidRoutingLink(_))));
+    assert!(matches!(HappRoutingProfile::from_deep_link("happ://routing/remove/e30"), Err(HappError::InvalidRoutingLink(_))));
+    assert!(matches!(HappRoutingProfile::from_deep_link("happ://routing/add/!!!"), Err(HappError::Base64(_))));
+}
+
+#[test]
+fn validation() {
+    let mut profile = HappRoutingProfile::new("");
+    profile.remote_dns_type = Some(HappDnsType::DoH);
+    profile.remote_dns_domain = Some("1.1.1.1".to_string());
+    profile.domestic_dns_ip = Some("dns.local".to_string());
+    profile.geoip_url = Some("ftp://example.com/geoip.dat".to_string());
+    profile.dns_hosts.insert("a/b".to_string(), "nope".to_string());
+    profile.proxy_ip = vec!["10.0.0.0/33".to_string(), "geoip:us".to_string(), "2001:db8::/32".to_string()];
+    profile.direct_sites = vec![" ".to_string()];
+
+    let pointers: Vec<_> = profile.validate().into_iter().map(|d| d.pointer).collect();
+    assert_eq!(pointers, ["/Name", "/RemoteDNSDomain", "/DomesticDNSIP", "/Geoipurl", "/DnsHosts/a~1b", "/ProxyIp/0", "/DirectSites/0"]);
+}
+
+#[test]
+fn announce() {
+    let header = encode_happ_announce("Maintenance tonight 🛠").unwrap();
+    assert!(header.starts_with("base64:"));
+    assert_eq!(decode_happ_announce(&header).unwrap(), "Maintenance tonight 🛠");
+    assert_eq!(decode_happ_announce("plain text").unwrap(), "plain text");
+    assert!(matches!(encode_happ_announce(&"a".repeat(201)), Err(HappError::AnnounceTooLong(201))));
+}
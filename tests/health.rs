@@ -0,0 +1,124 @@
+#![cfg(feature = "formats")]
+
+use remnawave::types::{SubscriptionClientType, SubscriptionHealthError};
+use remnawave::RemnawaveApiClient;
+use serde_json::json;
+use uuid::Uuid;
+
+fn host(remark: &str, is_hidden: bool) -> serde_json::Value {
+    json!({
+        "address": "node.example.com",
+        "port": 443,
+        "protocol": "vless",
+        "remark": remark,
+        "password": { "trojanPassword": "t", "vlessPassword": "v", "ssPassword": "s" },
+        "dbData": {
+            "rawInbound": {},
+            "inboundTag": "VLESS",
+            "uuid": Uuid::nil(),
+            "configProfileUuid": null,
+            "configProfileInboundUuid": null,
+            "isDisabled": false,
+            "viewPosition": 0,
+            "remark": remark,
+            "isHidden": is_hidden,
+            "tag": null,
+            "vlessRouteId": null
+        }
+    })
+}
+
+fn raw_subscription() -> String {
+    json!({
+        "response": {
+            "user": {
+                "uuid": Uuid::nil(),
+                "shortUuid": "abc",
+                "username": "alice",
+                "status": "ACTIVE",
+                "usedTrafficBytes": 0,
+                "lifetimeUsedTrafficBytes": 0,
+                "trafficLimitBytes": 0,
+                "trafficLimitStrategy": "NO_RESET",
+                "subLastUserAgent": null,
+                "subLastOpenedAt": null,
+                "expireAt": "2030-01-01T00:00:00Z",
+                "onlineAt": null,
+                "subRevokedAt": null,
+                "lastTrafficResetAt": null,
+                "trojanPassword": "t",
+                "vlessUuid": Uuid::nil(),
+                "ssPassword": "s",
+                "description": null,
+                "tag": null,
+                "telegramId": null,
+                "email": null,
+                "hwidDeviceLimit": null,
+                "firstConnectedAt": null,
+                "lastTriggeredThreshold": 0,
+                "createdAt": "2025-01-01T00:00:00Z",
+                "updatedAt": "2025-01-01T00:00:00Z",
+                "activeInternalSquads": [],
+                "externalSquadUuid": null,
+                "subscriptionUrl": "https://sub.example.com/abc",
+                "lastConnectedNode": null,
+                "happ": { "cryptoLink": "happ://crypt/abc" }
+            },
+            "convertedUserInfo": { "daysLeft": 10.0, "trafficLimit": "∞", "trafficUsed": "0 B", "lifetimeTrafficUsed": "0 B", "isHwidLimited": false },
+            "headers": {},
+            "rawHosts": [host("DE {{USERNAME}}", false), host("NL", false), host("Hidden", true)]
+        }
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn reports_missing_hosts_parse_failures_and_empty_configs() {
+    let mut server = mockito::Server::new_async().await;
+    let mut mocks = vec![server.mock("GET", "/api/subscriptions/by-short-uuid/abc/raw").with_body(raw_subscription()).create_async().await];
+
+    let mihomo = "proxies:\n  - { name: DE alice, type: vless, server: a, port: 443 }\n  - { name: NL, type: vless, server: b, port: 443 }\n";
+    let singbox = json!({ "outbounds": [
+        { "type": "selector", "tag": "proxy", "outbounds": ["DE alice", "NL"] },
+        { "type": "vless", "tag": "DE alice", "server": "a", "server_port": 443 },
+        { "type": "vless", "tag": "NL", "server": "b", "server_port": 443 }
+    ] });
+    let xray = json!([{ "remarks": "DE alice", "outbounds": [{ "protocol": "vless", "tag": "proxy" }] }]);
+
+    let bodies = [
+        ("mihomo", 200, mihomo.to_string()),
+        ("clash", 200, "proxies: [".to_string()),
+        ("stash", 200, "mixed-port: 7890\n".to_string()),
+        ("singbox", 200, singbox.to_string()),
+        ("singbox-legacy", 500, r#"{"message":"Template not found"}"#.to_string()),
+        ("json", 200, xray.to_string()),
+        ("v2ray-Json", 200, xray.to_string()),
+    ];
+    for (client_type, status, body) in bodies {
+        mocks.push(server.mock("GET", format!("/api/sub/abc/{client_type}").as_str()).with_status(status).with_body(body).create_async().await);
+    }
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let report = client.subscriptions.check_health("abc".to_string()).await.unwrap();
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+
+    assert_eq!(report.expected_hosts, ["DE alice", "NL"]);
+    assert_eq!(report.client_types.len(), SubscriptionClientType::ALL.len());
+    assert!(!report.is_healthy());
+
+    let health = |client_type: SubscriptionClientType| report.client_types.iter().find(|c| c.client_type == client_type).unwrap();
+    assert!(health(SubscriptionClientType::Mihomo).is_healthy());
+    assert!(health(SubscriptionClientType::SingBox).is_healthy());
+    assert!(matches!(health(SubscriptionClientType::Clash).error, Some(SubscriptionHealthError::Parse(_))));
+    assert!(health(SubscriptionClientType::Stash).is_empty());
+    assert!(matches!(health(SubscriptionClientType::SingBoxLegacy).error, Some(SubscriptionHealthError::Request(_))));
+    assert_eq!(health(SubscriptionClientType::Json).missing_hosts, ["NL"]);
+
+    let unhealthy: Vec<_> = report.unhealthy().map(|c| c.client_type.clone()).collect();
+    assert_eq!(
+        unhealthy,
+        [SubscriptionClientType::Stash, SubscriptionClientType::SingBoxLegacy, SubscriptionClientType::Json, SubscriptionClientType::V2RayJson, SubscriptionClientType::Clash]
+    );
+}
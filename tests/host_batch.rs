@@ -0,0 +1,136 @@
+mod common;
+
+use remnawave::tools::{HostBatchError, HostOverrides};
+use remnawave::types::{CreateHostRequestDto, FingerprintType, HostDto, HostInboundRequest, MuxParams};
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const PROFILE: Uuid = Uuid::from_u128(1);
+const INBOUND: Uuid = Uuid::from_u128(10);
+const SOURCE: Uuid = Uuid::from_u128(100);
+
+fn host_json(uuid: Uuid, remark: &str, inbound: Option<Uuid>, tag: Option<&str>) -> Value {
+    common::host_json(
+        uuid.as_u128(),
+        json!({
+            "remark": remark,
+            "address": "de.example.com",
+            "path": "/ws",
+            "sni": "de.example.com",
+            "alpn": "h2",
+            "fingerprint": "chrome",
+            "muxParams": { "enabled": true, "concurrency": 8 },
+            "inbound": { "configProfileUuid": inbound.map(|_| PROFILE), "configProfileInboundUuid": inbound },
+            "serverDescription": "Frankfurt",
+            "tag": tag
+        }),
+    )
+}
+
+fn inbound(uuid: Uuid) -> HostInboundRequest {
+    HostInboundRequest {
+        config_profile_uuid: PROFILE,
+        config_profile_inbound_uuid: uuid,
+    }
+}
+
+#[tokio::test]
+async fn clones_a_host_and_rolls_back_the_created_copies() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", format!("/api/hosts/{SOURCE}").as_str())
+        .with_body(json!({ "response": host_json(SOURCE, "DE", Some(INBOUND), Some("EU")) }).to_string())
+        .create_async()
+        .await;
+    let created = server
+        .mock("POST", "/api/hosts")
+        .match_body(mockito::Matcher::PartialJson(
+            json!({ "remark": "DE 2", "address": "de2.example.com", "sni": "de.example.com", "fingerprint": "chrome", "tag": "clone-1", "muxParams": { "concurrency": 8 } }),
+        ))
+        .with_status(201)
+        .with_body(json!({ "response": host_json(Uuid::from_u128(101), "DE 2", Some(INBOUND), Some("clone-1")) }).to_string())
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/api/hosts")
+        .match_body(mockito::Matcher::PartialJson(json!({ "remark": "DE 3" })))
+        .with_status(400)
+        .with_body(r#"{"message":"Host already exists"}"#)
+        .create_async()
+        .await;
+    let rollback = server
+        .mock("POST", "/api/hosts/bulk/delete")
+        .match_body(mockito::Matcher::Json(json!({ "uuids": [Uuid::from_u128(101)] })))
+        .with_body(json!({ "response": [] }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let overrides = [HostOverrides::default().remark("DE 2").address("de2.example.com"), HostOverrides::default().remark("DE 3")];
+    let batch = client.hosts.clone_host(SOURCE, overrides, "clone-1").await.unwrap();
+    assert_eq!(batch.items.len(), 2);
+    assert!(!batch.is_complete());
+    assert_eq!(batch.created_uuids(), [Uuid::from_u128(101)]);
+    assert!(matches!(batch.items[1].result, Err(HostBatchError::Api(_))));
+    created.assert_async().await;
+
+    client.hosts.rollback(&batch).await.unwrap();
+    rollback.assert_async().await;
+}
+
+#[tokio::test]
+async fn fans_out_a_template_across_inbounds_and_deletes_the_group() {
+    let mut server = mockito::Server::new_async().await;
+    for id in [11, 12] {
+        server
+            .mock("POST", "/api/hosts")
+            .match_body(mockito::Matcher::PartialJson(json!({ "inbound": { "configProfileInboundUuid": Uuid::from_u128(id) }, "tag": "fan-out" })))
+            .with_status(201)
+            .with_body(json!({ "response": host_json(Uuid::from_u128(id + 100), "NL", Some(Uuid::from_u128(id)), Some("fan-out")) }).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+    }
+    let hosts = [
+        host_json(SOURCE, "DE", Some(INBOUND), Some("EU")),
+        host_json(Uuid::from_u128(111), "NL", Some(Uuid::from_u128(11)), Some("fan-out")),
+        host_json(Uuid::from_u128(112), "NL", Some(Uuid::from_u128(12)), Some("fan-out")),
+    ];
+    server.mock("GET", "/api/hosts").with_body(json!({ "response": hosts }).to_string()).create_async().await;
+    let delete = server
+        .mock("POST", "/api/hosts/bulk/delete")
+        .match_body(mockito::Matcher::Json(json!({ "uuids": [Uuid::from_u128(111), Uuid::from_u128(112)] })))
+        .with_body(json!({ "response": [] }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let template = CreateHostRequestDto::builder(inbound(INBOUND), "NL", "nl.example.com", 443).fingerprint(FingerprintType::CHROME).build().unwrap();
+    let batch = client.hosts.fan_out(&template, [inbound(Uuid::from_u128(11)), inbound(Uuid::from_u128(12))], "fan-out").await;
+    assert!(batch.is_complete());
+    assert_eq!(batch.items[1].request.as_ref().map(|r| r.inbound.config_profile_inbound_uuid), Some(Uuid::from_u128(12)));
+
+    client.hosts.delete_group("fan-out").await.unwrap();
+    delete.assert_async().await;
+
+    let orphan = serde_json::from_value(host_json(SOURCE, "DE", None, None)).unwrap();
+    assert!(matches!(HostOverrides::default().apply(&orphan), Err(HostBatchError::MissingInbound(SOURCE))));
+}
+
+#[test]
+fn keeps_wrong_typed_params_raw_and_refuses_to_clone_them() {
+    let mut raw = host_json(SOURCE, "DE", Some(INBOUND), None);
+    raw["sockoptParams"] = json!({ "mark": "255" });
+    raw["muxParams"] = json!({ "enabled": true, "concurrency": 70000 });
+    let host: HostDto = serde_json::from_value(raw).unwrap();
+    assert_eq!(host.sockopt_params, Some(json!({ "mark": "255" })));
+    assert!(host.sockopt().is_err() && host.mux().is_err());
+    assert!(matches!(HostOverrides::default().apply(&host), Err(HostBatchError::InvalidParams(SOURCE, _))));
+
+    let host: HostDto = serde_json::from_value(host_json(SOURCE, "DE", Some(INBOUND), None)).unwrap();
+    assert_eq!(host.mux().unwrap().map(|mux: MuxParams| mux.concurrency), Some(Some(8)));
+    assert_eq!(HostOverrides::default().apply(&host).unwrap().mux_params.and_then(|mux| mux.concurrency), Some(8));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use remnawave::tools::HostBuildError;
+use remnawave::types::{AlpnType, CreateHostRequestDto, FingerprintType, HostInboundRequest, Inbound, SecurityLayerType};
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const PROFILE: Uuid = Uuid::from_u128(1);
+const REALITY: Uuid = Uuid::from_u128(10);
+const TLS_XHTTP: Uuid = Uuid::from_u128(11);
+
+fn inbound_json(uuid: Uuid, tag: &str, network: &str, security: &str) -> Value {
+    json!({
+        "uuid": uuid,
+        "profileUuid": PROFILE,
+        "tag": tag,
+        "type": "vless",
+        "network": network,
+        "security": security,
+        "port": 443,
+        "rawInbound": null
+    })
+}
+
+fn inbound(uuid: Uuid, network: &str, security: &str) -> Inbound {
+    serde_json::from_value(inbound_json(uuid, "VLESS", network, security)).unwrap()
+}
+
+fn reference(inbound: Uuid) -> HostInboundRequest {
+    HostInboundRequest {
+        config_profile_uuid: PROFILE,
+        config_profile_inbound_uuid: inbound,
+    }
+}
+
+fn created_host() -> Value {
+    common::host_json(
+        100,
+        json!({
+            "remark": "DE",
+            "address": "de.example.com",
+            "fingerprint": "chrome",
+            "inbound": { "configProfileUuid": PROFILE, "configProfileInboundUuid": REALITY }
+        }),
+    )
+}
+
+fn codes(diagnostics: &[remnawave::tools::Diagnostic]) -> Vec<&'static str> {
+    diagnostics.iter().map(|d| d.code).collect()
+}
+
+#[test]
+fn parses_host_enums() {
+    assert_eq!("h2,http/1.1".parse::<AlpnType>().unwrap(), AlpnType::H_COMBINED);
+    assert_eq!("chrome".parse::<FingerprintType>().unwrap(), FingerprintType::CHROME);
+    assert_eq!("TLS".parse::<SecurityLayerType>().unwrap(), SecurityLayerType::TLS);
+    let err = "h4".parse::<AlpnType>().unwrap_err();
+    assert_eq!(err.to_string(), "Invalid AlpnType: h4");
+}
+
+#[test]
+fn validates_settings_against_the_inbound() {
+    let reality = inbound(REALITY, "tcp", "reality");
+    let builder = CreateHostRequestDto::builder(reference(REALITY), "DE", "1.2.3.4", 443)
+        .security_layer(SecurityLayerType::TLS)
+        .alpn(AlpnType::H2)
+        .sni("1.2.3.4")
+        .override_sni_from_address(true)
+        .fingerprint(FingerprintType::CHROME);
+    assert_eq!(codes(&builder.validate(Some(&reality))), ["reality-with-tls", "alpn-with-reality", "sni-overridden", "ip-sni", "ip-sni"]);
+    assert!(matches!(builder.clone().build_for(Some(&reality)), Err(HostBuildError::Invalid(_))));
+
+    let xhttp = inbound(TLS_XHTTP, "xhttp", "tls");
+    let host = CreateHostRequestDto::builder(reference(TLS_XHTTP), "NL", "nl.example.com", 443).alpn(AlpnType::H3).allow_insecure(true).tag("EU").build_for(Some(&xhttp)).unwrap();
+    assert_eq!((host.alpn, host.allow_insecure, host.tag.as_deref()), (Some(AlpnType::H3), true, Some("EU")));
+
+    let plain = inbound(TLS_XHTTP, "ws", "none");
+    let builder = CreateHostRequestDto::builder(reference(TLS_XHTTP), "FI", "fi.example.com", 80).sni("fi.example.com").alpn(AlpnType::H3);
+    assert_eq!(codes(&builder.validate(Some(&plain))), ["tls-setting-without-tls", "tls-setting-without-tls", "h3-without-xhttp"]);
+    assert_eq!(codes(&builder.validate(Some(&reality))), ["inbound-mismatch", "alpn-with-reality", "h3-without-xhttp"]);
+
+    let empty = CreateHostRequestDto::builder(reference(REALITY), " ", "", 0).build().unwrap_err();
+    assert_eq!(empty.to_string(), "Invalid host; error[empty-remark] /remark: remark must not be empty; error[empty-address] /address: address must not be empty; error[invalid-port] /port: port must be between 1 and 65535");
+}
+
+#[tokio::test]
+async fn create_host_checks_the_inbound_exists() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", format!("/api/config-profiles/{PROFILE}/inbounds").as_str())
+        .with_body(json!({ "response": { "total": 1, "inbounds": [inbound_json(REALITY, "VLESS_REALITY", "tcp", "reality")] } }).to_string())
+        .expect(3)
+        .create_async()
+        .await;
+    let create = server
+        .mock("POST", "/api/hosts")
+        .match_body(mockito::Matcher::PartialJson(json!({ "remark": "DE", "fingerprint": "chrome" })))
+        .with_status(201)
+        .with_body(json!({ "response": created_host() }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let Err(HostBuildError::Invalid(diagnostics)) = client.create_host(CreateHostRequestDto::builder(reference(TLS_XHTTP), "DE", "de.example.com", 443)).await else {
+        panic!("expected validation failure");
+    };
+    assert_eq!(codes(&diagnostics), ["unknown-inbound"]);
+    assert_eq!(diagnostics[0].pointer, "/inbound/configProfileInboundUuid");
+
+    let builder = CreateHostRequestDto::builder(reference(REALITY), "DE", "de.example.com", 443).fingerprint(FingerprintType::CHROME).security_layer(SecurityLayerType::TLS);
+    assert!(matches!(client.create_host(builder.clone()).await, Err(HostBuildError::Invalid(_))));
+
+    let created = client.create_host(builder.security_layer(SecurityLayerType::DEFAULT)).await.unwrap();
+    assert_eq!(created.response.fingerprint_type(), Some(FingerprintType::CHROME));
+    create.assert_async().await;
+}
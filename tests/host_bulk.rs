@@ -0,0 +1,58 @@
+mod common;
+
+use remnawave::tools::{HostBulkAction, HostFilter};
+use remnawave::types::HostInboundRequest;
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn host_json(id: u128, port: u16, sni: &str, tag: Option<&str>) -> Value {
+    common::host_json(
+        id,
+        json!({
+            "viewPosition": id,
+            "port": port,
+            "sni": sni,
+            "inbound": { "configProfileUuid": Uuid::from_u128(1), "configProfileInboundUuid": Uuid::from_u128(10) },
+            "tag": tag
+        }),
+    )
+}
+
+#[tokio::test]
+async fn resolves_hosts_by_tag_and_filter() {
+    let mut server = mockito::Server::new_async().await;
+    let hosts = [host_json(1, 443, "a.example.com", Some("EU-BACKUP")), host_json(2, 443, "b.example.com", Some("EU-BACKUP")), host_json(3, 8443, "a.example.com", None)];
+    server.mock("GET", "/api/hosts").with_body(json!({ "response": hosts }).to_string()).create_async().await;
+    let disable = server
+        .mock("POST", "/api/hosts/bulk/disable")
+        .match_body(mockito::Matcher::Json(json!({ "uuids": [Uuid::from_u128(1), Uuid::from_u128(2)] })))
+        .with_body(json!({ "response": [] }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+    let set_inbound = server
+        .mock("POST", "/api/hosts/bulk/set-inbound")
+        .match_body(mockito::Matcher::Json(json!({ "uuids": [Uuid::from_u128(1)], "configProfileUuid": Uuid::from_u128(1), "configProfileInboundUuid": Uuid::from_u128(11) })))
+        .with_body(json!({ "response": [host_json(1, 443, "a.example.com", Some("EU-BACKUP"))] }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let dry = client.hosts.bulk_by_tag("EU-BACKUP", HostBulkAction::Disable, true).await.unwrap();
+    assert_eq!((dry.matched_uuids(), dry.applied), (vec![Uuid::from_u128(1), Uuid::from_u128(2)], None));
+    client.hosts.bulk_by_tag("EU-BACKUP", HostBulkAction::Disable, false).await.unwrap();
+    disable.assert_async().await;
+
+    let inbound = HostInboundRequest {
+        config_profile_uuid: Uuid::from_u128(1),
+        config_profile_inbound_uuid: Uuid::from_u128(11),
+    };
+    let moved = client.hosts.bulk_filtered(&HostFilter::default().port(443).sni("a.example.com"), HostBulkAction::SetInbound(inbound), false).await.unwrap();
+    assert_eq!(moved.applied.map(|hosts| hosts.len()), Some(1));
+    set_inbound.assert_async().await;
+
+    let none = client.hosts.bulk_where(|host| host.port == 80, HostBulkAction::Delete, false).await.unwrap();
+    assert!(none.matched.is_empty() && none.applied.is_none());
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use remnawave::tools::NodeBundleOptions;
+use remnawave::types::{ConfigProfileRequest, CreateNodeRequestDto};
+use remnawave::RemnawaveApiClient;
+use serde_json::json;
+use uuid::Uuid;
+
+fn created_node() -> serde_json::Value {
+    common::node_json(
+        7,
+        json!({
+            "name": "DE-1",
+            "address": "de1.example.com",
+            "isConnected": false,
+            "isNodeOnline": false,
+            "isXrayRunning": false,
+            "viewPosition": 5,
+            "configProfile": { "activeConfigProfileUuid": Uuid::from_u128(1), "activeInbounds": [] }
+        }),
+    )
+}
+
+#[tokio::test]
+async fn deploy_node_creates_node_and_renders_bundle() {
+    let mut server = mockito::Server::new_async().await;
+    let keygen = server.mock("GET", "/api/keygen").with_body(r#"{"response":{"pubKey":"eyJub2RlQ2VydFBlbSI6Ii4uLiJ9"}}"#).expect(1).create_async().await;
+    let create = server
+        .mock("POST", "/api/nodes")
+        .match_body(mockito::Matcher::PartialJson(json!({ "name": "DE-1", "address": "de1.example.com", "port": 2222 })))
+        .with_status(201)
+        .with_body(json!({ "response": created_node() }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let request = CreateNodeRequestDto {
+        name: "DE-1".to_string(),
+        address: "de1.example.com".to_string(),
+        port: 2222,
+        is_traffic_tracking_active: false,
+        traffic_limit_bytes: 0,
+        notify_percent: 0,
+        traffic_reset_day: 1,
+        country_code: "DE".parse().unwrap(),
+        consumption_multiplier: 1.0,
+        config_profile: ConfigProfileRequest {
+            active_config_profile_uuid: Uuid::from_u128(1),
+            active_inbounds: vec![],
+        },
+        provider_uuid: None,
+    };
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let options = NodeBundleOptions::default().systemd(true).install_dir("/srv/remnanode");
+    let bundle = client.deploy_node(request, &options).await.unwrap();
+
+    keygen.assert_async().await;
+    create.assert_async().await;
+    assert_eq!(bundle.node.uuid, Uuid::from_u128(7));
+    assert_eq!(bundle.files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), ["docker-compose.yml", ".env", "remnanode.service"]);
+
+    let env = bundle.file(".env").unwrap();
+    assert_eq!(env.contents, "NODE_PORT=2222\nSECRET_KEY=eyJub2RlQ2VydFBlbSI6Ii4uLiJ9\n");
+    assert_eq!(env.mode, 0o600);
+    let compose = &bundle.file("docker-compose.yml").unwrap().contents;
+    assert!(compose.contains("image: remnawave/node:latest") && compose.contains("network_mode: host") && compose.contains("- .env"));
+    assert!(bundle.file("remnanode.service").unwrap().contents.contains("WorkingDirectory=/srv/remnanode\n"));
+
+    let dir = std::env::temp_dir().join(format!("remnanode-bundle-{}", Uuid::new_v4()));
+    bundle.write_to(&dir).unwrap();
+    assert_eq!(std::fs::read_to_string(dir.join(".env")).unwrap(), env.contents);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(dir.join(".env")).unwrap().permissions().mode() & 0o777, 0o600);
+        std::fs::set_permissions(dir.join(".env"), std::fs::Permissions::from_mode(0o644)).unwrap();
+        bundle.write_to(&dir).unwrap();
+        assert_eq!(std::fs::metadata(dir.join(".env")).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn deploy_node_creates_nothing_when_keygen_fails() {
+    let mut server = mockito::Server::new_async().await;
+    server.mock("GET", "/api/keygen").with_status(500).with_body(r#"{"message":"boom"}"#).create_async().await;
+    let create = server.mock("POST", "/api/nodes").expect(0).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let request: CreateNodeRequestDto = serde_json::from_value(json!({
+        "name": "DE-1", "address": "de1.example.com", "port": 2222, "isTrafficTrackingActive": false, "trafficLimitBytes": 0,
+        "notifyPercent": 0, "trafficResetDay": 1, "consumptionMultiplier": 1.0,
+        "configProfile": { "activeConfigProfileUuid": Uuid::from_u128(1), "activeInbounds": [] }, "providerUuid": null
+    }))
+    .unwrap();
+    assert_eq!(client.deploy_node(request, &NodeBundleOptions::default()).await.unwrap_err().status_code, 500);
+    create.assert_async().await;
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use futures_util::{pin_mut, StreamExt};
+use remnawave::tools::{NodeEventKind, NodeWatchField, NodeWatchOptions, NodeWatcher};
+use remnawave::types::NodeDto;
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+fn node_json(uuid: u128, online: bool, used: usize) -> Value {
+    common::node_json(
+        uuid,
+        json!({
+            "isConnected": online,
+            "isNodeOnline": online,
+            "xrayVersion": "25.1.1",
+            "nodeVersion": "2.2.0",
+            "isTrafficTrackingActive": true,
+            "trafficResetDay": 1,
+            "trafficLimitBytes": 1000,
+            "trafficUsedBytes": used,
+            "notifyPercent": 80
+        }),
+    )
+}
+
+fn node(uuid: u128, online: bool, used: usize) -> NodeDto {
+    serde_json::from_value(node_json(uuid, online, used)).unwrap()
+}
+
+#[test]
+fn debounces_changes_and_reports_traffic_and_membership() {
+    let mut watcher = NodeWatcher::new(NodeWatchOptions::default().debounce_polls(2));
+    let start = Instant::now();
+    let at = |secs: u64| start + Duration::from_secs(secs);
+
+    assert!(watcher.observe(&[node(1, true, 100)], at(0)).is_empty());
+    // A single offline poll is noise.
+    assert!(watcher.observe(&[node(1, false, 100)], at(30)).is_empty());
+    assert!(watcher.observe(&[node(1, true, 100)], at(60)).is_empty());
+
+    assert!(watcher.observe(&[node(1, false, 100)], at(90)).is_empty());
+    let kinds: Vec<NodeEventKind> = watcher.observe(&[node(1, false, 850), node(2, true, 0)], at(120)).into_iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            NodeEventKind::StatusChanged {
+                field: NodeWatchField::IsConnected,
+                value: false
+            },
+            NodeEventKind::StatusChanged {
+                field: NodeWatchField::IsNodeOnline,
+                value: false
+            },
+            NodeEventKind::TrafficThresholdCrossed {
+                notify_percent: 80,
+                used_bytes: 850,
+                limit_bytes: 1000
+            },
+            NodeEventKind::Added,
+        ]
+    );
+
+    // Still over the threshold: no repeat.
+    assert!(watcher.observe(&[node(1, false, 900), node(2, true, 0)], at(150)).is_empty());
+
+    let mut updated = node(1, false, 900);
+    updated.xray_version = Some("25.2.0".to_string());
+    assert!(watcher.observe(&[updated.clone()], at(180)).iter().all(|e| e.kind == NodeEventKind::Removed && e.node_name == "node-2"));
+    let events = watcher.observe(&[updated], at(210));
+    assert_eq!(
+        events[0].kind,
+        NodeEventKind::XrayVersionChanged {
+            from: Some("25.1.1".to_string()),
+            to: Some("25.2.0".to_string())
+        }
+    );
+    assert_eq!(events[0].node_name, "node-1");
+}
+
+#[test]
+fn suppresses_flapping_fields() {
+    let mut watcher = NodeWatcher::new(NodeWatchOptions::default().debounce_polls(1).flap_suppression(Duration::from_secs(100), 2));
+    let start = Instant::now();
+    let mut poll = |secs: u64, online: bool| -> Vec<NodeEventKind> {
+        let events = watcher.observe(&[node(1, online, 0)], start + Duration::from_secs(secs));
+        // `isNodeOnline` flips together with `isConnected`; only look at the latter.
+        let connected = |kind: &NodeEventKind| match kind {
+            NodeEventKind::StatusChanged {
+                field,
+                ..
+            }
+            | NodeEventKind::Flapping {
+                field,
+            }
+            | NodeEventKind::Stabilized {
+                field,
+            } => *field == NodeWatchField::IsConnected,
+            _ => true,
+        };
+        events.into_iter().map(|e| e.kind).filter(connected).collect()
+    };
+    let changed = |value: bool| NodeEventKind::StatusChanged {
+        field: NodeWatchField::IsConnected,
+        value,
+    };
+
+    assert!(poll(0, true).is_empty());
+    assert_eq!(poll(10, false), [changed(false)]);
+    assert_eq!(poll(20, true), [changed(true)]);
+    assert_eq!(
+        poll(30, false),
+        [NodeEventKind::Flapping {
+            field: NodeWatchField::IsConnected
+        }]
+    );
+    assert!(poll(40, true).is_empty());
+    assert!(poll(50, false).is_empty());
+    assert!(poll(100, false).is_empty());
+    // Last flip at 50s; stable for the whole window since. Consumers last heard "online".
+    assert_eq!(
+        poll(151, false),
+        [
+            NodeEventKind::Stabilized {
+                field: NodeWatchField::IsConnected
+            },
+            changed(false)
+        ]
+    );
+    assert!(poll(160, false).is_empty());
+}
+
+#[tokio::test]
+async fn watch_streams_errors_and_events() {
+    let mut server = mockito::Server::new_async().await;
+    let body = |online: bool| json!({ "response": [node_json(1, online, 0)] }).to_string();
+    let failing = server.mock("GET", "/api/nodes").with_status(500).with_body(r#"{"message":"boom"}"#).expect(1).create_async().await;
+    let online = server.mock("GET", "/api/nodes").with_body(body(true)).expect(1).create_async().await;
+    let offline = server.mock("GET", "/api/nodes").with_body(body(false)).expect_at_least(2).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let stream = client.nodes.watch(Duration::from_millis(5));
+    pin_mut!(stream);
+
+    assert_eq!(stream.next().await.unwrap().unwrap_err().status_code, 500);
+    let event = stream.next().await.unwrap().unwrap();
+    assert_eq!(event.node_uuid, Uuid::from_u128(1));
+    assert_eq!(
+        event.kind,
+        NodeEventKind::StatusChanged {
+            field: NodeWatchField::IsConnected,
+            value: false
+        }
+    );
+
+    failing.assert_async().await;
+    online.assert_async().await;
+    offline.assert_async().await;
+}
+
+#[tokio::test]
+async fn zero_interval_polls_instead_of_panicking() {
+    let mut server = mockito::Server::new_async().await;
+    server.mock("GET", "/api/nodes").with_status(500).with_body(r#"{"message":"boom"}"#).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let stream = client.nodes.watch(Duration::ZERO);
+    pin_mut!(stream);
+
+    assert_eq!(stream.next().await.unwrap().unwrap_err().status_code, 500);
+    assert_eq!(stream.next().await.unwrap().unwrap_err().status_code, 500);
+}
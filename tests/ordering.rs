@@ -0,0 +1,71 @@
+mod common;
+
+use remnawave::tools::{OrderingError, ViewOrder};
+use remnawave::types::HostDto;
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn host_json(id: u128, position: i32, tag: Option<&str>) -> Value {
+    common::host_json(id, json!({ "viewPosition": position, "tag": tag }))
+}
+
+fn node_json(id: u128, position: u8, country: &str) -> Value {
+    common::node_json(id, json!({ "viewPosition": position, "countryCode": country }))
+}
+
+fn hosts(rows: &[(u128, i32, Option<&str>)]) -> Vec<HostDto> {
+    rows.iter().map(|&(id, position, tag)| serde_json::from_value(host_json(id, position, tag)).unwrap()).collect()
+}
+
+fn ids(order: &ViewOrder<HostDto>) -> Vec<u128> {
+    order.items().iter().map(|h| h.uuid.as_u128()).collect()
+}
+
+#[test]
+fn reorders_onto_existing_positions() {
+    let mut order = ViewOrder::new(hosts(&[(3, 30, Some("b")), (1, 10, Some("a")), (2, 20, None), (4, 40, Some("a")), (5, 50, Some("b"))]));
+    assert_eq!(ids(&order), [1, 2, 3, 4, 5]);
+
+    order.group_by_tag();
+    assert_eq!(ids(&order), [1, 4, 2, 3, 5]);
+    assert_eq!(order.changed().iter().map(Uuid::as_u128).collect::<Vec<_>>(), [4, 2, 3]);
+    let request = order.to_request().unwrap();
+    assert_eq!(request.hosts.iter().map(|h| (h.uuid.as_u128(), h.view_position)).collect::<Vec<_>>(), [(1, 10), (4, 20), (2, 30), (3, 40), (5, 50)]);
+
+    order.move_before(Uuid::from_u128(5), Uuid::from_u128(1)).unwrap().move_to(Uuid::from_u128(2), 99).unwrap();
+    assert_eq!(ids(&order), [5, 1, 4, 3, 2]);
+    assert!(matches!(order.move_to(Uuid::from_u128(9), 0), Err(OrderingError::UnknownUuid(_))));
+
+    order.sort_by(|h| h.remark.clone());
+    assert!(order.changed().is_empty());
+
+    // Duplicate positions are renumbered from the lowest one.
+    let order = ViewOrder::new(hosts(&[(1, 1, None), (2, 1, None), (3, 2, None)]));
+    assert_eq!(order.positions().iter().map(|(_, p)| *p).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(order.changed().iter().map(Uuid::as_u128).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[tokio::test]
+async fn submits_only_when_positions_change() {
+    let mut server = mockito::Server::new_async().await;
+    let nodes = json!({ "response": [node_json(1, 0, "NL"), node_json(2, 1, "DE"), node_json(3, 2, "FI")] }).to_string();
+    server.mock("GET", "/api/nodes").with_body(&nodes).expect(3).create_async().await;
+    let reorder = server
+        .mock("POST", "/api/nodes/actions/reorder")
+        .match_body(mockito::Matcher::Json(json!({ "nodes": [
+            { "viewPosition": 0, "uuid": Uuid::from_u128(2) },
+            { "viewPosition": 1, "uuid": Uuid::from_u128(3) },
+            { "viewPosition": 2, "uuid": Uuid::from_u128(1) }
+        ] })))
+        .with_body(&nodes)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    assert!(client.nodes.sort_by(|node| node.country_code).await.unwrap());
+    assert!(client.nodes.move_to(Uuid::from_u128(1), 2).await.unwrap());
+    assert!(!client.nodes.move_before(Uuid::from_u128(1), Uuid::from_u128(2)).await.unwrap());
+    reorder.assert_async().await;
+}
@@ -0,0 +1,54 @@
+use remnawave::types::{encode_outline_tag, outline_ssconf_url, OutlineKey, OutlineSubscriptionType};
+use remnawave::RemnawaveApiClient;
+
+#[tokio::test]
+async fn get_with_type_returns_outline_key() {
+    let mut server = mockito::Server::new_async().await;
+    let tag = encode_outline_tag("SS-DE");
+    let mock = server
+        .mock("GET", format!("/api/sub/outline/abc123/ss/{tag}").as_str())
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"server":"de.example.com","server_port":8388,"password":"secret","method":"chacha20-ietf-poly1305","prefix":"\u0016\u0003\u0001"}"#)
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let key = client.subscriptions.get_with_type("abc123".to_string(), tag, OutlineSubscriptionType::Ss).await.unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(
+        key,
+        OutlineKey {
+            server: "de.example.com".to_string(),
+            server_port: 8388,
+            password: "secret".to_string(),
+            method: "chacha20-ietf-poly1305".to_string(),
+            prefix: Some("\u{16}\u{3}\u{1}".to_string()),
+        }
+    );
+}
+
+#[test]
+fn ssconf_urls() {
+    assert_eq!(encode_outline_tag("SS-DE"), "U1MtREU");
+    assert_eq!(
+        outline_ssconf_url("https://panel.example.com/", "abc123", OutlineSubscriptionType::Ss, "SS DE"),
+        "ssconf://panel.example.com/api/sub/outline/abc123/ss/U1MgREU#SS%20DE"
+    );
+
+    let client = RemnawaveApiClient::new("http://127.0.0.1:3000".to_string(), None).unwrap();
+    assert_eq!(client.subscriptions.outline_ssconf_url("abc123", "SS-DE", OutlineSubscriptionType::default()), "ssconf://127.0.0.1:3000/api/sub/outline/abc123/ss/U1MtREU#SS-DE");
+}
+
+#[test]
+fn static_ss_url() {
+    let key = OutlineKey {
+        server: "2001:db8::1".to_string(),
+        server_port: 443,
+        password: "pw".to_string(),
+        method: "aes-256-gcm".to_string(),
+        prefix: Some("POST ".to_string()),
+    };
+    assert_eq!(key.to_ss_url(Some("My key")), "ss://YWVzLTI1Ni1nY206cHc@[2001:db8::1]:443/?outline=1&prefix=POST%20#My%20key");
+}
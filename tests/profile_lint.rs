@@ -0,0 +1,95 @@
+mod common;
+
+use remnawave::tools::{lint_xray_config, Diagnostic, ProfileLintError};
+use remnawave::types::{UpdateConfigProfileRequestDto, XrayConfig};
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const PROFILE: Uuid = Uuid::from_u128(1);
+const OTHER_PROFILE: Uuid = Uuid::from_u128(2);
+
+fn config(value: Value) -> XrayConfig {
+    serde_json::from_value(value).unwrap()
+}
+
+fn codes(diagnostics: &[Diagnostic]) -> Vec<(&'static str, &str)> {
+    diagnostics.iter().map(|d| (d.code, d.pointer.as_str())).collect()
+}
+
+fn panel_inbound(uuid: u128, profile: Uuid, tag: &str, port: u16) -> Value {
+    json!({ "uuid": Uuid::from_u128(uuid), "profileUuid": profile, "tag": tag, "type": "vless", "network": "raw", "security": "reality", "port": port, "rawInbound": null })
+}
+
+#[test]
+fn lints_tags_ports_reality_and_routing() {
+    let config = config(json!({
+        "inbounds": [
+            { "tag": "VLESS", "port": 443, "protocol": "vless", "settings": { "clients": [] }, "streamSettings": { "security": "reality", "realitySettings": { "privateKey": "", "serverNames": ["a.com"] } } },
+            { "tag": "VLESS", "port": "400-500", "listen": "127.0.0.1", "protocol": "vless", "settings": { "clients": [] } },
+            { "tag": "SS", "port": 8443, "listen": "10.0.0.1", "protocol": "shadowsocks", "settings": {} },
+            { "tag": "SS2", "port": 8443, "listen": "10.0.0.2", "protocol": "shadowsocks", "settings": {} },
+            { "tag": "BROKEN", "port": 9443, "protocol": "vless", "settings": { "clients": [] }, "streamSettings": { "security": "reality" } }
+        ],
+        "outbounds": [{ "tag": "DIRECT", "protocol": "freedom" }],
+        "routing": { "rules": [
+            { "outboundTag": "DIRECT", "inboundTag": ["VLESS"] },
+            { "outboundTag": "BLOCK", "inboundTag": ["VLESS", "GONE"] },
+            { "balancerTag": "auto" }
+        ] }
+    }));
+    assert_eq!(
+        codes(&lint_xray_config(&config)),
+        [
+            ("duplicate-inbound-tag", "/inbounds/1/tag"),
+            ("port-collision", "/inbounds/1/port"),
+            ("reality-missing-private-key", "/inbounds/0/streamSettings/realitySettings/privateKey"),
+            ("reality-missing-short-ids", "/inbounds/0/streamSettings/realitySettings/shortIds"),
+            ("reality-missing-settings", "/inbounds/4/streamSettings/realitySettings"),
+            ("unknown-outbound-tag", "/routing/rules/1/outboundTag"),
+            ("unknown-inbound-tag", "/routing/rules/1/inboundTag/1"),
+            ("unknown-balancer-tag", "/routing/rules/2/balancerTag"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn lints_against_the_panel_before_updating() {
+    let mut server = mockito::Server::new_async().await;
+    let inbounds = [panel_inbound(10, PROFILE, "USED", 443), panel_inbound(11, PROFILE, "UNUSED", 8443), panel_inbound(20, OTHER_PROFILE, "ELSEWHERE", 8443)];
+    server.mock("GET", "/api/config-profiles/inbounds").with_body(json!({ "response": { "total": 3, "inbounds": inbounds } }).to_string()).expect(2).create_async().await;
+    let host = common::host_json(
+        100,
+        json!({ "remark": "DE", "address": "de.example.com", "inbound": { "configProfileUuid": PROFILE, "configProfileInboundUuid": Uuid::from_u128(10) } }),
+    );
+    server.mock("GET", "/api/hosts").with_body(json!({ "response": [host] }).to_string()).expect(2).create_async().await;
+    let squad = json!({
+        "uuid": Uuid::from_u128(200), "name": "Default", "info": { "membersCount": 1, "inboundsCount": 1 },
+        "inbounds": [panel_inbound(10, PROFILE, "USED", 443)], "createdAt": "2025-01-01T00:00:00Z", "updatedAt": "2025-01-01T00:00:00Z"
+    });
+    server.mock("GET", "/api/internal-squads").with_body(json!({ "response": { "total": 1, "internalSquads": [squad] } }).to_string()).expect(2).create_async().await;
+    let update = server.mock("PATCH", "/api/config-profiles").expect(0).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let valid = config(json!({ "inbounds": [
+        { "tag": "USED", "port": 443, "protocol": "vless", "settings": { "clients": [] } },
+        { "tag": "UNUSED", "port": 8443, "protocol": "vless", "settings": { "clients": [] } }
+    ] }));
+    let diagnostics = client.lint_config_profile(PROFILE, &valid).await.unwrap();
+    assert_eq!(
+        codes(&diagnostics),
+        [("port-used-by-other-profile", "/inbounds/1/port"), ("inbound-without-host", "/inbounds/1/tag"), ("inbound-without-squad", "/inbounds/1/tag")]
+    );
+
+    let mut invalid = valid.clone();
+    invalid.inbounds[1].tag = Some("USED".into());
+    let request = UpdateConfigProfileRequestDto {
+        uuid: PROFILE,
+        config: invalid,
+    };
+    let Err(ProfileLintError::Invalid(diagnostics)) = client.update_config_profile_checked(request).await else {
+        panic!("expected lint failure");
+    };
+    assert!(diagnostics.iter().any(|d| d.code == "duplicate-inbound-tag"));
+    update.assert_async().await;
+}
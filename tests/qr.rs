@@ -0,0 +1,55 @@
+#![cfg(feature = "qr")]
+
+use remnawave::tools::{QrCode, QrError, QrErrorCorrection, QrOptions, MAX_PNG_SIDE};
+
+const URL: &str = "https://sub.example.com/api/sub/3fJk9aQx2LmN";
+
+#[test]
+fn png_has_requested_minimum_size() {
+    let code = QrCode::with_options(URL, QrOptions::default().size(300)).unwrap();
+    let png = code.to_png().unwrap();
+
+    let decoder = png::Decoder::new(png.as_slice());
+    let reader = decoder.read_info().unwrap();
+    let info = reader.info();
+    assert_eq!(info.width, info.height);
+    assert!(info.width >= 300);
+    assert_eq!(info.width % (code.width() as u32 + 8), 0);
+}
+
+#[test]
+fn svg_and_terminal_output() {
+    let code = QrCode::new(URL).unwrap();
+    assert!(code.to_svg().starts_with("<?xml"));
+
+    let terminal = code.to_terminal();
+    let rows = terminal.lines().count();
+    assert_eq!(rows, (code.width() + 8).div_ceil(2));
+
+    let without_border = QrCode::with_options(URL, QrOptions::default().quiet_zone(false)).unwrap().to_terminal();
+    assert_eq!(without_border.lines().count(), code.width().div_ceil(2));
+}
+
+#[test]
+fn error_correction_affects_code_size() {
+    let low = QrCode::with_options(URL, QrOptions::default().error_correction(QrErrorCorrection::Low)).unwrap();
+    let high = QrCode::with_options(URL, QrOptions::default().error_correction(QrErrorCorrection::High)).unwrap();
+    assert!(low.width() < high.width());
+}
+
+#[test]
+fn oversized_data_is_rejected() {
+    let data = "x".repeat(4000);
+    let result = QrCode::with_options(&data, QrOptions::default().error_correction(QrErrorCorrection::High));
+    assert!(matches!(result, Err(QrError::Encode(_))));
+}
+
+#[test]
+fn oversized_png_is_rejected() {
+    let code = QrCode::with_options(URL, QrOptions::default().size(u32::MAX)).unwrap();
+    assert!(matches!(code.to_png(), Err(QrError::TooLarge(u32::MAX))));
+    let code = QrCode::with_options(URL, QrOptions::default().size(60_000)).unwrap();
+    assert!(matches!(code.to_png(), Err(QrError::TooLarge(60_000))));
+    let code = QrCode::with_options(URL, QrOptions::default().size(MAX_PNG_SIDE + 1)).unwrap();
+    assert!(matches!(code.to_png(), Err(QrError::TooLarge(size)) if size == MAX_PNG_SIDE + 1));
+}
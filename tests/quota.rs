@@ -0,0 +1,151 @@
+mod common;
+
+use chrono::NaiveDate;
+use remnawave::tools::{forecast_node_quotas, next_reset_date, QuotaForecastOptions, QuotaStatus};
+use remnawave::types::{NodeDto, NodesUsageData};
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const GB: usize = 1_000_000_000;
+
+fn node_json(id: u128, limit: Option<usize>, used: usize, reset_day: Option<i32>, multiplier: f32) -> Value {
+    common::node_json(
+        id,
+        json!({
+            "isTrafficTrackingActive": limit.is_some(),
+            "trafficResetDay": reset_day,
+            "trafficLimitBytes": limit,
+            "trafficUsedBytes": used,
+            "consumptionMultiplier": multiplier
+        }),
+    )
+}
+
+fn usage_json(id: u128, date: &str, total: usize) -> Value {
+    json!({
+        "nodeUuid": Uuid::from_u128(id),
+        "nodeName": format!("node-{id}"),
+        "nodeCountryCode": "DE",
+        "total": total,
+        "totalDownload": total,
+        "totalUpload": 0,
+        "humanReadableTotal": "",
+        "humanReadableTotalDownload": "",
+        "humanReadableTotalUpload": "0 B",
+        "date": date
+    })
+}
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn reset_dates_clamp_to_month_end() {
+    assert_eq!(next_reset_date(date("2025-01-10"), 15), date("2025-01-15"));
+    assert_eq!(next_reset_date(date("2025-01-15"), 15), date("2025-02-15"));
+    assert_eq!(next_reset_date(date("2025-01-31"), 31), date("2025-02-28"));
+    assert_eq!(next_reset_date(date("2024-12-20"), 1), date("2025-01-01"));
+}
+
+#[test]
+fn projects_usage_to_next_reset() {
+    let nodes: Vec<NodeDto> = [
+        node_json(1, Some(800 * GB), 500 * GB, Some(1), 1.0),
+        node_json(2, Some(1000 * GB), 500 * GB, Some(1), 2.0),
+        node_json(3, Some(100 * GB), 120 * GB, Some(1), 1.0),
+        node_json(4, None, 900 * GB, None, 1.0),
+        node_json(5, Some(560 * GB), 100 * GB, Some(1), 1.0),
+    ]
+    .into_iter()
+    .map(|n| serde_json::from_value(n).unwrap())
+    .collect();
+    // Two days of history; node 1 averages 30 GB/day, node 2 only 5 GB/day. Rows outside the window are ignored.
+    let usage: Vec<NodesUsageData> = [
+        usage_json(1, "2025-03-19", 20 * GB),
+        usage_json(1, "2025-03-20T00:00:00.000Z", 40 * GB),
+        usage_json(1, "2025-01-01", 900 * GB),
+        usage_json(2, "2025-03-19", 5 * GB),
+        usage_json(2, "2025-03-20", 5 * GB),
+        usage_json(5, "2025-03-19", 38 * GB),
+        usage_json(5, "2025-03-20", 38 * GB),
+    ]
+    .into_iter()
+    .map(|u| serde_json::from_value(u).unwrap())
+    .collect();
+
+    let options = QuotaForecastOptions::default().today(date("2025-03-21")).history_days(2);
+    let forecasts = forecast_node_quotas(&nodes, &usage, &options);
+    let statuses: Vec<(u128, QuotaStatus)> = forecasts.iter().map(|f| (f.node_uuid.as_u128(), f.status)).collect();
+    assert_eq!(statuses, [(3, QuotaStatus::Exceeded), (1, QuotaStatus::WillExceed), (5, QuotaStatus::AtRisk), (2, QuotaStatus::Ok), (4, QuotaStatus::Unlimited)]);
+
+    // 500 GB used + 30 GB/day for 11 days until April 1st.
+    let first = &forecasts[1];
+    assert_eq!(first.next_reset, Some(date("2025-04-01")));
+    assert_eq!(first.projection_days, 11);
+    assert_eq!(first.daily_rate_bytes, 30.0 * GB as f64);
+    assert_eq!(first.projected_bytes, 830 * GB as u64);
+    assert_eq!(forecasts[0].limit_reached_on, Some(date("2025-03-21")));
+    assert_eq!(first.limit_reached_on, Some(date("2025-03-31")));
+    assert_eq!(forecasts[2].limit_reached_on, None);
+
+    let doubled = forecasts.iter().find(|f| f.node_uuid == Uuid::from_u128(2)).unwrap();
+    assert_eq!(doubled.projected_bytes, 555 * GB as u64);
+    assert_eq!(doubled.billed_projected_bytes, 1110 * GB as u64);
+    assert_eq!(doubled.billed_daily_rate_bytes, 10.0 * GB as f64);
+
+    let mut fast: NodeDto = serde_json::from_value(node_json(6, Some(100 * GB), 10 * GB, None, 1.0)).unwrap();
+    fast.traffic_reset_day = None;
+    let fast_usage: Vec<NodesUsageData> =
+        [usage_json(6, "2025-03-19", 10 * GB), usage_json(6, "2025-03-20", 10 * GB)].into_iter().map(|u| serde_json::from_value(u).unwrap()).collect();
+    let forecast = &forecast_node_quotas(&[fast], &fast_usage, &options)[0];
+    assert_eq!((forecast.status, forecast.projection_days), (QuotaStatus::WillExceed, 30));
+    assert_eq!(forecast.limit_reached_on, Some(date("2025-03-30")));
+}
+
+#[test]
+fn averages_over_calendar_days_since_the_reset() {
+    let options = QuotaForecastOptions::default().today(date("2025-03-21"));
+    // Reset on the 18th: the window is March 18-20 even though only two of its days have records.
+    let node: NodeDto = serde_json::from_value(node_json(1, Some(1000 * GB), 60 * GB, Some(18), 1.0)).unwrap();
+    let usage: Vec<NodesUsageData> = [usage_json(1, "2025-03-15", 100 * GB), usage_json(1, "2025-03-18", 30 * GB), usage_json(1, "2025-03-20", 30 * GB)]
+        .into_iter()
+        .map(|u| serde_json::from_value(u).unwrap())
+        .collect();
+    assert_eq!(forecast_node_quotas(&[node], &usage, &options)[0].daily_rate_bytes, 20.0 * GB as f64);
+
+    // Without a reset day the whole week counts, so one busy day is spread over seven.
+    let node: NodeDto = serde_json::from_value(node_json(2, Some(1000 * GB), 0, None, 1.0)).unwrap();
+    let usage: Vec<NodesUsageData> = vec![serde_json::from_value(usage_json(2, "2025-03-20", 70 * GB)).unwrap()];
+    assert_eq!(forecast_node_quotas(std::slice::from_ref(&node), &usage, &options)[0].daily_rate_bytes, 10.0 * GB as f64);
+
+    // A node created two days ago is averaged over those two days only.
+    let young = NodeDto {
+        created_at: "2025-03-19T12:00:00Z".into(),
+        ..node
+    };
+    assert_eq!(forecast_node_quotas(&[young], &usage, &options)[0].daily_rate_bytes, 35.0 * GB as f64);
+}
+
+#[tokio::test]
+async fn fetches_nodes_and_usage() {
+    let mut server = mockito::Server::new_async().await;
+    let nodes = server.mock("GET", "/api/nodes").with_body(json!({ "response": [node_json(1, Some(100 * GB), 0, Some(1), 1.0)] }).to_string()).create_async().await;
+    let usage = server
+        .mock("GET", "/api/nodes/usage/range")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("start".into(), "2025-03-14".into()),
+            mockito::Matcher::UrlEncoded("end".into(), "2025-03-21".into()),
+        ]))
+        .with_body(json!({ "response": [usage_json(1, "2025-03-20", 7 * GB)] }).to_string())
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let forecasts = client.forecast_node_quotas(&QuotaForecastOptions::default().today(date("2025-03-21"))).await.unwrap();
+
+    nodes.assert_async().await;
+    usage.assert_async().await;
+    assert_eq!(forecasts[0].projected_bytes, 11 * GB as u64);
+}
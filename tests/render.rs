@@ -0,0 +1,167 @@
+#![cfg(feature = "formats")]
+
+use base64::Engine;
+use remnawave::tools::{render_template, RenderError};
+use remnawave::types::{MihomoConfig, RawSubscriptionResponse, SubscriptionConfig, SubscriptionTemplateType, TemplateSummary};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn host(remark: &str, protocol: &str, network: &str, tls: &str, is_disabled: bool) -> Value {
+    json!({
+        "address": "node.example.com",
+        "alpn": "h2,http/1.1",
+        "fingerprint": "chrome",
+        "host": "cdn.example.com",
+        "network": network,
+        "password": { "trojanPassword": "trojan-secret", "vlessPassword": "a1b2c3d4-0000-4000-8000-000000000001", "ssPassword": "ss-secret" },
+        "path": "/ws",
+        "publicKey": "pbk",
+        "port": 443,
+        "protocol": protocol,
+        "remark": remark,
+        "shortId": "abcd",
+        "sni": "sni.example.com",
+        "tls": tls,
+        "flow": "",
+        "dbData": {
+            "rawInbound": {},
+            "inboundTag": "VLESS",
+            "uuid": Uuid::nil(),
+            "configProfileUuid": null,
+            "configProfileInboundUuid": null,
+            "isDisabled": is_disabled,
+            "viewPosition": 0,
+            "remark": remark,
+            "isHidden": false,
+            "tag": null,
+            "vlessRouteId": null
+        }
+    })
+}
+
+fn subscription() -> RawSubscriptionResponse {
+    serde_json::from_value(json!({
+        "user": {
+            "uuid": Uuid::nil(),
+            "shortUuid": "abc",
+            "username": "alice",
+            "status": "ACTIVE",
+            "usedTrafficBytes": 0,
+            "lifetimeUsedTrafficBytes": 0,
+            "trafficLimitBytes": 0,
+            "trafficLimitStrategy": "NO_RESET",
+            "subLastUserAgent": null,
+            "subLastOpenedAt": null,
+            "expireAt": "2030-01-01T00:00:00Z",
+            "onlineAt": null,
+            "subRevokedAt": null,
+            "lastTrafficResetAt": null,
+            "trojanPassword": "trojan-secret",
+            "vlessUuid": Uuid::nil(),
+            "ssPassword": "ss-secret",
+            "description": null,
+            "tag": null,
+            "telegramId": null,
+            "email": null,
+            "hwidDeviceLimit": null,
+            "firstConnectedAt": null,
+            "lastTriggeredThreshold": 0,
+            "createdAt": "2025-01-01T00:00:00Z",
+            "updatedAt": "2025-01-01T00:00:00Z",
+            "activeInternalSquads": [],
+            "externalSquadUuid": null,
+            "subscriptionUrl": "https://sub.example.com/abc",
+            "lastConnectedNode": null,
+            "happ": { "cryptoLink": "happ://crypt/abc" }
+        },
+        "convertedUserInfo": { "daysLeft": 12.7, "trafficLimit": "∞", "trafficUsed": "1.5 GiB", "lifetimeTrafficUsed": "3 GiB", "isHwidLimited": false },
+        "headers": {},
+        "rawHosts": [
+            host("🇩🇪 {{USERNAME}} Reality", "vless", "tcp", "reality", false),
+            host("🇳🇱 WS {{DAYS_LEFT}}d", "trojan", "ws", "tls", false),
+            host("Disabled", "vless", "tcp", "reality", true),
+            host("xHTTP", "vless", "xhttp", "tls", false)
+        ]
+    }))
+    .unwrap()
+}
+
+fn template(template_type: SubscriptionTemplateType, template_json: Option<Value>, yaml: Option<&str>) -> TemplateSummary {
+    TemplateSummary {
+        uuid: Uuid::nil(),
+        name: "preview".to_string(),
+        template_type,
+        template_json,
+        encoded_template_yaml: yaml.map(|y| base64::engine::general_purpose::STANDARD.encode(y)),
+    }
+}
+
+#[test]
+fn renders_mihomo_template() {
+    let yaml = "mixed-port: 7890\nproxy-groups:\n  - name: PROXY\n    type: select\n    proxies: [DIRECT]\n    remnawave:\n      include-proxies: true\n  - name: Static\n    type: select\n    proxies: [DIRECT]\nrules:\n  - MATCH,PROXY\n";
+    let SubscriptionConfig::Mihomo(config) = render_template(&template(SubscriptionTemplateType::Mihomo, None, Some(yaml)), &subscription()).unwrap() else {
+        panic!("expected a Mihomo config");
+    };
+
+    let names: Vec<&str> = config.proxies.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["🇩🇪 alice Reality", "🇳🇱 WS 12d"]);
+    assert_eq!(config.proxy_group("PROXY").unwrap().proxies, ["DIRECT", "🇩🇪 alice Reality", "🇳🇱 WS 12d"]);
+    assert_eq!(config.proxy_group("Static").unwrap().proxies, ["DIRECT"]);
+    assert!(config.proxy_groups.iter().all(|g| !g.extra.contains_key("remnawave")));
+
+    let reality = &config.proxies[0];
+    assert_eq!((reality.proxy_type.as_str(), reality.server.as_deref(), reality.port), ("vless", Some("node.example.com"), Some(443)));
+    assert_eq!(reality.extra["reality-opts"], json!({ "public-key": "pbk", "short-id": "abcd" }));
+    assert_eq!(config.proxies[1].extra["ws-opts"], json!({ "path": "/ws", "headers": { "Host": "cdn.example.com" } }));
+
+    let text = SubscriptionConfig::Mihomo(config).to_text().unwrap();
+    assert!(serde_yaml::from_str::<MihomoConfig>(&text).is_ok());
+}
+
+#[test]
+fn renders_singbox_template() {
+    let body = json!({
+        "outbounds": [
+            { "type": "selector", "tag": "proxy", "outbounds": ["auto"] },
+            { "type": "urltest", "tag": "auto" },
+            { "type": "direct", "tag": "direct" }
+        ],
+        "route": { "final": "proxy" }
+    });
+    let SubscriptionConfig::SingBox(config) = render_template(&template(SubscriptionTemplateType::Singbox, Some(body), None), &subscription()).unwrap() else {
+        panic!("expected a sing-box config");
+    };
+
+    assert_eq!(config.outbound("proxy").unwrap().outbounds, ["auto", "🇩🇪 alice Reality", "🇳🇱 WS 12d"]);
+    assert_eq!(config.outbound("auto").unwrap().outbounds, ["🇩🇪 alice Reality", "🇳🇱 WS 12d"]);
+    assert!(config.outbound("direct").unwrap().outbounds.is_empty());
+    assert_eq!(config.outbound("🇳🇱 WS 12d").unwrap().extra["transport"]["type"], "ws");
+    assert_eq!(config.outbound("🇩🇪 alice Reality").unwrap().extra["tls"]["reality"]["public_key"], "pbk");
+}
+
+#[test]
+fn renders_xray_json_template() {
+    let body = json!({ "log": { "loglevel": "warning" }, "outbounds": [{ "tag": "direct", "protocol": "freedom" }] });
+    let SubscriptionConfig::XrayJson(configs) = render_template(&template(SubscriptionTemplateType::XrayJson, Some(body), None), &subscription()).unwrap() else {
+        panic!("expected Xray JSON configs");
+    };
+
+    let remarks: Vec<_> = configs.iter().map(|c| c.remarks.as_deref().unwrap()).collect();
+    assert_eq!(remarks, ["🇩🇪 alice Reality", "🇳🇱 WS 12d", "xHTTP"]);
+    for config in &configs {
+        assert_eq!(config.outbounds[0].tag.as_deref(), Some("proxy"));
+        assert_eq!(config.outbounds[1].tag.as_deref(), Some("direct"));
+        assert_eq!(config.extra["log"]["loglevel"], "warning");
+    }
+    assert_eq!(configs[0].outbounds[0].stream_settings.as_ref().unwrap()["realitySettings"]["publicKey"], "pbk");
+    assert_eq!(configs[2].outbounds[0].stream_settings.as_ref().unwrap()["xhttpSettings"]["path"], "/ws");
+}
+
+#[test]
+fn rejects_unrenderable_templates() {
+    let base64 = render_template(&template(SubscriptionTemplateType::XrayBase64, None, None), &subscription());
+    assert!(matches!(base64, Err(RenderError::UnsupportedTemplateType(SubscriptionTemplateType::XrayBase64))));
+
+    let empty = render_template(&template(SubscriptionTemplateType::Stash, None, None), &subscription());
+    assert!(matches!(empty, Err(RenderError::MissingTemplateBody(SubscriptionTemplateType::Stash))));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use futures_util::{pin_mut, StreamExt};
+use remnawave::tools::{plan_rolling_restart, RestartFailure, RestartFailurePolicy, RestartOrder, RollingRestartEvent, RollingRestartOptions};
+use remnawave::types::NodeDto;
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn node_json(id: u128, view_position: u8, country_code: &str, is_disabled: bool) -> Value {
+    common::node_json(id, json!({ "isDisabled": is_disabled, "viewPosition": view_position, "countryCode": country_code }))
+}
+
+fn nodes_json() -> Vec<Value> {
+    vec![node_json(1, 2, "NL", false), node_json(2, 1, "NL", false), node_json(3, 3, "DE", false), node_json(4, 4, "DE", true)]
+}
+
+#[test]
+fn plans_batches() {
+    let nodes: Vec<NodeDto> = nodes_json().into_iter().map(|n| serde_json::from_value(n).unwrap()).collect();
+    let id = Uuid::from_u128;
+
+    assert_eq!(plan_rolling_restart(&nodes, &RollingRestartOptions::default()), [vec![id(2)], vec![id(1)], vec![id(3)]]);
+    assert_eq!(plan_rolling_restart(&nodes, &RollingRestartOptions::default().batch_size(2).order(RestartOrder::Country)), [vec![id(3), id(2)], vec![id(1)]]);
+    assert_eq!(plan_rolling_restart(&nodes, &RollingRestartOptions::default().nodes(vec![id(4), id(1), id(9)]).order(RestartOrder::AsGiven)), [vec![id(4)], vec![id(1)]]);
+}
+
+#[tokio::test]
+async fn pauses_on_failure_and_resumes() {
+    let mut server = mockito::Server::new_async().await;
+    let list = server.mock("GET", "/api/nodes").with_body(json!({ "response": nodes_json() }).to_string()).expect_at_least(3).create_async().await;
+    let mut restarts = Vec::new();
+    for (id, event_sent) in [(2, true), (1, false), (3, true)] {
+        let path = format!("/api/nodes/{}/actions/restart", Uuid::from_u128(id));
+        restarts.push(server.mock("POST", path.as_str()).with_body(json!({ "response": { "eventSent": event_sent } }).to_string()).expect(1).create_async().await);
+    }
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let options = RollingRestartOptions::default().poll_interval(Duration::from_millis(1)).settle_time(Duration::ZERO).on_failure(RestartFailurePolicy::Pause);
+    let (control, events) = client.nodes.rolling_restart(options);
+    pin_mut!(events);
+
+    let mut seen = Vec::new();
+    while let Some(event) = events.next().await {
+        let event = event.unwrap();
+        if event == RollingRestartEvent::Paused {
+            assert!(control.is_paused());
+            control.resume();
+        }
+        seen.push(event);
+    }
+
+    let id = Uuid::from_u128;
+    let ready = |uuid: Uuid| seen.iter().any(|e| matches!(e, RollingRestartEvent::NodeReady { node_uuid, .. } if *node_uuid == uuid));
+    assert!(ready(id(2)) && ready(id(3)));
+    let expected = [
+        RollingRestartEvent::Planned {
+            batches: vec![vec![id(2)], vec![id(1)], vec![id(3)]],
+        },
+        RollingRestartEvent::BatchStarted {
+            index: 0,
+            nodes: vec![id(2)],
+        },
+        RollingRestartEvent::BatchCompleted {
+            index: 0,
+        },
+        RollingRestartEvent::BatchStarted {
+            index: 1,
+            nodes: vec![id(1)],
+        },
+        RollingRestartEvent::NodeFailed {
+            node_uuid: id(1),
+            failure: RestartFailure::NotSent,
+        },
+        RollingRestartEvent::BatchCompleted {
+            index: 1,
+        },
+        RollingRestartEvent::Paused,
+        RollingRestartEvent::Resumed,
+        RollingRestartEvent::BatchStarted {
+            index: 2,
+            nodes: vec![id(3)],
+        },
+        RollingRestartEvent::BatchCompleted {
+            index: 2,
+        },
+        RollingRestartEvent::Finished {
+            restarted: vec![id(2), id(3)],
+            failed: vec![id(1)],
+        },
+    ];
+    let without_ready: Vec<_> = seen.into_iter().filter(|e| !matches!(e, RollingRestartEvent::NodeReady { .. })).collect();
+    assert_eq!(without_ready, expected);
+
+    list.assert_async().await;
+    for mock in restarts {
+        mock.assert_async().await;
+    }
+}
+
+#[tokio::test]
+async fn aborts_on_timeout() {
+    let mut server = mockito::Server::new_async().await;
+    let mut down = node_json(2, 1, "NL", false);
+    down["isXrayRunning"] = json!(false);
+    server.mock("GET", "/api/nodes").with_body(json!({ "response": [node_json(1, 2, "NL", false), down] }).to_string()).create_async().await;
+    let restart =
+        server.mock("POST", format!("/api/nodes/{}/actions/restart", Uuid::from_u128(2)).as_str()).with_body(r#"{"response":{"eventSent":true}}"#).expect(1).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let options = RollingRestartOptions::default().poll_interval(Duration::from_millis(1)).timeout(Duration::from_millis(20));
+    let (_, events) = client.nodes.rolling_restart(options);
+    let events: Vec<RollingRestartEvent> = events.map(Result::unwrap).collect().await;
+
+    restart.assert_async().await;
+    assert_eq!(
+        events[events.len() - 3..],
+        [
+            RollingRestartEvent::NodeFailed {
+                node_uuid: Uuid::from_u128(2),
+                failure: RestartFailure::Timeout,
+            },
+            RollingRestartEvent::BatchCompleted {
+                index: 0
+            },
+            RollingRestartEvent::Aborted {
+                remaining: vec![Uuid::from_u128(1)]
+            },
+        ]
+    );
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use remnawave::tools::{SharingAnalyzer, SharingEvidence, SharingThresholds};
+use remnawave::types::HwidDeviceDto;
+use remnawave::RemnawaveApiClient;
+use serde_json::json;
+use uuid::Uuid;
+
+fn at(hours: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap() + Duration::hours(hours)
+}
+
+fn device(user_uuid: Uuid, hwid: &str) -> HwidDeviceDto {
+    HwidDeviceDto {
+        hwid: hwid.to_string(),
+        user_uuid,
+        platform: None,
+        os_version: None,
+        device_model: None,
+        user_agent: None,
+        created_at: "2025-06-01T00:00:00Z".to_string(),
+        updated_at: "2025-06-01T00:00:00Z".to_string(),
+    }
+}
+
+#[test]
+fn ranks_sharing_suspects_with_evidence() {
+    let honest = Uuid::from_u128(1);
+    let sharer = Uuid::from_u128(2);
+    let reseller = Uuid::from_u128(3);
+    let mut analyzer = SharingAnalyzer::new(SharingThresholds {
+        max_ips: 2,
+        max_subnets: 2,
+        max_apps: 2,
+        max_hwid_devices: 2,
+        ..Default::default()
+    });
+
+    // Two IPs in one /24, one app, one device.
+    for hour in 0..48 {
+        let ip = if hour % 2 == 0 {
+            "10.0.0.1"
+        } else {
+            "10.0.0.2"
+        };
+        analyzer.add_request(honest, Some(ip), Some("Happ/1.2.0"), at(hour * 3));
+    }
+    analyzer.add_hwid_devices(&[device(honest, "a")]);
+
+    // Three networks and three apps within the hour, but only one registered device.
+    for (minute, ip, ua) in [(0, "10.1.0.1", "Happ/1.2.0"), (20, "172.16.3.4", "v2rayNG/1.9.0"), (40, "2001:db8:1::1", "clash-verge/v2.0.0")] {
+        analyzer.add_request(sharer, Some(ip), Some(ua), at(0) + Duration::minutes(minute));
+    }
+    analyzer.add_hwid_devices(&[device(sharer, "b")]);
+
+    // Every request from a new network; requests outside the window are ignored.
+    analyzer.add_request(reseller, Some("198.51.100.1"), Some("Streisand/1.0"), at(-24 * 30));
+    for i in 0..48 {
+        analyzer.add_request(reseller, Some(&format!("192.0.{i}.1")), Some("Happ/1.2.0"), at(0) + Duration::minutes(i * 10));
+    }
+    analyzer.add_hwid_devices(&[device(reseller, "c"), device(reseller, "d"), device(reseller, "e"), device(reseller, "e")]);
+
+    let honest_stats = analyzer.stats(&honest).unwrap();
+    assert_eq!((honest_stats.distinct_ips, honest_stats.distinct_subnets, honest_stats.hwid_devices), (2, 1, Some(1)));
+
+    let suspects = analyzer.suspects();
+    let ranked: Vec<Uuid> = suspects.iter().map(|s| s.user_uuid).collect();
+    assert_eq!(ranked, [reseller, sharer]);
+
+    let reseller_suspect = &suspects[0];
+    assert_eq!(reseller_suspect.stats.requests, 48);
+    assert_eq!(reseller_suspect.stats.distinct_subnets, 48);
+    assert_eq!(reseller_suspect.stats.hwid_devices, Some(3));
+    assert!(reseller_suspect.evidence.contains(&SharingEvidence::HwidDevices {
+        count: 3,
+        limit: 2
+    }));
+    assert!(reseller_suspect.evidence.iter().any(|e| matches!(e, SharingEvidence::IpChurn { .. })));
+    assert!(reseller_suspect.evidence.iter().any(|e| matches!(e, SharingEvidence::RequestFrequency { .. })));
+
+    assert_eq!(
+        suspects[1].evidence,
+        [
+            SharingEvidence::DistinctIps {
+                count: 3,
+                limit: 2
+            },
+            SharingEvidence::DistinctSubnets {
+                count: 3,
+                limit: 2
+            },
+            SharingEvidence::DistinctApps {
+                apps: vec!["Clash Verge".to_string(), "Happ".to_string(), "v2rayNG".to_string()],
+                limit: 2
+            },
+            SharingEvidence::SubnetsExceedDevices {
+                subnets: 3,
+                devices: 1
+            },
+        ]
+    );
+}
+
+#[test]
+fn roaming_device_is_not_counted_as_several() {
+    let user = Uuid::from_u128(1);
+    let mut analyzer = SharingAnalyzer::default();
+    analyzer.add_hwid_devices(&[device(user, "phone")]);
+    // Home in the morning, mobile on the way, office during the day, home again; never two networks within an hour.
+    for (hour, ip) in [(7, "192.168.1.10"), (8, "100.64.5.3"), (9, "10.20.0.7"), (13, "10.20.0.8"), (18, "100.64.9.1"), (19, "192.168.1.10")] {
+        analyzer.add_request(user, Some(ip), Some("Happ/1.2.0"), at(hour));
+    }
+
+    let stats = analyzer.stats(&user).unwrap();
+    assert_eq!((stats.distinct_subnets, stats.concurrent_subnets, stats.hwid_devices), (4, 1, Some(1)));
+    assert!(analyzer.suspects().is_empty());
+
+    // Another network ten minutes after the home request does count.
+    analyzer.add_request(user, Some("100.64.5.9"), Some("Happ/1.2.0"), at(19) + Duration::minutes(10));
+    let suspect = &analyzer.suspects()[0];
+    assert_eq!(
+        suspect.evidence,
+        [SharingEvidence::SubnetsExceedDevices {
+            subnets: 2,
+            devices: 1
+        }]
+    );
+}
+
+#[tokio::test]
+async fn get_all_since_pages_until_records_are_too_old() {
+    let mut server = mockito::Server::new_async().await;
+    let record = |id: i64, hours: i64| json!({ "id": id, "userUuid": Uuid::nil(), "requestIp": "10.0.0.1", "userAgent": "Happ/1.2.0", "requestAt": at(hours) });
+    let pages = [
+        ("0", vec![record(10, 10), record(9, 9)]),
+        ("2", vec![record(8, 8), record(7, -100)]),
+        ("4", vec![record(6, -101), record(5, -102)]),
+        ("6", vec![record(4, -103), record(3, -104)]),
+    ];
+    let mut mocks = Vec::new();
+    for (i, (start, records)) in pages.into_iter().enumerate() {
+        let query = mockito::Matcher::AllOf(vec![mockito::Matcher::UrlEncoded("size".into(), "2".into()), mockito::Matcher::UrlEncoded("start".into(), start.into())]);
+        let body = json!({ "response": { "records": records, "total": 10 } }).to_string();
+        mocks.push(server.mock("GET", "/api/subscription-request-history").match_query(query).with_body(body).expect(usize::from(i < 3)).create_async().await);
+    }
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let records = client.subscription_request_history.get_all_since(at(0), 2).await.unwrap();
+
+    for mock in mocks {
+        mock.assert_async().await;
+    }
+    assert_eq!(records.iter().map(|r| r.id).collect::<Vec<_>>(), [10, 9, 8]);
+}
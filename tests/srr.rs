@@ -0,0 +1,258 @@
+use remnawave::tools::{Severity, SrrEvaluator, SrrRequest, SRR_DEFAULT_RESPONSE_TYPE};
+use remnawave::types::{
+    SubscriptionResponseRule, SubscriptionResponseRuleCondition, SubscriptionResponseRuleConditionOperator as Op, SubscriptionResponseRuleHeaderModification,
+    SubscriptionResponseRuleModifications, SubscriptionResponseRuleOperator, SubscriptionResponseRuleType, SubscriptionResponseRulesConfig, SubscriptionTemplateType,
+    TemplateSummary,
+};
+use uuid::Uuid;
+
+fn condition(header_name: &str, operator: Op, value: &str, case_sensitive: bool) -> SubscriptionResponseRuleCondition {
+    SubscriptionResponseRuleCondition {
+        header_name: header_name.to_string(),
+        operator,
+        value: value.to_string(),
+        case_sensitive,
+    }
+}
+
+fn rule(
+    name: &str,
+    operator: SubscriptionResponseRuleOperator,
+    conditions: Vec<SubscriptionResponseRuleCondition>,
+    response_type: SubscriptionResponseRuleType,
+) -> SubscriptionResponseRule {
+    SubscriptionResponseRule {
+        name: name.to_string(),
+        description: None,
+        enabled: true,
+        operator,
+        conditions,
+        response_type,
+        response_modifications: None,
+    }
+}
+
+fn config(rules: Vec<SubscriptionResponseRule>) -> SubscriptionResponseRulesConfig {
+    SubscriptionResponseRulesConfig {
+        version: "1".to_string(),
+        rules,
+    }
+}
+
+#[test]
+fn condition_operators() {
+    let cases = [
+        (Op::Equals, "Happ/1.0", "Happ/1.0", false, true),
+        (Op::Equals, "happ/1.0", "Happ/1.0", false, true),
+        (Op::Equals, "happ/1.0", "Happ/1.0", true, false),
+        (Op::NotEquals, "Happ/1.0", "Happ/2.0", false, true),
+        (Op::NotEquals, "HAPP/1.0", "happ/1.0", false, false),
+        (Op::Contains, "clash", "ClashMeta/1.18", false, true),
+        (Op::Contains, "clash", "ClashMeta/1.18", true, false),
+        (Op::NotContains, "clash", "sing-box 1.11", false, true),
+        (Op::NotContains, "Clash", "clash-verge", false, false),
+        (Op::StartsWith, "sfa", "SFA/1.11.0", false, true),
+        (Op::StartsWith, "1.11", "SFA/1.11.0", false, false),
+        (Op::NotStartsWith, "sfa", "Hiddify/2.0", false, true),
+        (Op::EndsWith, "(ios)", "Streisand/1.6 (iOS)", false, true),
+        (Op::EndsWith, "(ios)", "Streisand/1.6 (iOS)", true, false),
+        (Op::NotEndsWith, "(ios)", "Streisand/1.6 (Android)", false, true),
+        (Op::Regex, r"^v2rayng/1\.(8|9)", "v2rayNG/1.9.3", false, true),
+        (Op::Regex, r"^v2rayng/1\.(8|9)", "v2rayNG/1.9.3", true, false),
+        (Op::Regex, r"\d+\.\d+", "mihomo/1.18.5", false, true),
+        (Op::NotRegex, r"^clash", "Stash/2.4", false, true),
+        (Op::NotRegex, r"^clash", "clash.meta", false, false),
+    ];
+
+    for (operator, value, header, case_sensitive, expected) in cases {
+        let c = condition("user-agent", operator.clone(), value, case_sensitive);
+        assert_eq!(c.matches(Some(header)), expected, "{operator:?} {value:?} against {header:?} (case_sensitive: {case_sensitive})");
+    }
+}
+
+#[test]
+fn missing_header_only_matches_negated_operators() {
+    for operator in [Op::Equals, Op::Contains, Op::StartsWith, Op::EndsWith, Op::Regex] {
+        assert!(!condition("x-hwid", operator, "a", false).matches(None));
+    }
+    for operator in [Op::NotEquals, Op::NotContains, Op::NotStartsWith, Op::NotEndsWith, Op::NotRegex] {
+        assert!(condition("x-hwid", operator, "a", false).matches(None));
+    }
+}
+
+#[test]
+fn invalid_regex_never_matches() {
+    assert!(!condition("user-agent", Op::Regex, "(unclosed", false).matches(Some("(unclosed")));
+    assert!(!condition("user-agent", Op::NotRegex, "(unclosed", false).matches(Some("anything")));
+}
+
+#[test]
+fn header_names_are_case_insensitive() {
+    let rules = config(vec![rule("hwid", SubscriptionResponseRuleOperator::And, vec![condition("X-HWID", Op::Equals, "abc", true)], SubscriptionResponseRuleType::XrayJson)]);
+
+    let request = SrrRequest::from_headers([("x-hwid", "abc")]);
+    assert!(rules.evaluate(&request).matched);
+}
+
+#[test]
+fn and_or_operators() {
+    let conditions = vec![condition("user-agent", Op::Contains, "happ", false), condition("x-device-os", Op::Equals, "iOS", false)];
+    let and_rules = config(vec![rule("and", SubscriptionResponseRuleOperator::And, conditions.clone(), SubscriptionResponseRuleType::XrayJson)]);
+    let or_rules = config(vec![rule("or", SubscriptionResponseRuleOperator::Or, conditions, SubscriptionResponseRuleType::XrayJson)]);
+
+    let both = SrrRequest::with_user_agent("Happ/1.6").header("X-Device-OS", "ios");
+    let one = SrrRequest::with_user_agent("Happ/1.6").header("X-Device-OS", "Android");
+    let none = SrrRequest::with_user_agent("curl/8.0");
+
+    assert!(and_rules.evaluate(&both).matched);
+    assert!(!and_rules.evaluate(&one).matched);
+    assert!(!and_rules.evaluate(&none).matched);
+
+    assert!(or_rules.evaluate(&both).matched);
+    assert!(or_rules.evaluate(&one).matched);
+    assert!(!or_rules.evaluate(&none).matched);
+}
+
+#[test]
+fn rules_without_conditions() {
+    let request = SrrRequest::with_user_agent("anything");
+    let and_rules = config(vec![rule("catch-all", SubscriptionResponseRuleOperator::And, vec![], SubscriptionResponseRuleType::Block)]);
+    let or_rules = config(vec![rule("never", SubscriptionResponseRuleOperator::Or, vec![], SubscriptionResponseRuleType::Block)]);
+
+    assert_eq!(and_rules.evaluate(&request).response_type, SubscriptionResponseRuleType::Block);
+    assert!(!or_rules.evaluate(&request).matched);
+}
+
+#[test]
+fn first_enabled_match_wins() {
+    let mut disabled = rule("disabled", SubscriptionResponseRuleOperator::And, vec![condition("user-agent", Op::Contains, "clash", false)], SubscriptionResponseRuleType::Block);
+    disabled.enabled = false;
+
+    let rules = config(vec![
+        disabled,
+        rule("stash", SubscriptionResponseRuleOperator::And, vec![condition("user-agent", Op::StartsWith, "stash", false)], SubscriptionResponseRuleType::Stash),
+        rule("clash", SubscriptionResponseRuleOperator::And, vec![condition("user-agent", Op::Contains, "clash", false)], SubscriptionResponseRuleType::Mihomo),
+        rule("any-meta", SubscriptionResponseRuleOperator::And, vec![condition("user-agent", Op::Regex, "meta", false)], SubscriptionResponseRuleType::Clash),
+    ]);
+    let evaluator = SrrEvaluator::new(&rules);
+
+    let (index, matched) = evaluator.matched_rule(&SrrRequest::with_user_agent("clash.meta")).unwrap();
+    assert_eq!((index, matched.name.as_str()), (2, "clash"));
+
+    let response = evaluator.evaluate(&SrrRequest::with_user_agent("Stash/2.4 clash"));
+    assert!(response.matched);
+    assert_eq!(response.response_type, SubscriptionResponseRuleType::Stash);
+    assert_eq!(response.matched_rule.unwrap().name, "stash");
+}
+
+#[test]
+fn unmatched_request_falls_back_to_default() {
+    let rules =
+        config(vec![rule("browser", SubscriptionResponseRuleOperator::And, vec![condition("user-agent", Op::StartsWith, "Mozilla", true)], SubscriptionResponseRuleType::Browser)]);
+
+    let response = rules.evaluate(&SrrRequest::with_user_agent("mozilla/5.0"));
+    assert!(!response.matched);
+    assert!(response.matched_rule.is_none());
+    assert_eq!(response.response_type, SRR_DEFAULT_RESPONSE_TYPE);
+}
+
+fn codes(rules: &SubscriptionResponseRulesConfig) -> Vec<(&'static str, String)> {
+    rules.validate().into_iter().map(|d| (d.code, d.pointer)).collect()
+}
+
+#[test]
+fn builder_produces_rule() {
+    let built = SubscriptionResponseRule::builder("happ", SubscriptionResponseRuleType::XrayJson)
+        .any()
+        .condition(SubscriptionResponseRuleCondition::user_agent().starts_with("Happ"))
+        .condition(SubscriptionResponseRuleCondition::header("X-Device-OS").case_sensitive().equals("iOS"))
+        .response_header("profile-title", "VPN")
+        .subscription_template("happ-json")
+        .build();
+
+    let mut expected = rule(
+        "happ",
+        SubscriptionResponseRuleOperator::Or,
+        vec![condition("user-agent", Op::StartsWith, "Happ", false), condition("X-Device-OS", Op::Equals, "iOS", true)],
+        SubscriptionResponseRuleType::XrayJson,
+    );
+    expected.response_modifications = Some(SubscriptionResponseRuleModifications {
+        headers: Some(vec![SubscriptionResponseRuleHeaderModification {
+            key: "profile-title".to_string(),
+            value: "VPN".to_string(),
+        }]),
+        subscription_template: Some("happ-json".to_string()),
+    });
+    assert_eq!(built, expected);
+}
+
+#[test]
+fn validator_reports_broken_and_impossible_rules() {
+    let ua = SubscriptionResponseRuleCondition::user_agent;
+    let rules = config(vec![
+        SubscriptionResponseRule::builder("bad-regex", SubscriptionResponseRuleType::XrayJson).condition(ua().regex("(unclosed")).build(),
+        SubscriptionResponseRule::builder("contradiction", SubscriptionResponseRuleType::XrayJson).conditions([ua().equals("happ"), ua().starts_with("clash")]).build(),
+        SubscriptionResponseRule::builder("negation", SubscriptionResponseRuleType::XrayJson).conditions([ua().contains("meta"), ua().not_contains("met")]).build(),
+        SubscriptionResponseRule::builder("prefixes", SubscriptionResponseRuleType::XrayJson).conditions([ua().starts_with("sfa"), ua().starts_with("sfi")]).build(),
+        SubscriptionResponseRule::builder("empty-or", SubscriptionResponseRuleType::XrayJson).any().build(),
+        SubscriptionResponseRule::builder("fine", SubscriptionResponseRuleType::XrayJson).conditions([ua().starts_with("sfa"), ua().contains("1.11")]).build(),
+    ]);
+
+    assert_eq!(
+        codes(&rules),
+        [
+            ("invalid-regex", "/rules/0/conditions/0/value".to_string()),
+            ("rule-never-matches", "/rules/1".to_string()),
+            ("rule-never-matches", "/rules/2".to_string()),
+            ("rule-never-matches", "/rules/3".to_string()),
+            ("rule-never-matches", "/rules/4".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn validator_reports_shadowed_and_unreachable_rules() {
+    let ua = SubscriptionResponseRuleCondition::user_agent;
+    let rules = config(vec![
+        SubscriptionResponseRule::builder("clash", SubscriptionResponseRuleType::Mihomo).condition(ua().contains("clash")).build(),
+        SubscriptionResponseRule::builder("clash-meta", SubscriptionResponseRuleType::Mihomo).condition(ua().starts_with("clash.meta")).build(),
+        SubscriptionResponseRule::builder("clash-or-stash", SubscriptionResponseRuleType::Clash).any().conditions([ua().contains("stash"), ua().contains("clash")]).build(),
+        SubscriptionResponseRule::builder("disabled-catch-all", SubscriptionResponseRuleType::Block).disabled().build(),
+        SubscriptionResponseRule::builder("tautology", SubscriptionResponseRuleType::Browser).any().conditions([ua().regex("^mozilla"), ua().not_regex("^mozilla")]).build(),
+        SubscriptionResponseRule::builder("after-catch-all", SubscriptionResponseRuleType::XrayJson).condition(ua().equals("happ")).build(),
+        SubscriptionResponseRule::builder("clash", SubscriptionResponseRuleType::XrayJson).build(),
+    ]);
+
+    assert_eq!(
+        codes(&rules),
+        [
+            ("shadowed-rule", "/rules/1".to_string()),
+            ("unreachable-rule", "/rules/5".to_string()),
+            ("duplicate-rule-name", "/rules/6/name".to_string()),
+            ("unreachable-rule", "/rules/6".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn validator_cross_checks_templates() {
+    let template = |name: &str, template_type| TemplateSummary {
+        uuid: Uuid::nil(),
+        name: name.to_string(),
+        template_type,
+        template_json: None,
+        encoded_template_yaml: None,
+    };
+    let templates = [template("mihomo-custom", SubscriptionTemplateType::Mihomo), template("json-custom", SubscriptionTemplateType::XrayJson)];
+    let ua = SubscriptionResponseRuleCondition::user_agent;
+    let rules = config(vec![
+        SubscriptionResponseRule::builder("ok", SubscriptionResponseRuleType::Mihomo).condition(ua().contains("mihomo")).subscription_template("mihomo-custom").build(),
+        SubscriptionResponseRule::builder("mismatch", SubscriptionResponseRuleType::Singbox).condition(ua().contains("sing-box")).subscription_template("json-custom").build(),
+        SubscriptionResponseRule::builder("missing", SubscriptionResponseRuleType::XrayJson).condition(ua().contains("happ")).subscription_template("nope").build(),
+        SubscriptionResponseRule::builder("ignored", SubscriptionResponseRuleType::Block).condition(ua().contains("curl")).subscription_template("json-custom").build(),
+    ]);
+
+    let found: Vec<_> = rules.validate_with_templates(&templates).into_iter().map(|d| (d.severity, d.code)).collect();
+    assert_eq!(found, [(Severity::Error, "template-type-mismatch"), (Severity::Error, "unknown-template"), (Severity::Warning, "template-ignored")]);
+    assert_eq!(rules.validate().len(), 1);
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures_util::{pin_mut, StreamExt};
+use remnawave::tools::{Rate, ThroughputOptions, ThroughputSampler};
+use remnawave::types::NodeRealtimeUsage;
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn usage_json(id: u128, download: usize, upload: usize) -> Value {
+    json!({
+        "nodeUuid": Uuid::from_u128(id),
+        "nodeName": format!("node-{id}"),
+        "countryCode": "DE",
+        "downloadBytes": download,
+        "uploadBytes": upload,
+        "totalBytes": download + upload,
+        "downloadSpeedBps": 0,
+        "uploadSpeedBps": 0,
+        "totalSpeedBps": 0
+    })
+}
+
+fn usage(rows: &[(u128, usize, usize)]) -> Vec<NodeRealtimeUsage> {
+    rows.iter().map(|&(id, down, up)| serde_json::from_value(usage_json(id, down, up)).unwrap()).collect()
+}
+
+fn pair(rate: Rate) -> (f64, f64) {
+    (rate.download, rate.upload)
+}
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    DateTime::UNIX_EPOCH + ChronoDuration::seconds(seconds)
+}
+
+#[test]
+fn computes_rates_averages_and_peaks() {
+    let mut sampler = ThroughputSampler::new(ThroughputOptions::default().capacity(3).average_window(2));
+    let node = Uuid::from_u128(1);
+
+    sampler.record(&usage(&[(1, 0, 0), (2, 0, 0)]), at(0));
+    assert_eq!(sampler.stats(&node), None);
+
+    sampler.record(&usage(&[(1, 1000, 100), (2, 50, 0)]), at(10));
+    sampler.record(&usage(&[(1, 4000, 300), (2, 100, 0)]), at(20));
+    sampler.record(&usage(&[(1, 5000, 400), (2, 150, 0)]), at(30));
+    // Counter reset after a node restart: new baseline, no sample.
+    sampler.record(&usage(&[(1, 10, 10), (2, 200, 0)]), at(40));
+    sampler.record(&usage(&[(1, 2010, 510), (2, 250, 0)]), at(50));
+
+    let rates: Vec<(f64, f64)> = sampler.samples(&node).map(|s| pair(s.rate)).collect();
+    assert_eq!(rates, [(300.0, 20.0), (100.0, 10.0), (200.0, 50.0)]);
+
+    let stats = sampler.stats(&node).unwrap();
+    assert_eq!(pair(stats.current), (200.0, 50.0));
+    assert_eq!(pair(stats.average), (150.0, 30.0));
+    assert_eq!(pair(stats.peak), (300.0, 50.0));
+    assert_eq!(stats.samples, 3);
+    assert_eq!(sampler.moving_average(&node, 10).map(pair), Some((200.0, 80.0 / 3.0)));
+
+    assert_eq!(sampler.all_stats().iter().map(|s| s.node_name.as_str()).collect::<Vec<_>>(), ["node-1", "node-2"]);
+    assert_eq!(pair(sampler.total()), (205.0, 50.0));
+
+    sampler.record(&usage(&[(2, 300, 0)]), at(60));
+    assert_eq!(sampler.stats(&node), None);
+    assert_eq!(sampler.all_stats().len(), 1);
+}
+
+#[test]
+fn counter_reset_starts_a_new_baseline_instead_of_using_reported_speed() {
+    let snapshot = |download: usize, upload: usize, speed: usize| {
+        let mut row = usage_json(1, download, upload);
+        row["downloadSpeedBps"] = json!(speed);
+        row["uploadSpeedBps"] = json!(speed / 10);
+        vec![serde_json::from_value::<NodeRealtimeUsage>(row).unwrap()]
+    };
+    let node = Uuid::from_u128(1);
+    let mut sampler = ThroughputSampler::default();
+
+    sampler.record(&snapshot(1000, 100, 9000), at(0));
+    sampler.record(&snapshot(2000, 200, 9000), at(10));
+    sampler.record(&snapshot(50, 5, 9000), at(20));
+    assert_eq!(sampler.samples(&node).map(|s| pair(s.rate)).collect::<Vec<_>>(), [(100.0, 10.0)]);
+
+    sampler.record(&snapshot(550, 55, 70), at(30));
+    assert_eq!(sampler.samples(&node).map(|s| pair(s.rate)).collect::<Vec<_>>(), [(100.0, 10.0), (50.0, 5.0)]);
+    let stats = sampler.stats(&node).unwrap();
+    assert_eq!((pair(stats.current), pair(stats.reported)), ((50.0, 5.0), (70.0, 7.0)));
+}
+
+#[tokio::test]
+async fn stream_polls_realtime_usage() {
+    let mut server = mockito::Server::new_async().await;
+    server.mock("GET", "/api/nodes/usage/realtime").with_body(json!({ "response": [usage_json(1, 0, 0)] }).to_string()).expect(1).create_async().await;
+    server.mock("GET", "/api/nodes/usage/realtime").with_status(500).with_body(r#"{"message":"boom"}"#).expect(1).create_async().await;
+    server.mock("GET", "/api/nodes/usage/realtime").with_body(json!({ "response": [usage_json(1, 10_000, 1_000)] }).to_string()).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let (sampler, stream) = client.nodes_usage.sample_throughput(ThroughputOptions::default().interval(Duration::from_millis(10)));
+    pin_mut!(stream);
+
+    assert_eq!(stream.next().await.unwrap().unwrap(), []);
+    assert_eq!(stream.next().await.unwrap().unwrap_err().status_code, 500);
+    let stats = stream.next().await.unwrap().unwrap();
+    assert_eq!(stats.len(), 1);
+    assert!(stats[0].current.download > 0.0 && stats[0].current.upload > 0.0);
+    assert_eq!(sampler.lock().unwrap().stats(&Uuid::from_u128(1)), Some(stats[0].clone()));
+}
+
+#[tokio::test]
+async fn zero_interval_polls_instead_of_panicking() {
+    let mut server = mockito::Server::new_async().await;
+    server.mock("GET", "/api/nodes/usage/realtime").with_body(json!({ "response": [usage_json(1, 0, 0)] }).to_string()).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let (_, stream) = client.nodes_usage.sample_throughput(ThroughputOptions::default().interval(Duration::ZERO));
+    pin_mut!(stream);
+
+    assert_eq!(stream.next().await.unwrap().unwrap(), []);
+    assert!(stream.next().await.unwrap().is_ok());
+}
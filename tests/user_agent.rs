@@ -0,0 +1,48 @@
+use remnawave::tools::detect_client_app;
+use remnawave::types::SubscriptionClientType::{self, Clash, Mihomo, SingBox, SingBoxLegacy, Stash};
+
+#[test]
+fn user_agent_corpus() {
+    let corpus: &[(&str, &str, Option<&str>, Option<SubscriptionClientType>)] = &[
+        ("Happ/1.6.2", "Happ", Some("1.6.2"), None),
+        ("Happ/2.0.4/ios", "Happ", Some("2.0.4"), None),
+        ("Streisand/1.6.12 CFNetwork/1496.0.7 Darwin/23.5.0", "Streisand", Some("1.6.12"), None),
+        ("v2rayNG/1.9.16", "v2rayNG", Some("1.9.16"), None),
+        ("v2rayN/7.4.2", "v2rayN", Some("7.4.2"), None),
+        ("V2Box 1.4.7", "V2Box", Some("1.4.7"), None),
+        ("HiddifyNext/2.5.7 (android) like ClashMeta v2ray sing-box", "Hiddify", Some("2.5.7"), None),
+        ("NekoBox/Android/1.3.3 (Prefer ClashMeta Format)", "NekoBox", Some("1.3.3"), None),
+        ("Shadowrocket/2070 CFNetwork/1410.0.3 Darwin/22.6.0", "Shadowrocket", Some("2070"), None),
+        ("FoXray/3.2 CFNetwork/1494.0.7", "FoXray", Some("3.2"), None),
+        ("clash-verge/v1.3.8", "Clash Verge", Some("1.3.8"), Some(Mihomo)),
+        ("clash.meta", "Clash Meta", None, Some(Mihomo)),
+        ("ClashMetaForAndroid/2.11.1.Meta", "Clash Meta", Some("2.11.1.Meta"), Some(Mihomo)),
+        ("FlClash/v0.8.80 clash-verge Platform/android", "FlClash", Some("0.8.80"), Some(Mihomo)),
+        ("mihomo/1.18.5", "mihomo", Some("1.18.5"), Some(Mihomo)),
+        ("Stash/2.4.7 Clash/1.9.0", "Stash", Some("2.4.7"), Some(Stash)),
+        ("ClashForAndroid/2.5.12", "Clash", Some("2.5.12"), Some(Clash)),
+        ("ClashX Pro/1.118.0", "Clash", Some("1.118.0"), Some(Clash)),
+        ("Karing/1.0.35.470 android", "Karing", Some("1.0.35.470"), Some(SingBox)),
+        ("SFA/1.11.4 (Android 14; sing-box 1.11.4)", "sing-box", Some("1.11.4"), Some(SingBox)),
+        ("SFI/1.10.3 (Build 1; sing-box 1.10.3; language en_US)", "sing-box", Some("1.10.3"), Some(SingBoxLegacy)),
+        ("sing-box 1.12.0", "sing-box", Some("1.12.0"), Some(SingBox)),
+        ("sing-box 1.9.7", "sing-box", Some("1.9.7"), Some(SingBoxLegacy)),
+        ("  happ/1.0  ", "Happ", Some("1.0"), None),
+    ];
+
+    for (user_agent, name, version, client_type) in corpus {
+        let app = detect_client_app(user_agent).unwrap_or_else(|| panic!("{user_agent:?} was not recognised"));
+        assert_eq!(app.name, *name, "{user_agent:?}");
+        assert_eq!(app.version.as_deref(), *version, "{user_agent:?}");
+        assert_eq!(app.client_type, *client_type, "{user_agent:?}");
+        assert_eq!(SubscriptionClientType::detect(user_agent), *client_type, "{user_agent:?}");
+    }
+}
+
+#[test]
+fn unknown_user_agents() {
+    for user_agent in ["", "curl/8.5.0", "Mozilla/5.0 (Windows NT 10.0; Win64; x64)", "TelegramBot (like TwitterBot)", "sftp-client/1.0", "SFMonitor/2.1", "sfa"] {
+        assert_eq!(detect_client_app(user_agent), None, "{user_agent:?}");
+        assert_eq!(SubscriptionClientType::detect(user_agent), None, "{user_agent:?}");
+    }
+}
@@ -0,0 +1,150 @@
+use remnawave::types::{CreateConfigProfileRequestDto, XrayConfig, XrayDestination, XrayDnsServer, XrayInboundProtocol, XrayOutboundProtocol, XrayPort};
+use remnawave::RemnawaveApiClient;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn profile_config() -> Value {
+    json!({
+        "log": { "loglevel": "warning", "access": "none" },
+        "dns": { "servers": ["1.1.1.1", { "address": "8.8.8.8", "domains": ["geosite:google"], "expectIPs": ["geoip:us"] }], "queryStrategy": "UseIPv4" },
+        "inbounds": [
+            {
+                "tag": "VLESS_REALITY",
+                "port": 443,
+                "listen": "0.0.0.0",
+                "protocol": "vless",
+                "settings": { "clients": [], "decryption": "none" },
+                "sniffing": { "enabled": true, "destOverride": ["http", "tls", "quic"] },
+                "streamSettings": {
+                    "network": "raw",
+                    "security": "reality",
+                    "realitySettings": { "show": false, "target": "www.example.com:443", "xver": 0, "serverNames": ["www.example.com"], "privateKey": "key", "shortIds": ["", "0123abcd"], "mldsa65Seed": "seed" },
+                    "rawSettings": { "acceptProxyProtocol": false },
+                    "sockopt": { "tcpFastOpen": true }
+                }
+            },
+            {
+                "tag": "XHTTP_TLS",
+                "port": "2053",
+                "protocol": "trojan",
+                "settings": { "clients": [{ "password": "secret", "email": "a" }] },
+                "streamSettings": {
+                    "network": "xhttp",
+                    "security": "tls",
+                    "tlsSettings": { "alpn": ["h2", "http/1.1"], "certificates": [{ "certificateFile": "/cert.pem", "keyFile": "/key.pem" }] },
+                    "xhttpSettings": { "path": "/xh", "mode": "auto", "extra": { "xPaddingBytes": "100-1000" } }
+                }
+            },
+            { "tag": "VMESS", "port": 10086, "protocol": "vmess", "settings": {} },
+            { "tag": "TUNNEL", "port": 53, "protocol": "tunnel", "settings": { "address": "1.1.1.1", "port": 53 } }
+        ],
+        "outbounds": [
+            { "tag": "DIRECT", "protocol": "freedom", "settings": { "domainStrategy": "UseIPv4" } },
+            { "tag": "BLOCK", "protocol": "blackhole" },
+            { "tag": "WARP", "protocol": "wireguard", "settings": { "secretKey": "k" } }
+        ],
+        "routing": {
+            "domainStrategy": "IPIfNonMatch",
+            "rules": [
+                { "type": "field", "ip": ["geoip:private"], "outboundTag": "BLOCK" },
+                { "port": "6881-6889", "protocol": ["bittorrent"], "outboundTag": "BLOCK" }
+            ]
+        },
+        "policy": { "levels": { "0": { "handshake": 4, "connIdle": 300, "statsUserUplink": true } }, "system": { "statsInboundDownlink": true } },
+        "stats": {},
+        "observatory": { "subjectSelector": ["WARP"] }
+    })
+}
+
+#[test]
+fn round_trips_a_profile_config_losslessly() {
+    let raw = profile_config();
+    let config: XrayConfig = serde_json::from_value(raw.clone()).unwrap();
+
+    let reality = config.inbound("VLESS_REALITY").unwrap();
+    let XrayInboundProtocol::Vless(Some(vless)) = &reality.protocol else {
+        panic!("expected vless settings")
+    };
+    assert_eq!((vless.decryption.as_deref(), vless.clients.as_ref().map(Vec::len)), (Some("none"), Some(0)));
+    assert_eq!((reality.network(), reality.security(), reality.port.clone()), (Some("raw"), Some("reality"), Some(XrayPort::Number(443))));
+    let settings = reality.stream_settings.as_ref().unwrap().reality_settings.as_ref().unwrap();
+    assert_eq!(settings.destination(), Some(&XrayDestination::Address("www.example.com:443".into())));
+    assert_eq!(settings.extra.get("mldsa65Seed"), Some(&json!("seed")));
+
+    let xhttp = config.inbound("XHTTP_TLS").unwrap();
+    let XrayInboundProtocol::Trojan(Some(trojan)) = &xhttp.protocol else {
+        panic!("expected trojan settings")
+    };
+    assert_eq!(trojan.clients.as_ref().unwrap()[0].password.as_deref(), Some("secret"));
+    let stream = xhttp.stream_settings.as_ref().unwrap();
+    assert_eq!(stream.xhttp_settings.as_ref().and_then(|x| x.extra_params.as_ref()).and_then(|e| e.x_padding_bytes.as_ref()).and_then(|r| r.bounds()), Some((100, 1000)));
+    assert!(matches!(&config.inbound("VMESS").unwrap().protocol, XrayInboundProtocol::Vmess(Some(vmess)) if vmess.clients.is_none()));
+    assert_eq!(config.inbound("TUNNEL").unwrap().protocol.protocol(), "tunnel");
+
+    assert!(matches!(&config.outbound("DIRECT").unwrap().protocol, XrayOutboundProtocol::Freedom(Some(freedom)) if freedom.domain_strategy.as_deref() == Some("UseIPv4")));
+    assert!(matches!(&config.outbound("BLOCK").unwrap().protocol, XrayOutboundProtocol::Blackhole(None)));
+    assert!(matches!(&config.outbound("WARP").unwrap().protocol, XrayOutboundProtocol::Other { protocol, .. } if protocol == "wireguard"));
+    assert!(matches!(&config.dns.as_ref().unwrap().servers[1], XrayDnsServer::Server(server) if server.expect_ips == ["geoip:us"]));
+    assert_eq!(config.routing.as_ref().unwrap().rules[1].port, Some(XrayPort::Text("6881-6889".into())));
+    assert_eq!(config.policy.as_ref().unwrap().levels["0"].conn_idle, Some(300));
+    assert!(config.extra.contains_key("observatory"));
+
+    assert_eq!(serde_json::to_value(&config).unwrap(), raw);
+}
+
+#[tokio::test]
+async fn create_accepts_a_typed_config() {
+    let config: XrayConfig = serde_json::from_value(profile_config()).unwrap();
+    let profile = json!({
+        "uuid": Uuid::from_u128(1),
+        "name": "default",
+        "config": profile_config(),
+        "inbounds": [],
+        "nodes": [],
+        "createdAt": "2025-01-01T00:00:00Z",
+        "updatedAt": "2025-01-01T00:00:00Z"
+    });
+    let mut server = mockito::Server::new_async().await;
+    let create = server
+        .mock("POST", "/api/config-profiles")
+        .match_body(mockito::Matcher::PartialJson(json!({ "name": "default", "config": { "inbounds": [{ "tag": "VLESS_REALITY", "protocol": "vless" }] } })))
+        .with_status(201)
+        .with_body(json!({ "response": profile }).to_string())
+        .create_async()
+        .await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let created = client
+        .config_profiles
+        .create(CreateConfigProfileRequestDto {
+            name: "default".into(),
+            config: config.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(created.response.config, profile_config());
+    assert_eq!(created.response.xray_config().unwrap(), config);
+    create.assert_async().await;
+}
+
+#[tokio::test]
+async fn computed_config_stays_raw_when_the_typed_model_rejects_it() {
+    let profile = |config: Value| {
+        json!({ "response": {
+            "uuid": Uuid::from_u128(1), "name": "default", "config": config, "inbounds": [], "nodes": [],
+            "createdAt": "2025-01-01T00:00:00Z", "updatedAt": "2025-01-01T00:00:00Z"
+        } })
+        .to_string()
+    };
+    let mut server = mockito::Server::new_async().await;
+    let path = format!("/api/config-profiles/{}/computed-config", Uuid::from_u128(1));
+    server.mock("GET", path.as_str()).with_body(profile(profile_config())).expect(1).create_async().await;
+    server.mock("GET", path.as_str()).with_body(profile(json!({ "inbounds": "broken" }))).expect(1).create_async().await;
+
+    let client = RemnawaveApiClient::new(server.url(), None).unwrap();
+    let computed = client.config_profiles.get_computed_config_by_uuid(Uuid::from_u128(1)).await.unwrap();
+    assert_eq!(computed.response.xray_config().unwrap(), serde_json::from_value::<XrayConfig>(profile_config()).unwrap());
+    let broken = client.config_profiles.get_computed_config_by_uuid(Uuid::from_u128(1)).await.unwrap();
+    assert_eq!(broken.response.config, json!({ "inbounds": "broken" }));
+    assert!(broken.response.xray_config().is_err());
+}
@@ -0,0 +1,50 @@
+use remnawave::types::{MuxParams, SockoptParams, TcpFastOpen, XHttpExtraParams, XrayRange};
+use serde_json::json;
+
+#[test]
+fn xhttp_extra_params_round_trip_losslessly() {
+    let raw = json!({
+        "headers": { "X-Forwarded-For": "1.1.1.1" },
+        "xPaddingBytes": "100-1000",
+        "noGRPCHeader": true,
+        "scMaxEachPostBytes": 1000000,
+        "scMinPostsIntervalMs": { "from": 10, "to": 50 },
+        "xmux": { "maxConcurrency": "16-32", "hKeepAlivePeriod": 0, "futureKnob": 1 },
+        "downloadSettings": {
+            "address": "cdn.example.com",
+            "port": 443,
+            "network": "xhttp",
+            "security": "tls",
+            "tlsSettings": { "serverName": "cdn.example.com" },
+            "sockopt": { "tcpFastOpen": 256, "mark": 255 }
+        },
+        "mode": "packet-up"
+    });
+    let params: XHttpExtraParams = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(params.x_padding_bytes.as_ref().and_then(XrayRange::bounds), Some((100, 1000)));
+    assert_eq!(params.sc_min_posts_interval_ms.as_ref().and_then(XrayRange::bounds), Some((10, 50)));
+    assert_eq!(params.no_grpc_header, Some(true));
+    let xmux = params.xmux.as_ref().unwrap();
+    assert_eq!((xmux.max_concurrency.as_ref().and_then(XrayRange::bounds), xmux.extra.get("futureKnob")), (Some((16, 32)), Some(&json!(1))));
+    let download = params.download_settings.as_ref().unwrap();
+    assert_eq!(download.sockopt.as_ref().unwrap().tcp_fast_open, Some(TcpFastOpen::QueueLength(256)));
+    assert_eq!(params.extra.get("mode"), Some(&json!("packet-up")));
+    assert_eq!(serde_json::to_value(&params).unwrap(), raw);
+}
+
+#[test]
+fn mux_and_sockopt_keep_xray_key_names() {
+    let mux: MuxParams = serde_json::from_value(json!({ "enabled": true, "concurrency": -1, "xudpConcurrency": 16, "xudpProxyUDP443": "reject" })).unwrap();
+    assert_eq!((mux.enabled, mux.concurrency, mux.xudp_concurrency, mux.xudp_proxy_udp443.as_deref()), (Some(true), Some(-1), Some(16), Some("reject")));
+
+    let sockopt = SockoptParams {
+        tcp_fast_open: Some(TcpFastOpen::Enabled(true)),
+        dialer_proxy: Some("chain".into()),
+        tcp_congestion: Some("bbr".into()),
+        v6_only: Some(false),
+        tcp_mptcp: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(serde_json::to_value(&sockopt).unwrap(), json!({ "tcpFastOpen": true, "dialerProxy": "chain", "tcpcongestion": "bbr", "v6only": false, "tcpMptcp": true }));
+    assert_eq!(serde_json::to_value(SockoptParams::default()).unwrap(), json!({}));
+}